@@ -6,7 +6,7 @@ async fn main() {
     let activites = client.deals_activity().await.unwrap();
     let price_updates = activites.iter().filter_map(|x| match x {
         Activity::PriceUpdate(x) => Some(x),
-        Activity::RapUpdate(_) => None,
+        Activity::RapUpdate(_) | Activity::Other(_) => None,
     });
 
     println!("Price Updates Count: {}", price_updates.count());
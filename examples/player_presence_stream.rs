@@ -0,0 +1,19 @@
+use futures_util::StreamExt;
+use std::error::Error;
+use std::time::Duration;
+
+const USER_ID: u64 = 2207291;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let client = roli::ClientBuilder::new().build();
+    let stream = client.player_presence_stream(USER_ID, Duration::from_secs(60));
+    futures_util::pin_mut!(stream);
+
+    while let Some(change) = stream.next().await {
+        let change = change?;
+        println!("{:?} -> {:?}", change.previous, change.current);
+    }
+
+    Ok(())
+}
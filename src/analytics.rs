@@ -0,0 +1,414 @@
+use crate::deals::Activity;
+use crate::items::{ItemDetails, ItemInfoProvider};
+use crate::market_activity::{discount_percent, Sale};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// The amount of items included in [`MarketPulse::hottest_items`].
+pub const HOTTEST_ITEM_COUNT: usize = 5;
+
+/// The amount of recent sales per item an [`AnomalyDetector`] averages by default.
+pub const DEFAULT_ANOMALY_HISTORY: usize = 10;
+
+/// An item's activity inside of a [`MarketPulse`] window.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct HotItem {
+    /// The id of the item.
+    pub item_id: u64,
+    /// The amount of times the item sold.
+    pub sales: u64,
+    /// The amount of price updates (new listings) the item had on the deals feed.
+    pub price_updates: u64,
+}
+
+/// A summary of the market over a window of time, produced by [`market_pulse`].
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct MarketPulse {
+    /// The unix timestamp the window starts at, inclusive.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
+    pub window_start: u64,
+    /// The unix timestamp the window ends at, inclusive.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
+    pub window_end: u64,
+    /// The amount of sales in the window.
+    pub sale_count: u64,
+    /// The amount of sales per minute over the window.
+    pub sales_per_minute: f64,
+    /// The average percentage below the item's previous rap that items sold at.
+    ///
+    /// Sales of items without a previous rap are not counted. This is 0 if there
+    /// are no sales to average.
+    pub average_discount_percent: f64,
+    /// The sum of every sale price in the window.
+    pub total_traded_value: u64,
+    /// The amount of price updates on the deals feed in the window.
+    pub price_update_count: u64,
+    /// Up to [`HOTTEST_ITEM_COUNT`] items with the most sales and price updates,
+    /// from most to least active.
+    pub hottest_items: Vec<HotItem>,
+}
+
+/// The price an [`AnomalousSale`] was compared against.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum ReferencePrice {
+    /// The item's Rolimons value.
+    #[default]
+    Value,
+    /// The average of the item's recent normal sales.
+    RecentAverage,
+    /// The item's rap before the sale.
+    Rap,
+}
+
+/// A sale whose price is far from what the item is worth, as flagged by an
+/// [`AnomalyDetector`].
+///
+/// Sales far above value are the classic sign of someone trying to project an item.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct AnomalousSale {
+    /// The sale that was flagged.
+    pub sale: Sale,
+    /// The price the sale was compared against.
+    pub reference_price: u64,
+    /// Where `reference_price` came from.
+    pub reference: ReferencePrice,
+}
+
+/// Flags sales whose price is more than a configurable multiple above or below the
+/// item's value, or its recent average sale price if it is unvalued.
+///
+/// The detector remembers the last [`DEFAULT_ANOMALY_HISTORY`] normal sales of each
+/// item, so it should be kept around and fed every new sale, such as the ones from
+/// [`Client::sale_stream`](crate::Client::sale_stream). Flagged sales are not
+/// remembered, so a run of manipulated sales can not drag the average with it.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::analytics::AnomalyDetector;
+/// use roli::items::ItemDetailsTable;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+/// let mut detector = AnomalyDetector::new(3.0);
+///
+/// for anomaly in detector.check_all(&client.recent_sales().await?, &table) {
+///     println!(
+///         "{} sold at {:.1}x its {:?}",
+///         anomaly.sale.item_id,
+///         anomaly.ratio(),
+///         anomaly.reference
+///     );
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnomalyDetector {
+    multiple: f64,
+    history: usize,
+    recent_prices: HashMap<u64, VecDeque<u64>>,
+}
+
+impl AnomalousSale {
+    /// The sale price divided by the reference price.
+    pub fn ratio(&self) -> f64 {
+        self.sale.sale_price as f64 / self.reference_price as f64
+    }
+
+    /// Whether the sale was above the reference price, rather than below it.
+    pub fn is_above(&self) -> bool {
+        self.sale.sale_price > self.reference_price
+    }
+}
+
+impl AnomalyDetector {
+    /// Creates a detector that flags sales more than `multiple` times above, or
+    /// `multiple` times below, the reference price. `multiple` is raised to 1 if it
+    /// is lower.
+    pub fn new(multiple: f64) -> Self {
+        Self {
+            multiple: multiple.max(1.0),
+            history: DEFAULT_ANOMALY_HISTORY,
+            recent_prices: HashMap::new(),
+        }
+    }
+
+    /// Sets how many recent sales per item are averaged. Defaults to
+    /// [`DEFAULT_ANOMALY_HISTORY`], and is raised to 1 if it is lower.
+    pub fn set_history(mut self, history: usize) -> Self {
+        self.history = history.max(1);
+        self
+    }
+
+    /// Checks a sale, returning it as an [`AnomalousSale`] if it should be flagged.
+    ///
+    /// The sale is compared against the value of `item` if it is valued, then the
+    /// average of the item's recent normal sales, then the rap before the sale. Sales
+    /// without any of these are never flagged.
+    pub fn check(&mut self, sale: &Sale, item: Option<&ItemDetails>) -> Option<AnomalousSale> {
+        let recent_prices = self.recent_prices.entry(sale.item_id).or_default();
+
+        let (reference, reference_price) = match item {
+            Some(item) if item.valued && item.value > 0 => (ReferencePrice::Value, item.value),
+            _ if !recent_prices.is_empty() => (
+                ReferencePrice::RecentAverage,
+                recent_prices.iter().sum::<u64>() / recent_prices.len() as u64,
+            ),
+            _ if sale.old_rap > 0 => (ReferencePrice::Rap, sale.old_rap),
+            _ => (ReferencePrice::Rap, 0),
+        };
+
+        let price = sale.sale_price as f64;
+        let reference_f64 = reference_price as f64;
+
+        if reference_price > 0
+            && (price > reference_f64 * self.multiple || price * self.multiple < reference_f64)
+        {
+            return Some(AnomalousSale {
+                sale: sale.clone(),
+                reference_price,
+                reference,
+            });
+        }
+
+        recent_prices.push_back(sale.sale_price);
+
+        if recent_prices.len() > self.history {
+            recent_prices.pop_front();
+        }
+
+        None
+    }
+
+    /// Checks every sale in `sales`, oldest first, looking items up in `table`, and
+    /// returns the ones that should be flagged.
+    pub fn check_all(
+        &mut self,
+        sales: &[Sale],
+        table: &impl ItemInfoProvider,
+    ) -> Vec<AnomalousSale> {
+        let mut sales = sales.iter().collect::<Vec<_>>();
+        sales.sort_by_key(|sale| (sale.timestamp, sale.sale_id));
+
+        sales
+            .into_iter()
+            .filter_map(|sale| self.check(sale, table.item_details(sale.item_id).as_deref()))
+            .collect()
+    }
+}
+
+/// Summarizes `sales` (from [`Client::recent_sales`](crate::Client::recent_sales))
+/// and `activities` (from [`Client::deals_activity`](crate::Client::deals_activity))
+/// over the last `window` of time.
+///
+/// The window ends at the newest timestamp in either feed, so the same inputs
+/// always produce the same summary. Anything older than the window is ignored.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let sales = client.recent_sales().await?;
+/// let activities = client.deals_activity().await?;
+///
+/// let pulse = roli::analytics::market_pulse(&sales, &activities, Duration::from_secs(3600));
+///
+/// println!(
+///     "{:.1} sales/min, {:.1}% average discount, {} robux traded",
+///     pulse.sales_per_minute, pulse.average_discount_percent, pulse.total_traded_value
+/// );
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn market_pulse(sales: &[Sale], activities: &[Activity], window: Duration) -> MarketPulse {
+    let window_end = sales
+        .iter()
+        .map(|sale| sale.timestamp)
+        .chain(activities.iter().map(activity_timestamp))
+        .max()
+        .unwrap_or(0);
+    let window_start = window_end.saturating_sub(window.as_secs());
+
+    let mut pulse = MarketPulse {
+        window_start,
+        window_end,
+        ..Default::default()
+    };
+
+    let mut hot_items = HashMap::<u64, HotItem>::new();
+    let mut discount_sum = 0.0;
+    let mut discount_count = 0;
+
+    for sale in sales.iter().filter(|sale| sale.timestamp >= window_start) {
+        pulse.sale_count += 1;
+        pulse.total_traded_value += sale.sale_price;
+
+        if sale.old_rap > 0 {
+            discount_sum += discount_percent(sale.sale_price, sale.old_rap);
+            discount_count += 1;
+        }
+
+        hot_item(&mut hot_items, sale.item_id).sales += 1;
+    }
+
+    for activity in activities.iter() {
+        if let Activity::PriceUpdate(price_update) = activity {
+            if price_update.timestamp >= window_start {
+                pulse.price_update_count += 1;
+                hot_item(&mut hot_items, price_update.item_id).price_updates += 1;
+            }
+        }
+    }
+
+    if window.as_secs() > 0 {
+        pulse.sales_per_minute = pulse.sale_count as f64 / (window.as_secs() as f64 / 60.0);
+    }
+
+    if discount_count > 0 {
+        pulse.average_discount_percent = discount_sum / discount_count as f64;
+    }
+
+    let mut hottest_items = hot_items.into_values().collect::<Vec<_>>();
+    hottest_items.sort_by(|a, b| {
+        (b.sales + b.price_updates)
+            .cmp(&(a.sales + a.price_updates))
+            .then(a.item_id.cmp(&b.item_id))
+    });
+    hottest_items.truncate(HOTTEST_ITEM_COUNT);
+    pulse.hottest_items = hottest_items;
+
+    pulse
+}
+
+fn activity_timestamp(activity: &Activity) -> u64 {
+    match activity {
+        Activity::PriceUpdate(x) => x.timestamp,
+        Activity::RapUpdate(x) => x.timestamp,
+        Activity::Other(x) => x.timestamp,
+    }
+}
+
+fn hot_item(hot_items: &mut HashMap<u64, HotItem>, item_id: u64) -> &mut HotItem {
+    hot_items.entry(item_id).or_insert(HotItem {
+        item_id,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deals::{PriceUpdate, RapUpdate};
+
+    fn sale(item_id: u64, old_rap: u64, sale_price: u64, timestamp: u64) -> Sale {
+        Sale {
+            item_id,
+            old_rap,
+            sale_price,
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_anomaly_detector() {
+        let valued = ItemDetails {
+            item_id: 1,
+            valued: true,
+            value: 1000,
+            ..Default::default()
+        };
+
+        let mut detector = AnomalyDetector::new(3.0).set_history(2);
+
+        let anomaly = detector
+            .check(&sale(1, 500, 5000, 1), Some(&valued))
+            .unwrap();
+        assert_eq!(anomaly.reference, ReferencePrice::Value);
+        assert!(anomaly.is_above());
+        assert_eq!(anomaly.ratio(), 5.0);
+        assert!(detector
+            .check(&sale(1, 500, 900, 2), Some(&valued))
+            .is_none());
+
+        // Unvalued: falls back to the rap, then to the recent average.
+        assert!(detector.check(&sale(2, 100, 250, 1), None).is_none());
+        assert!(detector.check(&sale(2, 250, 350, 2), None).is_none());
+
+        let anomaly = detector.check(&sale(2, 300, 50, 3), None).unwrap();
+        assert_eq!(anomaly.reference, ReferencePrice::RecentAverage);
+        assert_eq!(anomaly.reference_price, 300);
+        assert!(!anomaly.is_above());
+
+        assert!(detector.check(&sale(3, 0, 50, 1), None).is_none());
+    }
+
+    #[test]
+    fn test_market_pulse() {
+        let sales = vec![
+            sale(1, 1000, 800, 1000),
+            sale(1, 1000, 900, 1100),
+            sale(2, 0, 500, 1150),
+            // Outside of the window.
+            sale(3, 1000, 100, 100),
+        ];
+
+        let activities = vec![
+            Activity::PriceUpdate(PriceUpdate {
+                timestamp: 1200,
+                item_id: 2,
+                price: 400,
+            }),
+            Activity::RapUpdate(RapUpdate {
+                timestamp: 1190,
+                item_id: 1,
+                rap: 950,
+            }),
+        ];
+
+        let pulse = market_pulse(&sales, &activities, Duration::from_secs(600));
+
+        assert_eq!(pulse.window_start, 600);
+        assert_eq!(pulse.window_end, 1200);
+        assert_eq!(pulse.sale_count, 3);
+        assert!((pulse.sales_per_minute - 0.3).abs() < 1e-9);
+        assert!((pulse.average_discount_percent - 15.0).abs() < 1e-9);
+        assert_eq!(pulse.total_traded_value, 2200);
+        assert_eq!(pulse.price_update_count, 1);
+        assert_eq!(
+            pulse.hottest_items,
+            vec![
+                HotItem {
+                    item_id: 1,
+                    sales: 2,
+                    price_updates: 0
+                },
+                HotItem {
+                    item_id: 2,
+                    sales: 1,
+                    price_updates: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_market_pulse_empty() {
+        let pulse = market_pulse(&[], &[], Duration::ZERO);
+        assert_eq!(pulse, MarketPulse::default());
+    }
+}
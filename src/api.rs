@@ -0,0 +1,133 @@
+use crate::deals::Activity;
+use crate::games::Game;
+use crate::groups::GroupSearchResult;
+use crate::items::ItemDetails;
+use crate::market_activity::Sale;
+use crate::players::{PlayerProfile, PlayerSearchResult};
+use crate::trade_ads::{CreateTradeAdParams, TradeAd};
+use crate::{Client, RoliError};
+use std::future::Future;
+
+/// The Rolimons api, as exposed by [`Client`].
+///
+/// Code that only needs to call endpoints can be generic over this trait so that
+/// a mock (such as `roli::testing::MockClient`) can be used in tests instead of
+/// a real [`Client`].
+///
+/// # Example
+/// ```no_run
+/// use roli::api::RoliApi;
+/// use roli::RoliError;
+///
+/// async fn total_value(api: &impl RoliApi) -> Result<u64, RoliError> {
+///     let items = api.all_item_details().await?;
+///     Ok(items.iter().map(|x| x.value_or_rap()).sum())
+/// }
+/// ```
+pub trait RoliApi: Send + Sync {
+    /// See [`Client::all_item_details`].
+    fn all_item_details(&self) -> impl Future<Output = Result<Vec<ItemDetails>, RoliError>> + Send;
+
+    /// See [`Client::deals_activity`].
+    fn deals_activity(&self) -> impl Future<Output = Result<Vec<Activity>, RoliError>> + Send;
+
+    /// See [`Client::recent_trade_ads`].
+    fn recent_trade_ads(&self) -> impl Future<Output = Result<Vec<TradeAd>, RoliError>> + Send;
+
+    /// See [`Client::create_trade_ad`].
+    fn create_trade_ad(
+        &self,
+        create_trade_ad_params: CreateTradeAdParams,
+    ) -> impl Future<Output = Result<(), RoliError>> + Send;
+
+    /// See [`Client::player_search`].
+    fn player_search(
+        &self,
+        username: &str,
+    ) -> impl Future<Output = Result<Vec<PlayerSearchResult>, RoliError>> + Send;
+
+    /// See [`Client::player_profile`].
+    fn player_profile(
+        &self,
+        user_id: u64,
+    ) -> impl Future<Output = Result<PlayerProfile, RoliError>> + Send;
+
+    /// See [`Client::player_profile_cached`].
+    ///
+    /// Defaults to [`RoliApi::player_profile`], for implementations without a cache.
+    fn player_profile_cached(
+        &self,
+        user_id: u64,
+    ) -> impl Future<Output = Result<PlayerProfile, RoliError>> + Send {
+        self.player_profile(user_id)
+    }
+
+    /// See [`Client::games_list`].
+    fn games_list(&self) -> impl Future<Output = Result<Vec<Game>, RoliError>> + Send;
+
+    /// See [`Client::group_search`].
+    fn group_search(
+        &self,
+        group_name: &str,
+    ) -> impl Future<Output = Result<Vec<GroupSearchResult>, RoliError>> + Send;
+
+    /// See [`Client::recent_sales`].
+    fn recent_sales(&self) -> impl Future<Output = Result<Vec<Sale>, RoliError>> + Send;
+}
+
+impl RoliApi for Client {
+    fn all_item_details(&self) -> impl Future<Output = Result<Vec<ItemDetails>, RoliError>> + Send {
+        Client::all_item_details(self)
+    }
+
+    fn deals_activity(&self) -> impl Future<Output = Result<Vec<Activity>, RoliError>> + Send {
+        Client::deals_activity(self)
+    }
+
+    fn recent_trade_ads(&self) -> impl Future<Output = Result<Vec<TradeAd>, RoliError>> + Send {
+        Client::recent_trade_ads(self)
+    }
+
+    fn create_trade_ad(
+        &self,
+        create_trade_ad_params: CreateTradeAdParams,
+    ) -> impl Future<Output = Result<(), RoliError>> + Send {
+        Client::create_trade_ad(self, create_trade_ad_params)
+    }
+
+    fn player_search(
+        &self,
+        username: &str,
+    ) -> impl Future<Output = Result<Vec<PlayerSearchResult>, RoliError>> + Send {
+        Client::player_search(self, username)
+    }
+
+    fn player_profile(
+        &self,
+        user_id: u64,
+    ) -> impl Future<Output = Result<PlayerProfile, RoliError>> + Send {
+        Client::player_profile(self, user_id)
+    }
+
+    fn player_profile_cached(
+        &self,
+        user_id: u64,
+    ) -> impl Future<Output = Result<PlayerProfile, RoliError>> + Send {
+        Client::player_profile_cached(self, user_id)
+    }
+
+    fn games_list(&self) -> impl Future<Output = Result<Vec<Game>, RoliError>> + Send {
+        Client::games_list(self)
+    }
+
+    fn group_search(
+        &self,
+        group_name: &str,
+    ) -> impl Future<Output = Result<Vec<GroupSearchResult>, RoliError>> + Send {
+        Client::group_search(self, group_name)
+    }
+
+    fn recent_sales(&self) -> impl Future<Output = Result<Vec<Sale>, RoliError>> + Send {
+        Client::recent_sales(self)
+    }
+}
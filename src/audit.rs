@@ -0,0 +1,665 @@
+#[cfg(feature = "async-rt")]
+use crate::util::SeenWindow;
+#[cfg(feature = "async-rt")]
+use crate::{items, Client, ClientBuilder, Event};
+use crate::{Endpoint, RoliError};
+use bytes::Bytes;
+#[cfg(feature = "async-rt")]
+use futures_util::stream::{self, Stream};
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// The default amount of entries kept in memory by an [`AuditLog`].
+pub const DEFAULT_AUDIT_LOG_CAPACITY: usize = 1000;
+
+/// The default amount of bytes of each response body kept by an [`AuditLog`].
+pub const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// The amount of deals activity [`replay_events`] remembers, several times the size of
+/// a deals activity response.
+#[cfg(feature = "async-rt")]
+const ACTIVITY_SEEN_CAPACITY: usize = 1000;
+
+/// How long [`replay_events`] remembers deals activity for.
+#[cfg(feature = "async-rt")]
+const ACTIVITY_SEEN_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A record of a single request made by a [`Client`](crate::Client).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// The endpoint the request was made to.
+    pub endpoint: Endpoint,
+    /// The http method of the request.
+    pub method: String,
+    /// The url of the request.
+    pub url: String,
+    /// The unix timestamp the request was sent at.
+    pub timestamp: u64,
+    /// How long the request took, in milliseconds.
+    pub duration_ms: u64,
+    /// The status code of the response, or `None` if no response was received.
+    pub status: Option<u16>,
+    /// The body of the response, decoded lossily as utf-8 and cut to the log's
+    /// `max_body_bytes`.
+    pub body: String,
+    /// Whether `body` was cut short. Truncated responses can not be replayed.
+    pub truncated: bool,
+    /// The error that stopped a response from being received, if any.
+    pub error: Option<String>,
+}
+
+/// A bounded log of every request a [`Client`](crate::Client) makes, set with
+/// [`ClientBuilder::set_audit_log`](crate::ClientBuilder::set_audit_log).
+///
+/// Only the method and url of a request are recorded, so the `roli_verification`
+/// cookie never ends up in the log. Once the log holds `capacity` entries, the
+/// oldest entry is dropped for each new one. A log created with
+/// [`AuditLog::with_file`] also appends every entry to a file as a json line.
+///
+/// Clones share the same entries, so a clone can be kept to read the log while
+/// the client writes to it.
+///
+/// Entries can be exported with [`AuditLog::to_json_lines`] and replayed with
+/// [`ClientBuilder::set_replay`](crate::ClientBuilder::set_replay), which makes the
+/// client answer requests from the log instead of Rolimons.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::audit::AuditLog;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let audit_log = AuditLog::default().with_file("roli-audit.jsonl")?;
+/// let client = roli::ClientBuilder::new()
+///     .set_audit_log(audit_log)
+///     .build();
+///
+/// client.recent_sales().await?;
+///
+/// for entry in client.audit_log().unwrap().entries() {
+///     println!("{} {} -> {:?} in {}ms", entry.method, entry.url, entry.status, entry.duration_ms);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    capacity: usize,
+    max_body_bytes: usize,
+    inner: Arc<Mutex<AuditLogInner>>,
+}
+
+#[derive(Debug, Default)]
+struct AuditLogInner {
+    entries: VecDeque<AuditEntry>,
+    file: Option<File>,
+}
+
+/// How fast [`replay_events`] replays a log.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ReplaySpeed {
+    /// Emits every event as soon as possible.
+    #[default]
+    Instant,
+    /// Waits between responses for as long as was waited when they were recorded.
+    Original,
+    /// Waits between responses for the recorded time divided by the multiplier, so a
+    /// multiplier of 60 replays an hour in a minute.
+    Multiplier(f64),
+}
+
+/// Responses recorded in an audit log, served in place of real requests.
+#[derive(Debug, Default)]
+pub(crate) struct Replay {
+    responses: Mutex<HashMap<String, VecDeque<(u16, Bytes)>>>,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_AUDIT_LOG_CAPACITY, DEFAULT_MAX_BODY_BYTES)
+    }
+}
+
+impl AuditLog {
+    /// Creates an in-memory log that keeps the last `capacity` requests, with at most
+    /// `max_body_bytes` of each response body.
+    pub fn new(capacity: usize, max_body_bytes: usize) -> Self {
+        Self {
+            capacity,
+            max_body_bytes,
+            inner: Arc::new(Mutex::new(AuditLogInner::default())),
+        }
+    }
+
+    /// Also appends every entry to the file at `path` as a json line, creating the
+    /// file if it does not exist.
+    ///
+    /// Entries are still kept in memory. Failing to write an entry to the file does
+    /// not fail the request.
+    pub fn with_file(self, path: impl AsRef<Path>) -> Result<Self, RoliError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(RoliError::IoError)?;
+
+        self.inner().file = Some(file);
+
+        Ok(self)
+    }
+
+    /// Returns the entries in the log, oldest first.
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.inner().entries.iter().cloned().collect()
+    }
+
+    /// Returns the amount of entries in the log.
+    pub fn len(&self) -> usize {
+        self.inner().entries.len()
+    }
+
+    /// Returns whether the log is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner().entries.is_empty()
+    }
+
+    /// Removes every entry from memory. Entries already written to a file are kept.
+    pub fn clear(&self) {
+        self.inner().entries.clear();
+    }
+
+    /// Returns the entries in the log as json lines, oldest first, in the same format
+    /// used by [`AuditLog::with_file`].
+    pub fn to_json_lines(&self) -> String {
+        self.inner()
+            .entries
+            .iter()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// Parses entries from json lines, such as the contents of a file written by
+    /// [`AuditLog::with_file`]. Blank lines are skipped.
+    ///
+    /// Returns [`RoliError::MalformedResponse`] if a line is not a valid entry.
+    pub fn parse_json_lines(json_lines: &str) -> Result<Vec<AuditEntry>, RoliError> {
+        json_lines
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|_| RoliError::MalformedResponse))
+            .collect()
+    }
+
+    /// Writes the entries in the log to the file at `path` as json lines, replacing the
+    /// file if it exists. With the `zstd` feature, the file is compressed with zstd.
+    ///
+    /// Unlike [`AuditLog::with_file`], this writes the log once, which suits archiving
+    /// a log before it is [cleared](AuditLog::clear).
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RoliError> {
+        let json_lines = self.to_json_lines();
+        std::fs::write(path, crate::compression::compress(json_lines.as_bytes())?)
+            .map_err(RoliError::IoError)
+    }
+
+    /// Reads entries from a file written by [`AuditLog::save`] or [`AuditLog::with_file`],
+    /// decompressing it first if it was compressed with zstd.
+    ///
+    /// Returns [`RoliError::MalformedResponse`] if a line is not a valid entry, or if
+    /// the file is compressed and the `zstd` feature is disabled.
+    pub fn read_file(path: impl AsRef<Path>) -> Result<Vec<AuditEntry>, RoliError> {
+        let bytes = std::fs::read(path).map_err(RoliError::IoError)?;
+        let bytes = crate::compression::decompress(&bytes)?;
+        let json_lines = std::str::from_utf8(&bytes).map_err(|_| RoliError::MalformedResponse)?;
+
+        Self::parse_json_lines(json_lines)
+    }
+
+    pub(crate) fn record(
+        &self,
+        endpoint: Endpoint,
+        (method, url): (String, String),
+        timestamp: u64,
+        duration: Duration,
+        result: &Result<(u16, Bytes), RoliError>,
+    ) {
+        let mut entry = AuditEntry {
+            endpoint,
+            method,
+            url,
+            timestamp,
+            duration_ms: duration.as_millis() as u64,
+            status: None,
+            body: String::new(),
+            truncated: false,
+            error: None,
+        };
+
+        match result {
+            Ok((status, body)) => {
+                let kept = &body[..body.len().min(self.max_body_bytes)];
+
+                entry.status = Some(*status);
+                entry.body = String::from_utf8_lossy(kept).into_owned();
+                entry.truncated = kept.len() < body.len();
+            }
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+
+        let mut inner = self.inner();
+
+        if let Some(file) = &mut inner.file {
+            if let Ok(line) = serde_json::to_string(&entry) {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+
+        if inner.entries.len() == self.capacity {
+            inner.entries.pop_front();
+        }
+
+        inner.entries.push_back(entry);
+    }
+
+    fn inner(&self) -> MutexGuard<'_, AuditLogInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Replay {
+    pub(crate) fn new(entries: Vec<AuditEntry>) -> Self {
+        let mut responses = HashMap::<String, VecDeque<(u16, Bytes)>>::new();
+
+        for entry in entries {
+            if let Some(status) = entry.status {
+                responses
+                    .entry(key(&entry.method, &entry.url))
+                    .or_default()
+                    .push_back((status, Bytes::from(entry.body)));
+            }
+        }
+
+        Self {
+            responses: Mutex::new(responses),
+        }
+    }
+
+    /// Returns the next recorded response for a request, in the order they were
+    /// recorded. Returns [`RoliError::UnidentifiedStatusCode`] with a status code of
+    /// 404 once there are none left.
+    pub(crate) fn next(&self, (method, url): &(String, String)) -> Result<(u16, Bytes), RoliError> {
+        self.responses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_mut(&key(method, url))
+            .and_then(|responses| responses.pop_front())
+            .ok_or(RoliError::UnidentifiedStatusCode(404))
+    }
+}
+
+/// Returns the method and url of a request, if it can be built.
+pub(crate) fn target(request: &RequestBuilder) -> Option<(String, String)> {
+    let request = request.try_clone()?.build().ok()?;
+    Some((request.method().to_string(), request.url().to_string()))
+}
+
+/// Replays the responses in an audit log as a stream of [`Event`]s, without making any
+/// requests, so strategies can be tested offline against recorded data.
+///
+/// Responses are replayed in the order they were recorded, waiting between them as set
+/// by `speed`. Each response only emits what was not in an earlier one, the same as the
+/// polling streams: sales and trade ads with a higher id than the last one emitted, deals
+/// activity that is not in a bounded window of recently seen activity, and an
+/// [`Event::ItemChange`] for every item that changed between two item details
+/// responses. Responses of other endpoints are skipped. Recorded errors (such as a 429)
+/// are yielded as errors.
+///
+/// The stream can be passed straight to [`handler::run`](crate::handler::run).
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use futures_util::StreamExt;
+/// use roli::audit::{self, AuditLog, ReplaySpeed};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let entries = AuditLog::read_file("roli-audit.jsonl")?;
+/// let events = audit::replay_events(entries, ReplaySpeed::Multiplier(60.0));
+/// futures_util::pin_mut!(events);
+///
+/// while let Some(event) = events.next().await {
+///     println!("{:?}", event?);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async-rt")]
+pub fn replay_events(
+    entries: Vec<AuditEntry>,
+    speed: ReplaySpeed,
+) -> impl Stream<Item = Result<Event, RoliError>> + Send {
+    let mut entries = entries
+        .into_iter()
+        .filter(|entry| entry.status.is_some() && !entry.truncated)
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.timestamp);
+
+    let state = ReplayState {
+        client: ClientBuilder::new().set_replay(entries.clone()).build(),
+        entries: entries.into(),
+        speed,
+        previous_timestamp: None,
+        pending: VecDeque::new(),
+        last_sale_id: None,
+        last_trade_id: None,
+        seen_activities: SeenWindow::new(ACTIVITY_SEEN_CAPACITY, ACTIVITY_SEEN_MAX_AGE),
+        previous_items: None,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((Ok(event), state));
+            }
+
+            let entry = state.entries.pop_front()?;
+
+            if let Some(delay) = state.delay(entry.timestamp) {
+                tokio::time::sleep(delay).await;
+            }
+
+            if let Err(e) = state.replay(entry.endpoint).await {
+                return Some((Err(e), state));
+            }
+        }
+    })
+}
+
+#[cfg(feature = "async-rt")]
+struct ReplayState {
+    client: Client,
+    entries: VecDeque<AuditEntry>,
+    speed: ReplaySpeed,
+    previous_timestamp: Option<u64>,
+    pending: VecDeque<Event>,
+    last_sale_id: Option<u64>,
+    last_trade_id: Option<u64>,
+    seen_activities: SeenWindow<crate::deals::Activity>,
+    previous_items: Option<items::ItemDetailsTable>,
+}
+
+#[cfg(feature = "async-rt")]
+impl ReplayState {
+    /// Returns how long to wait before replaying a response recorded at `timestamp`.
+    fn delay(&mut self, timestamp: u64) -> Option<Duration> {
+        let previous = self.previous_timestamp.replace(timestamp)?;
+        let gap = Duration::from_secs(timestamp.saturating_sub(previous));
+
+        match self.speed {
+            ReplaySpeed::Instant => None,
+            ReplaySpeed::Original => Some(gap),
+            ReplaySpeed::Multiplier(multiplier) if multiplier > 0.0 => {
+                Some(gap.div_f64(multiplier))
+            }
+            ReplaySpeed::Multiplier(_) => None,
+        }
+    }
+
+    /// Parses the next recorded response of `endpoint`, queueing any new events.
+    async fn replay(&mut self, endpoint: Endpoint) -> Result<(), RoliError> {
+        match endpoint {
+            Endpoint::RecentSales => {
+                let mut sales = self.client.recent_sales().await?;
+                sales.sort_by_key(|sale| sale.sale_id);
+
+                for sale in sales {
+                    if self.last_sale_id.is_none_or(|last| sale.sale_id > last) {
+                        self.last_sale_id = Some(sale.sale_id);
+                        self.pending.push_back(Event::Sale(sale));
+                    }
+                }
+            }
+            Endpoint::RecentTradeAds => {
+                let mut trade_ads = self.client.recent_trade_ads().await?;
+                trade_ads.sort_by_key(|trade_ad| trade_ad.trade_id);
+
+                for trade_ad in trade_ads {
+                    if self
+                        .last_trade_id
+                        .is_none_or(|last| trade_ad.trade_id > last)
+                    {
+                        self.last_trade_id = Some(trade_ad.trade_id);
+                        self.pending.push_back(Event::TradeAd(trade_ad));
+                    }
+                }
+            }
+            Endpoint::DealsActivity => {
+                for activity in self.client.deals_activity().await? {
+                    if self.seen_activities.insert(activity) {
+                        self.pending.push_back(Event::from(activity));
+                    }
+                }
+            }
+            Endpoint::AllItemDetails => {
+                let table = items::ItemDetailsTable::new(self.client.all_item_details().await?);
+
+                if let Some(previous) = &self.previous_items {
+                    self.pending.extend(
+                        items::diff(previous, &table)
+                            .into_iter()
+                            .map(Event::ItemChange),
+                    );
+                }
+
+                self.previous_items = Some(table);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+fn key(method: &str, url: &str) -> String {
+    format!("{} {}", method, url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> (String, String) {
+        ("GET".to_string(), "https://example.com/a".to_string())
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let audit_log = AuditLog::new(2, 4);
+
+        for body in ["[1]", "[1, 2]", "[3]"] {
+            audit_log.record(
+                Endpoint::RecentSales,
+                target(),
+                1,
+                Duration::from_millis(5),
+                &Ok((200, Bytes::from(body))),
+            );
+        }
+        audit_log.record(
+            Endpoint::RecentSales,
+            target(),
+            2,
+            Duration::ZERO,
+            &Err(RoliError::MalformedResponse),
+        );
+
+        let entries = audit_log.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].body, "[3]");
+        assert!(!entries[0].truncated);
+        assert_eq!(entries[1].status, None);
+        assert_eq!(entries[1].error.as_deref(), Some("Malformed Response"));
+
+        let parsed = AuditLog::parse_json_lines(&audit_log.to_json_lines()).unwrap();
+        assert_eq!(parsed, entries);
+
+        let replay = Replay::new(parsed);
+        assert_eq!(replay.next(&target()).unwrap(), (200, Bytes::from("[3]")));
+        assert!(matches!(
+            replay.next(&target()),
+            Err(RoliError::UnidentifiedStatusCode(404))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_client_replay() {
+        let entry = AuditEntry {
+            endpoint: Endpoint::RecentSales,
+            method: "GET".to_string(),
+            url: "https://www.rolimons.com/api/activity".to_string(),
+            timestamp: 1,
+            duration_ms: 1,
+            status: Some(200),
+            body: r#"{"success": true, "activities": [], "activities_count": 0}"#.to_string(),
+            truncated: false,
+            error: None,
+        };
+
+        let client = crate::ClientBuilder::new().set_replay(vec![entry]).build();
+
+        assert!(client.recent_sales().await.unwrap().is_empty());
+        assert!(matches!(
+            client.recent_sales().await,
+            Err(RoliError::UnidentifiedStatusCode(404))
+        ));
+    }
+
+    #[test]
+    fn test_save_and_read_file() {
+        let path = std::env::temp_dir().join(format!("roli-audit-{}.jsonl", std::process::id()));
+        let audit_log = AuditLog::default();
+        audit_log.record(
+            Endpoint::RecentSales,
+            target(),
+            1,
+            Duration::ZERO,
+            &Ok((200, Bytes::from("[]"))),
+        );
+
+        audit_log.save(&path).unwrap();
+        assert_eq!(AuditLog::read_file(&path).unwrap(), audit_log.entries());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "async-rt")]
+    #[tokio::test]
+    async fn test_replay_events() {
+        use crate::Event;
+        use futures_util::StreamExt;
+
+        let entry = |timestamp, body: &str| AuditEntry {
+            endpoint: Endpoint::RecentSales,
+            method: "GET".to_string(),
+            url: "https://www.rolimons.com/api/activity".to_string(),
+            timestamp,
+            duration_ms: 1,
+            status: Some(200),
+            body: body.to_string(),
+            truncated: false,
+            error: None,
+        };
+
+        // The second response repeats the first sale, which is only emitted once.
+        let entries = vec![
+            entry(
+                2,
+                r#"{"success": true, "activities": [[1, 1, "2", 100, 90, 6], [2, 1, "3", 100, 90, 7]], "activities_count": 2}"#,
+            ),
+            entry(
+                1,
+                r#"{"success": true, "activities": [[1, 1, "2", 100, 90, 6]], "activities_count": 1}"#,
+            ),
+        ];
+
+        let sale_ids = replay_events(entries, ReplaySpeed::Instant)
+            .map(|event| match event.unwrap() {
+                Event::Sale(sale) => sale.sale_id,
+                event => panic!("unexpected event {:?}", event),
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(sale_ids, vec![6, 7]);
+    }
+
+    #[cfg(feature = "async-rt")]
+    #[tokio::test]
+    async fn test_replay_deals_activity() {
+        use crate::Event;
+        use futures_util::StreamExt;
+
+        let entry = |timestamp, body: &str| AuditEntry {
+            endpoint: Endpoint::DealsActivity,
+            method: "GET".to_string(),
+            url: "https://www.rolimons.com/api/activity2".to_string(),
+            timestamp,
+            duration_ms: 1,
+            status: Some(200),
+            body: body.to_string(),
+            truncated: false,
+            error: None,
+        };
+
+        // The second response repeats the first price update, which is only emitted once.
+        let entries = vec![
+            entry(
+                1,
+                r#"{"success": true, "activities": [[100, 0, "5", 0, 50]]}"#,
+            ),
+            entry(
+                2,
+                r#"{"success": true, "activities": [[100, 0, "5", 0, 50], [101, 1, "5", 0, 60]]}"#,
+            ),
+        ];
+
+        let events = replay_events(entries, ReplaySpeed::Instant)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], Event::PriceUpdate(_)));
+        assert!(matches!(events[1], Event::RapUpdate(_)));
+    }
+
+    #[test]
+    fn test_truncated_body() {
+        let audit_log = AuditLog::new(10, 4);
+        audit_log.record(
+            Endpoint::RecentSales,
+            target(),
+            1,
+            Duration::ZERO,
+            &Ok((200, Bytes::from("[1, 2]"))),
+        );
+
+        let entry = &audit_log.entries()[0];
+        assert_eq!(entry.body, "[1, ");
+        assert!(entry.truncated);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{Client, RoliError};
+use reqwest::{header, Method, RequestBuilder};
+
+impl Client {
+    /// Starts a request to an endpoint that requires authentication, with the
+    /// `_RoliVerification` cookie and the headers Rolimons expects from a browser.
+    ///
+    /// Every authenticated endpoint should be built from this so that cookie
+    /// handling only lives in one place.
+    ///
+    /// Returns [`RoliError::RoliVerificationNotSet`] if the client has no
+    /// roli_verification, and [`RoliError::RoliVerificationContainsInvalidCharacters`]
+    /// if it can't be put in a header.
+    pub(crate) fn authenticated_request(
+        &self,
+        method: Method,
+        url: &str,
+    ) -> Result<RequestBuilder, RoliError> {
+        let mut headers = header::HeaderMap::new();
+
+        // Authenticated endpoints are only accepted from what looks like a browser,
+        // unless the client was explicitly set to declare itself as a bot.
+        headers.insert(header::USER_AGENT, self.user_agent());
+
+        headers.insert(
+            header::CONNECTION,
+            header::HeaderValue::from_static("keep-alive"),
+        );
+
+        headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/json;charset=utf-8"),
+        );
+
+        headers.insert(header::COOKIE, self.roli_verification_cookie()?);
+
+        Ok(self.reqwest_client.request(method, url).headers(headers))
+    }
+
+    fn roli_verification_cookie(&self) -> Result<header::HeaderValue, RoliError> {
+        match &self.roli_verification {
+            Some(x) => roli_verification_cookie(x),
+            None => Err(RoliError::RoliVerificationNotSet),
+        }
+    }
+}
+
+/// Builds the `_RoliVerification` cookie header, returning
+/// [`RoliError::RoliVerificationContainsInvalidCharacters`] if it can't be put in a header.
+pub(crate) fn roli_verification_cookie(
+    roli_verification: &str,
+) -> Result<header::HeaderValue, RoliError> {
+    match header::HeaderValue::from_str(&format!("_RoliVerification={}", roli_verification)) {
+        Ok(x) => Ok(x),
+        Err(_) => Err(RoliError::RoliVerificationContainsInvalidCharacters),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClientBuilder, RoliError};
+    use reqwest::{header, Method};
+
+    #[test]
+    fn test_authenticated_request() {
+        let client = ClientBuilder::new().build();
+
+        assert!(matches!(
+            client.authenticated_request(Method::POST, "https://www.rolimons.com"),
+            Err(RoliError::RoliVerificationNotSet)
+        ));
+
+        let client = ClientBuilder::new()
+            .set_roli_verification("bad\nvalue".to_string())
+            .build();
+
+        assert!(matches!(
+            client.authenticated_request(Method::POST, "https://www.rolimons.com"),
+            Err(RoliError::RoliVerificationContainsInvalidCharacters)
+        ));
+
+        let client = ClientBuilder::new()
+            .set_roli_verification("abc".to_string())
+            .build();
+
+        let request = client
+            .authenticated_request(Method::POST, "https://www.rolimons.com")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(request.headers()[header::COOKIE], "_RoliVerification=abc");
+    }
+}
@@ -0,0 +1,338 @@
+use crate::deals::{Activity, Deal, DealEvaluator, PriceUpdate, RapUpdate};
+use crate::items::ItemInfoProvider;
+use crate::limits;
+use crate::market_activity::Sale;
+use crate::Event;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+/// What a [`Strategy`] does with a deal.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum Decision {
+    /// Buy the item at the listed price.
+    Buy,
+    /// Ignore the deal.
+    #[default]
+    Skip,
+}
+
+/// A deal strategy that can be backtested with [`run`].
+///
+/// Closures taking a [`Deal`] and returning a [`Decision`] implement this trait.
+pub trait Strategy {
+    /// Called for every listing of an item in the item details table, in the order
+    /// they happened. The deal's rap is the most recent rap seen up to that point.
+    fn on_deal(&mut self, deal: &Deal) -> Decision;
+}
+
+impl<F> Strategy for F
+where
+    F: FnMut(&Deal) -> Decision,
+{
+    fn on_deal(&mut self, deal: &Deal) -> Decision {
+        self(deal)
+    }
+}
+
+/// A hypothetical purchase made by a [`Strategy`] during a backtest.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Trade {
+    /// The deal that was bought.
+    pub deal: Deal,
+    /// The price the item was resold at, before tax.
+    pub exit_price: u64,
+    /// The timestamp of the sale the item was resold into, or `None` if no sale of the
+    /// item happened after it was bought, in which case it is marked at its latest rap.
+    pub exit_timestamp: Option<u64>,
+}
+
+/// The result of [`run`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacktestReport {
+    /// Every deal the strategy bought, in the order they were bought.
+    pub trades: Vec<Trade>,
+    /// The amount of deals the strategy skipped.
+    pub skipped: usize,
+}
+
+impl Trade {
+    /// The price the item was bought at.
+    pub fn cost(&self) -> u64 {
+        self.deal.price_update.price
+    }
+
+    /// The robux received from reselling the item, after the marketplace tax.
+    pub fn proceeds(&self) -> u64 {
+        after_tax(self.exit_price)
+    }
+
+    /// The profit (or loss) of the trade, after the marketplace tax.
+    pub fn profit(&self) -> i64 {
+        self.proceeds() as i64 - self.cost() as i64
+    }
+
+    /// Whether the item was resold into a real sale rather than marked at its rap.
+    pub fn is_closed(&self) -> bool {
+        self.exit_timestamp.is_some()
+    }
+}
+
+impl BacktestReport {
+    /// The total robux spent buying deals.
+    pub fn spent(&self) -> u64 {
+        self.trades.iter().map(|x| x.cost()).sum()
+    }
+
+    /// The total robux received from reselling, after the marketplace tax.
+    pub fn proceeds(&self) -> u64 {
+        self.trades.iter().map(|x| x.proceeds()).sum()
+    }
+
+    /// The total profit (or loss) after the marketplace tax.
+    pub fn profit(&self) -> i64 {
+        self.proceeds() as i64 - self.spent() as i64
+    }
+
+    /// The profit as a percentage of the robux spent. Returns 0 if nothing was bought.
+    pub fn roi_percent(&self) -> f64 {
+        match self.spent() {
+            0 => 0.0,
+            spent => self.profit() as f64 / spent as f64 * 100.0,
+        }
+    }
+
+    /// The percentage of trades that made a profit. Returns 0 if nothing was bought.
+    pub fn win_rate(&self) -> f64 {
+        if self.trades.is_empty() {
+            return 0.0;
+        }
+
+        let wins = self.trades.iter().filter(|x| x.profit() > 0).count();
+        wins as f64 / self.trades.len() as f64 * 100.0
+    }
+}
+
+/// Returns what the seller receives from a sale at `price`, after Roblox takes
+/// [`limits::MARKETPLACE_TAX_PERCENT`].
+pub fn after_tax(price: u64) -> u64 {
+    price * (100 - limits::MARKETPLACE_TAX_PERCENT) / 100
+}
+
+/// Runs `strategy` over historical sales and deals activity and reports the
+/// hypothetical profit of the deals it bought.
+///
+/// Sales, price updates, and rap updates are replayed in timestamp order; every other
+/// event is ignored. Each price update of an item in `table` is turned into a [`Deal`]
+/// (using the latest rap seen so far, like [`DealEvaluator`]) and passed to the strategy.
+/// A bought item is resold at the price of the next sale of the same item, one sale per
+/// bought copy. Copies that are never resold are marked at the item's latest rap.
+///
+/// The events usually come from recorded responses passed through `audit::replay_events`
+/// (with the `audit` feature), or from polls saved over time.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::backtest::{self, Decision};
+/// use roli::deals::Deal;
+/// use roli::items::ItemDetailsTable;
+/// use roli::Event;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+///
+/// let mut events = Vec::new();
+/// events.extend(client.recent_sales().await?.into_iter().map(Event::from));
+/// events.extend(client.deals_activity().await?.into_iter().map(Event::from));
+///
+/// let mut strategy = |deal: &Deal| {
+///     if deal.deal_percent() >= 30.0 {
+///         Decision::Buy
+///     } else {
+///         Decision::Skip
+///     }
+/// };
+///
+/// let report = backtest::run(&mut strategy, &events, &table);
+/// println!("{} trades, {:+} robux ({:.1}%)", report.trades.len(), report.profit(), report.roi_percent());
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn run<S: Strategy + ?Sized>(
+    strategy: &mut S,
+    events: &[Event],
+    table: &impl ItemInfoProvider,
+) -> BacktestReport {
+    let mut events = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Sale(x) => Some((x.timestamp, MarketEvent::Sale(x))),
+            Event::PriceUpdate(x) => Some((x.timestamp, MarketEvent::Listing(x))),
+            Event::RapUpdate(x) => Some((x.timestamp, MarketEvent::RapUpdate(x))),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // Stable, so events with the same timestamp keep the order they were recorded in.
+    events.sort_by_key(|(timestamp, _)| *timestamp);
+
+    let mut evaluator = DealEvaluator::new();
+    let mut holdings = HashMap::<u64, VecDeque<Deal>>::new();
+    let mut report = BacktestReport::default();
+
+    for (_, event) in events {
+        match event {
+            MarketEvent::RapUpdate(rap_update) => {
+                evaluator.observe(&[Activity::RapUpdate(*rap_update)]);
+            }
+            MarketEvent::Listing(price_update) => {
+                let Some(mut item) = table
+                    .item_details(price_update.item_id)
+                    .map(Cow::into_owned)
+                else {
+                    continue;
+                };
+
+                if let Some(rap) = evaluator.rap(item.item_id) {
+                    item.rap = rap;
+                }
+
+                if item.value_or_rap() == 0 {
+                    continue;
+                }
+
+                let deal = Deal::new(*price_update, item);
+
+                match strategy.on_deal(&deal) {
+                    Decision::Buy => holdings
+                        .entry(deal.item.item_id)
+                        .or_default()
+                        .push_back(deal),
+                    Decision::Skip => report.skipped += 1,
+                }
+            }
+            MarketEvent::Sale(sale) => {
+                evaluator.observe(&[Activity::RapUpdate(RapUpdate {
+                    timestamp: sale.timestamp,
+                    item_id: sale.item_id,
+                    rap: sale.new_rap,
+                })]);
+
+                if let Some(deal) = holdings
+                    .get_mut(&sale.item_id)
+                    .and_then(|held| held.pop_front())
+                {
+                    report.trades.push(Trade {
+                        deal,
+                        exit_price: sale.sale_price,
+                        exit_timestamp: Some(sale.timestamp),
+                    });
+                }
+            }
+        }
+    }
+
+    for deal in holdings.into_values().flatten() {
+        let exit_price = evaluator.rap(deal.item.item_id).unwrap_or(deal.item.rap);
+
+        report.trades.push(Trade {
+            deal,
+            exit_price,
+            exit_timestamp: None,
+        });
+    }
+
+    report.trades.sort_by_key(|x| {
+        (
+            x.deal.price_update.timestamp,
+            x.deal.item.item_id,
+            x.exit_timestamp.is_none(),
+        )
+    });
+
+    report
+}
+
+enum MarketEvent<'a> {
+    Sale(&'a Sale),
+    Listing(&'a PriceUpdate),
+    RapUpdate(&'a RapUpdate),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::{ItemDetails, ItemDetailsTable};
+
+    #[test]
+    fn test_run() {
+        let table = ItemDetailsTable::new(vec![ItemDetails {
+            item_id: 1,
+            rap: 1000,
+            ..Default::default()
+        }]);
+
+        let sale = |timestamp, sale_price| {
+            Event::Sale(Sale {
+                item_id: 1,
+                sale_price,
+                new_rap: sale_price,
+                timestamp,
+                ..Default::default()
+            })
+        };
+
+        let listing = |timestamp, price| {
+            Event::PriceUpdate(PriceUpdate {
+                timestamp,
+                item_id: 1,
+                price,
+            })
+        };
+
+        let events = vec![
+            sale(40, 1400),
+            listing(10, 500),
+            Event::RapUpdate(RapUpdate {
+                timestamp: 5,
+                item_id: 1,
+                rap: 2000,
+            }),
+            listing(20, 1900),
+            listing(30, 600),
+        ];
+
+        let mut strategy = |deal: &Deal| {
+            if deal.deal_percent() >= 50.0 {
+                Decision::Buy
+            } else {
+                Decision::Skip
+            }
+        };
+
+        let report = run(&mut strategy, &events, &table);
+
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.trades.len(), 2);
+
+        // Resold into the sale at 1400, which pays out 980.
+        assert_eq!(report.trades[0].exit_timestamp, Some(40));
+        assert_eq!(report.trades[0].profit(), 480);
+
+        // Never resold, so marked at the rap set by the sale.
+        assert!(!report.trades[1].is_closed());
+        assert_eq!(report.trades[1].exit_price, 1400);
+        assert_eq!(report.trades[1].profit(), 380);
+
+        assert_eq!(report.spent(), 1100);
+        assert_eq!(report.profit(), 860);
+        assert_eq!(report.win_rate(), 100.0);
+        assert_eq!(after_tax(1000), 700);
+    }
+}
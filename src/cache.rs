@@ -0,0 +1,619 @@
+use crate::api::RoliApi;
+use crate::items::{ItemDetails, ItemInfoProvider, Snapshot};
+use crate::state::SnapshotStore;
+use crate::{limits, Client, Endpoint, RoliError};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// The default time a snapshot is considered fresh for. Rolimons caches the item
+/// details endpoint for 60 seconds, so refreshing faster than this is pointless.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(limits::ITEMDETAILS_CACHE_SECONDS);
+
+/// What an [`ItemCache`] does when it has to wait on a refresh and the refresh fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum FailurePolicy {
+    /// Return the error.
+    #[default]
+    Propagate,
+    /// Return the last good snapshot, marked as stale, if the refresh failed with an
+    /// error that [`RoliError::is_retryable`], such as a rate limit, a 5xx status code,
+    /// a maintenance page, or a timeout. Other errors, and failures while the cache is
+    /// empty, are still returned.
+    ///
+    /// After such a failure, the last good snapshot is returned without fetching again
+    /// until [`limits::MIN_ALL_ITEM_DETAILS_INTERVAL`] has passed, so an outage is not
+    /// met with a fetch on every call.
+    ServeStale,
+}
+
+/// A snapshot returned by [`ItemCache::get_with_status`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedSnapshot {
+    /// The snapshot.
+    pub snapshot: Arc<Snapshot>,
+    /// Whether the snapshot is older than the ttl, either because a refresh is
+    /// running in the background or because the refresh failed.
+    pub stale: bool,
+}
+
+/// A cache of [`Client::all_item_details`] with stale-while-revalidate semantics.
+///
+/// - While a snapshot is younger than the ttl, it is returned as is.
+/// - Once it is older than the ttl, it is still returned immediately, and a refresh
+///   is started in the background.
+/// - If a max staleness is set and the snapshot is older than it, calls wait for
+///   a fresh snapshot instead. If that refresh fails, the [`FailurePolicy`] decides
+///   whether the error or the old snapshot is returned.
+///
+/// Only one refresh runs at a time, no matter how many tasks call
+/// [`ItemCache::get`]. The cache is cheap to clone and clones share the same
+/// snapshot. To share the snapshot between processes too, give the builder a
+/// [`SnapshotStore`] with [`ItemCacheBuilder::set_state`].
+///
+/// Created using an [`ItemCacheBuilder`].
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::cache::ItemCacheBuilder;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let cache = ItemCacheBuilder::new(client)
+///     .set_max_staleness(Duration::from_secs(300))
+///     .build();
+///
+/// // Fetch the first snapshot up front so the first call to `get` is fast.
+/// cache.warm_up().await?;
+///
+/// let snapshot = cache.get().await?;
+/// println!("{} items", snapshot.items.len());
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ItemCache<A = Client> {
+    inner: Arc<Inner<A>>,
+}
+
+/// Used to build an [`ItemCache`].
+#[derive(Clone, Debug)]
+pub struct ItemCacheBuilder<A = Client> {
+    api: A,
+    ttl: Duration,
+    max_staleness: Option<Duration>,
+    failure_policy: FailurePolicy,
+    state: Option<SharedState>,
+}
+
+#[derive(Debug)]
+struct Inner<A> {
+    api: A,
+    ttl: Duration,
+    max_staleness: Option<Duration>,
+    failure_policy: FailurePolicy,
+    state: Option<SharedState>,
+    cached: RwLock<Option<Cached>>,
+    /// The last failed refresh, cleared once a refresh succeeds.
+    last_failure: RwLock<Option<Failure>>,
+    refreshing: AtomicBool,
+    refresh_lock: tokio::sync::Mutex<()>,
+}
+
+#[derive(Clone, Debug)]
+struct Cached {
+    snapshot: Arc<Snapshot>,
+    fetched_at: Instant,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Failure {
+    at: Instant,
+    retryable: bool,
+}
+
+#[derive(Clone)]
+struct SharedState(Arc<dyn SnapshotStore>);
+
+impl fmt::Debug for SharedState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedState").finish_non_exhaustive()
+    }
+}
+
+/// Looks items up in the cached snapshot without refreshing it, so lookups never wait
+/// on the network. Every lookup returns `None` while the cache is empty.
+impl<A: RoliApi + 'static> ItemInfoProvider for ItemCache<A> {
+    fn item_details(&self, item_id: u64) -> Option<Cow<'_, ItemDetails>> {
+        let snapshot = self.peek()?;
+        snapshot.items.get(item_id).cloned().map(Cow::Owned)
+    }
+}
+
+impl<A> Clone for ItemCache<A> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<A: RoliApi + 'static> ItemCacheBuilder<A> {
+    /// Constructs a new builder that fetches items with `api`.
+    ///
+    /// The ttl defaults to [`DEFAULT_TTL`] and there is no max staleness.
+    pub fn new(api: A) -> Self {
+        Self {
+            api,
+            ttl: DEFAULT_TTL,
+            max_staleness: None,
+            failure_policy: FailurePolicy::default(),
+            state: None,
+        }
+    }
+
+    /// Sets how long a snapshot is considered fresh for.
+    ///
+    /// [`ItemCacheBuilder::build`] raises a ttl below
+    /// [`limits::MIN_ALL_ITEM_DETAILS_INTERVAL`] to it, and
+    /// [`ItemCacheBuilder::try_build`] rejects it.
+    pub fn set_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets how old a snapshot can get before calls wait for a refresh instead of
+    /// returning it.
+    pub fn set_max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = Some(max_staleness);
+        self
+    }
+
+    /// Sets what happens when a call has to wait on a refresh and the refresh fails.
+    ///
+    /// Defaults to [`FailurePolicy::Propagate`]. With [`FailurePolicy::ServeStale`],
+    /// bots keep running on the last good snapshot while Rolimons is rate limiting or
+    /// having issues, and [`ItemCache::get_with_status`] tells them it is stale.
+    pub fn set_failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Sets a store that the snapshot is shared through, such as
+    /// [`RedisState`](crate::state::redis::RedisState).
+    ///
+    /// Before fetching, the cache checks the store for a snapshot younger than the
+    /// ttl and uses it instead, so caches in different processes only fetch once
+    /// per ttl between them. Fetched snapshots are saved back to the store. Errors
+    /// from the store are ignored, and the cache falls back to fetching.
+    pub fn set_state(mut self, state: impl SnapshotStore + 'static) -> Self {
+        self.state = Some(SharedState(Arc::new(state)));
+        self
+    }
+
+    /// The same as [`ItemCacheBuilder::build`], but returns [`RoliError::InvalidConfig`]
+    /// if the ttl is below [`limits::MIN_ALL_ITEM_DETAILS_INTERVAL`], which would make the
+    /// cache poll the item details endpoint more often than it is allowed to be.
+    pub fn try_build(self) -> Result<ItemCache<A>, RoliError> {
+        limits::check_poll_interval(Endpoint::AllItemDetails, self.ttl)?;
+        Ok(self.build())
+    }
+
+    /// Builds the [`ItemCache`]. The cache starts empty.
+    ///
+    /// A ttl below [`limits::MIN_ALL_ITEM_DETAILS_INTERVAL`] is raised to it, so that the
+    /// cache never polls the item details endpoint more often than it is allowed to be.
+    /// Use [`ItemCacheBuilder::try_build`] to get an error instead.
+    pub fn build(mut self) -> ItemCache<A> {
+        self.ttl = self.ttl.max(limits::MIN_ALL_ITEM_DETAILS_INTERVAL);
+        self.build_unclamped()
+    }
+
+    /// Builds the [`ItemCache`] with the ttl as it was set.
+    fn build_unclamped(self) -> ItemCache<A> {
+        ItemCache {
+            inner: Arc::new(Inner {
+                api: self.api,
+                ttl: self.ttl,
+                max_staleness: self.max_staleness,
+                failure_policy: self.failure_policy,
+                state: self.state,
+                cached: RwLock::new(None),
+                last_failure: RwLock::new(None),
+                refreshing: AtomicBool::new(false),
+                refresh_lock: tokio::sync::Mutex::new(()),
+            }),
+        }
+    }
+}
+
+impl<A: RoliApi + 'static> ItemCache<A> {
+    /// Returns the cached snapshot, refreshing it as described in [`ItemCache`].
+    ///
+    /// Only waits on the network if the cache is empty or the snapshot is older
+    /// than the max staleness. Errors from background refreshes are discarded, and
+    /// no background refresh is started again until
+    /// [`limits::MIN_ALL_ITEM_DETAILS_INTERVAL`] has passed since the failure, so a rate
+    /// limit or an outage is not met with a fetch on every call.
+    pub async fn get(&self) -> Result<Arc<Snapshot>, RoliError> {
+        self.get_with_status()
+            .await
+            .map(|cached_snapshot| cached_snapshot.snapshot)
+    }
+
+    /// The same as [`ItemCache::get`], but also returns whether the snapshot is stale.
+    pub async fn get_with_status(&self) -> Result<CachedSnapshot, RoliError> {
+        let cached = self.inner.cached();
+
+        if let Some(cached) = &cached {
+            let age = cached.fetched_at.elapsed();
+
+            if age < self.inner.ttl {
+                return Ok(CachedSnapshot {
+                    snapshot: cached.snapshot.clone(),
+                    stale: false,
+                });
+            }
+
+            let within_max_staleness = match self.inner.max_staleness {
+                Some(max_staleness) => age < max_staleness,
+                None => true,
+            };
+
+            if within_max_staleness {
+                if self.inner.recent_failure().is_none() {
+                    self.spawn_refresh();
+                }
+
+                return Ok(CachedSnapshot {
+                    snapshot: cached.snapshot.clone(),
+                    stale: true,
+                });
+            }
+
+            // Serves the stale snapshot without fetching again while the last refresh
+            // failed recently, instead of sending a request on every call.
+            let serve_stale = self.inner.failure_policy == FailurePolicy::ServeStale
+                && self
+                    .inner
+                    .recent_failure()
+                    .is_some_and(|failure| failure.retryable);
+
+            if serve_stale {
+                return Ok(CachedSnapshot {
+                    snapshot: cached.snapshot.clone(),
+                    stale: true,
+                });
+            }
+        }
+
+        match self.inner.refresh_if_stale().await {
+            Ok(snapshot) => Ok(CachedSnapshot {
+                snapshot,
+                stale: false,
+            }),
+            Err(e) => match cached {
+                Some(cached)
+                    if self.inner.failure_policy == FailurePolicy::ServeStale
+                        && e.is_retryable() =>
+                {
+                    Ok(CachedSnapshot {
+                        snapshot: cached.snapshot,
+                        stale: true,
+                    })
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Fetches a snapshot if the cache does not have a fresh one, waiting for it to finish.
+    ///
+    /// Call this on startup so that the first call to [`ItemCache::get`] does not
+    /// have to wait on the network.
+    pub async fn warm_up(&self) -> Result<(), RoliError> {
+        self.inner.refresh_if_stale().await.map(|_| ())
+    }
+
+    /// Returns the cached snapshot without refreshing it, or `None` if the cache is empty.
+    pub fn peek(&self) -> Option<Arc<Snapshot>> {
+        self.inner.cached().map(|cached| cached.snapshot)
+    }
+
+    /// Returns the age of the cached snapshot, or `None` if the cache is empty.
+    pub fn age(&self) -> Option<Duration> {
+        self.inner
+            .cached()
+            .map(|cached| cached.fetched_at.elapsed())
+    }
+
+    fn spawn_refresh(&self) {
+        if self.inner.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let inner = self.inner.clone();
+
+        tokio::spawn(async move {
+            let _ = inner.refresh_if_stale().await;
+            inner.refreshing.store(false, Ordering::Release);
+        });
+    }
+}
+
+impl<A: RoliApi> Inner<A> {
+    fn cached(&self) -> Option<Cached> {
+        self.cached
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Fetches a new snapshot unless another caller refreshed the cache while
+    /// this one was waiting on the lock.
+    async fn refresh_if_stale(&self) -> Result<Arc<Snapshot>, RoliError> {
+        let _guard = self.refresh_lock.lock().await;
+
+        if let Some(cached) = self.cached() {
+            if cached.fetched_at.elapsed() < self.ttl {
+                return Ok(cached.snapshot);
+            }
+        }
+
+        if let Some(snapshot) = self.load_shared().await {
+            return Ok(snapshot);
+        }
+
+        let items = match self.api.all_item_details().await {
+            Ok(x) => x,
+            Err(e) => {
+                *self.last_failure.write().unwrap_or_else(|e| e.into_inner()) = Some(Failure {
+                    at: Instant::now(),
+                    retryable: e.is_retryable(),
+                });
+                return Err(e);
+            }
+        };
+
+        *self.last_failure.write().unwrap_or_else(|e| e.into_inner()) = None;
+
+        let snapshot = Arc::new(Snapshot::new(crate::unix_timestamp(), items));
+
+        if let Some(state) = &self.state {
+            let _ = state.0.save_snapshot(&snapshot).await;
+        }
+
+        self.set_cached(snapshot.clone(), Instant::now());
+
+        Ok(snapshot)
+    }
+
+    /// Returns the last failed refresh if it failed less than
+    /// [`limits::MIN_ALL_ITEM_DETAILS_INTERVAL`] ago.
+    fn recent_failure(&self) -> Option<Failure> {
+        self.last_failure
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .filter(|failure| failure.at.elapsed() < limits::MIN_ALL_ITEM_DETAILS_INTERVAL)
+    }
+
+    /// Returns the snapshot in the shared state if there is one younger than the ttl.
+    async fn load_shared(&self) -> Option<Arc<Snapshot>> {
+        let snapshot = self.state.as_ref()?.0.load_snapshot().await.ok()??;
+
+        let age = Duration::from_secs(
+            crate::unix_timestamp().saturating_sub(snapshot.metadata.fetched_at),
+        );
+
+        if age >= self.ttl {
+            return None;
+        }
+
+        let snapshot = Arc::new(snapshot);
+        let fetched_at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+        self.set_cached(snapshot.clone(), fetched_at);
+
+        Some(snapshot)
+    }
+
+    fn set_cached(&self, snapshot: Arc<Snapshot>, fetched_at: Instant) {
+        *self.cached.write().unwrap_or_else(|e| e.into_inner()) = Some(Cached {
+            snapshot,
+            fetched_at,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Faker, MockClient};
+    use crate::Endpoint;
+
+    #[test]
+    fn test_try_build_ttl_floor() {
+        let mock = MockClient::new();
+
+        let cache = ItemCacheBuilder::new(mock.clone())
+            .set_ttl(Duration::from_secs(5))
+            .build();
+        assert_eq!(cache.inner.ttl, limits::MIN_ALL_ITEM_DETAILS_INTERVAL);
+
+        assert!(ItemCacheBuilder::new(mock.clone()).try_build().is_ok());
+        assert!(matches!(
+            ItemCacheBuilder::new(mock)
+                .set_ttl(Duration::from_secs(5))
+                .try_build(),
+            Err(RoliError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fresh_snapshot_is_reused() {
+        let mock = MockClient::new();
+        mock.set_all_item_details(Faker::new(1).items(3));
+
+        let cache = ItemCacheBuilder::new(mock.clone()).build();
+        cache.warm_up().await.unwrap();
+        cache.get().await.unwrap();
+        cache.get().await.unwrap();
+
+        assert_eq!(mock.call_count(Endpoint::AllItemDetails), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate() {
+        let mut faker = Faker::new(2);
+        let mock = MockClient::new();
+        mock.set_all_item_details(faker.items(3));
+
+        let cache = ItemCacheBuilder::new(mock.clone())
+            .set_ttl(Duration::ZERO)
+            .build_unclamped();
+
+        assert_eq!(cache.get().await.unwrap().items.len(), 3);
+
+        mock.set_all_item_details(faker.items(5));
+
+        // The stale snapshot is returned while the refresh runs in the background.
+        assert_eq!(cache.get().await.unwrap().items.len(), 3);
+
+        while cache.inner.refreshing.load(Ordering::Acquire) {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(cache.peek().unwrap().items.len(), 5);
+        assert_eq!(mock.call_count(Endpoint::AllItemDetails), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_refresh_backs_off() {
+        let mut faker = Faker::new(2);
+        let mock = MockClient::new();
+        mock.set_all_item_details(faker.items(3));
+
+        let cache = ItemCacheBuilder::new(mock.clone()).build();
+        cache.warm_up().await.unwrap();
+
+        let wait_for_refresh = || async {
+            while cache.inner.refreshing.load(Ordering::Acquire) {
+                tokio::task::yield_now().await;
+            }
+        };
+
+        tokio::time::advance(DEFAULT_TTL).await;
+        mock.push_error(Endpoint::AllItemDetails, RoliError::TooManyRequests);
+
+        // The first call after the ttl starts a refresh, which fails.
+        assert_eq!(cache.get().await.unwrap().items.len(), 3);
+        wait_for_refresh().await;
+
+        // The next call does not start another one right away.
+        mock.set_all_item_details(faker.items(5));
+        assert_eq!(cache.get().await.unwrap().items.len(), 3);
+        wait_for_refresh().await;
+        assert_eq!(mock.call_count(Endpoint::AllItemDetails), 2);
+
+        tokio::time::advance(limits::MIN_ALL_ITEM_DETAILS_INTERVAL).await;
+
+        cache.get().await.unwrap();
+        wait_for_refresh().await;
+        assert_eq!(cache.peek().unwrap().items.len(), 5);
+        assert_eq!(mock.call_count(Endpoint::AllItemDetails), 3);
+    }
+
+    #[tokio::test]
+    async fn test_max_staleness_blocks() {
+        let mut faker = Faker::new(3);
+        let mock = MockClient::new();
+        mock.set_all_item_details(faker.items(3));
+
+        let cache = ItemCacheBuilder::new(mock.clone())
+            .set_ttl(Duration::ZERO)
+            .set_max_staleness(Duration::ZERO)
+            .build_unclamped();
+
+        cache.warm_up().await.unwrap();
+        mock.set_all_item_details(faker.items(5));
+
+        assert_eq!(cache.get().await.unwrap().items.len(), 5);
+
+        mock.push_error(Endpoint::AllItemDetails, RoliError::TooManyRequests);
+
+        assert!(matches!(cache.get().await, Err(RoliError::TooManyRequests)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_serve_stale_on_failure() {
+        let mut faker = Faker::new(5);
+        let mock = MockClient::new();
+        mock.set_all_item_details(faker.items(3));
+
+        let cache = ItemCacheBuilder::new(mock.clone())
+            .set_ttl(Duration::ZERO)
+            .set_max_staleness(Duration::ZERO)
+            .set_failure_policy(FailurePolicy::ServeStale)
+            .build_unclamped();
+
+        cache.warm_up().await.unwrap();
+
+        mock.push_error(Endpoint::AllItemDetails, RoliError::InternalServerError);
+        let cached_snapshot = cache.get_with_status().await.unwrap();
+        assert!(cached_snapshot.stale);
+        assert_eq!(cached_snapshot.snapshot.items.len(), 3);
+
+        // Until the failure floor has passed, the stale snapshot is served without
+        // fetching again.
+        let cached_snapshot = cache.get_with_status().await.unwrap();
+        assert!(cached_snapshot.stale);
+        assert_eq!(mock.call_count(Endpoint::AllItemDetails), 2);
+
+        tokio::time::advance(limits::MIN_ALL_ITEM_DETAILS_INTERVAL).await;
+
+        // A maintenance page is also a Rolimons hiccup.
+        mock.push_error(Endpoint::AllItemDetails, RoliError::UnexpectedHtmlResponse);
+        let cached_snapshot = cache.get_with_status().await.unwrap();
+        assert!(cached_snapshot.stale);
+        assert_eq!(cached_snapshot.snapshot.items.len(), 3);
+        assert_eq!(mock.call_count(Endpoint::AllItemDetails), 3);
+
+        tokio::time::advance(limits::MIN_ALL_ITEM_DETAILS_INTERVAL).await;
+
+        // Errors that are not Rolimons' fault are still returned.
+        mock.push_error(Endpoint::AllItemDetails, RoliError::MalformedResponse);
+        assert!(matches!(
+            cache.get().await,
+            Err(RoliError::MalformedResponse)
+        ));
+
+        mock.set_all_item_details(faker.items(5));
+        let cached_snapshot = cache.get_with_status().await.unwrap();
+        assert!(!cached_snapshot.stale);
+        assert_eq!(cached_snapshot.snapshot.items.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_shared_state() {
+        let mock = MockClient::new();
+        mock.set_all_item_details(Faker::new(4).items(3));
+
+        let state = crate::state::MemoryState::new();
+
+        let first = ItemCacheBuilder::new(mock.clone())
+            .set_state(state.clone())
+            .build();
+        let second = ItemCacheBuilder::new(mock.clone()).set_state(state).build();
+
+        first.warm_up().await.unwrap();
+        assert_eq!(second.get().await.unwrap().items.len(), 3);
+
+        assert_eq!(mock.call_count(Endpoint::AllItemDetails), 1);
+    }
+}
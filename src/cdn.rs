@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const AVATAR_HEADSHOT_API: &str = "https://thumbnails.roblox.com/v1/users/avatar-headshot";
+
+/// A thumbnail hosted on Roblox's image cdn, such as
+/// `https://tr.rbxcdn.com/10887f751be70e18cd3e50d2e2247266/150/150/Image/Png`.
+///
+/// Game and group thumbnails are returned by Rolimons as plain urls, which can be
+/// parsed into this type to request the same image at another size or format.
+///
+/// # Example
+/// ```
+/// use roli::cdn::RobloxCdnImage;
+///
+/// let image: RobloxCdnImage = "https://tr.rbxcdn.com/10887f751be70e18cd3e50d2e2247266/150/150/Image/Png"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(image.size, (150, 150));
+/// assert_eq!(
+///     image.with_size(420, 420).with_format("Webp").to_string(),
+///     "https://tr.rbxcdn.com/10887f751be70e18cd3e50d2e2247266/420/420/Image/Webp"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct RobloxCdnImage {
+    /// The url up to and including the image's hash, such as
+    /// `https://tr.rbxcdn.com/10887f751be70e18cd3e50d2e2247266`.
+    pub base: String,
+    /// The width and height of the image in pixels.
+    pub size: (u32, u32),
+    /// The kind of image, which is usually `Image` or `Avatar`.
+    pub kind: String,
+    /// The format of the image, such as `Png`, `Jpeg`, or `Webp`.
+    pub format: String,
+    /// Anything after the format, such as `noFilter` on newer thumbnails.
+    pub suffix: Option<String>,
+}
+
+/// A size Roblox renders avatar headshots at, used by [`avatar_headshot_url`].
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum HeadshotSize {
+    /// 48x48 pixels.
+    Size48,
+    /// 60x60 pixels.
+    Size60,
+    /// 75x75 pixels.
+    Size75,
+    /// 100x100 pixels.
+    Size100,
+    /// 150x150 pixels.
+    Size150,
+    /// 180x180 pixels.
+    Size180,
+    /// 352x352 pixels.
+    Size352,
+    /// 420x420 pixels.
+    #[default]
+    Size420,
+    /// 720x720 pixels.
+    Size720,
+}
+
+/// The error returned when a url can not be parsed into a [`RobloxCdnImage`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ParseCdnImageError;
+
+impl RobloxCdnImage {
+    /// Returns the same image at a different size.
+    ///
+    /// Roblox only serves a fixed set of sizes for each kind of image (such as
+    /// 150x150 and 420x420 for game icons), so not every size resolves.
+    pub fn with_size(self, width: u32, height: u32) -> Self {
+        Self {
+            size: (width, height),
+            ..self
+        }
+    }
+
+    /// Returns the same image in a different format, such as `Png` or `Webp`.
+    pub fn with_format(self, format: impl Into<String>) -> Self {
+        Self {
+            format: format.into(),
+            ..self
+        }
+    }
+
+    /// Returns the url of the image.
+    pub fn url(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl HeadshotSize {
+    /// Returns the width (and height) of the headshot in pixels.
+    pub fn pixels(&self) -> u32 {
+        match self {
+            Self::Size48 => 48,
+            Self::Size60 => 60,
+            Self::Size75 => 75,
+            Self::Size100 => 100,
+            Self::Size150 => 150,
+            Self::Size180 => 180,
+            Self::Size352 => 352,
+            Self::Size420 => 420,
+            Self::Size720 => 720,
+        }
+    }
+}
+
+/// Returns the Roblox thumbnails api url for a player's avatar headshot.
+///
+/// Unlike game and group thumbnails, Rolimons does not return headshot urls, and
+/// Roblox's cdn urls contain a hash that can only be learned from this api. The api
+/// responds with json containing the cdn url under `data[0].imageUrl`, which can be
+/// parsed into a [`RobloxCdnImage`].
+///
+/// # Example
+/// ```
+/// use roli::cdn::{self, HeadshotSize};
+///
+/// assert_eq!(
+///     cdn::avatar_headshot_url(2207291, HeadshotSize::Size150),
+///     "https://thumbnails.roblox.com/v1/users/avatar-headshot?userIds=2207291&size=150x150&format=Png&isCircular=false"
+/// );
+/// ```
+pub fn avatar_headshot_url(user_id: u64, size: HeadshotSize) -> String {
+    format!(
+        "{}?userIds={}&size={}x{}&format=Png&isCircular=false",
+        AVATAR_HEADSHOT_API,
+        user_id,
+        size.pixels(),
+        size.pixels()
+    )
+}
+
+impl FromStr for RobloxCdnImage {
+    type Err = ParseCdnImageError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = url.split_once("://").ok_or(ParseCdnImageError)?;
+        let (host, path) = rest.split_once('/').ok_or(ParseCdnImageError)?;
+
+        let mut segments = path.splitn(6, '/');
+        let mut next = || segments.next().filter(|x| !x.is_empty());
+
+        let hash = next().ok_or(ParseCdnImageError)?;
+        let width = next()
+            .and_then(|x| x.parse().ok())
+            .ok_or(ParseCdnImageError)?;
+        let height = next()
+            .and_then(|x| x.parse().ok())
+            .ok_or(ParseCdnImageError)?;
+        let kind = next().ok_or(ParseCdnImageError)?.to_string();
+        let format = next().ok_or(ParseCdnImageError)?.to_string();
+        let suffix = next().map(str::to_string);
+
+        Ok(Self {
+            base: format!("{}://{}/{}", scheme, host, hash),
+            size: (width, height),
+            kind,
+            format,
+            suffix,
+        })
+    }
+}
+
+impl fmt::Display for RobloxCdnImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}/{}",
+            self.base, self.size.0, self.size.1, self.kind, self.format
+        )?;
+
+        if let Some(suffix) = &self.suffix {
+            write!(f, "/{}", suffix)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ParseCdnImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a Roblox cdn image url")
+    }
+}
+
+impl std::error::Error for ParseCdnImageError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trip() {
+        for url in [
+            "https://tr.rbxcdn.com/10887f751be70e18cd3e50d2e2247266/150/150/Image/Png",
+            "https://tr.rbxcdn.com/180DAY-5c5a2d0d9f0e/512/512/Image/Webp/noFilter",
+        ] {
+            let image = url.parse::<RobloxCdnImage>().unwrap();
+            assert_eq!(image.to_string(), url);
+        }
+
+        let image = "https://tr.rbxcdn.com/180DAY-5c5a2d0d9f0e/512/512/Image/Webp/noFilter"
+            .parse::<RobloxCdnImage>()
+            .unwrap();
+        assert_eq!(image.base, "https://tr.rbxcdn.com/180DAY-5c5a2d0d9f0e");
+        assert_eq!(image.suffix.as_deref(), Some("noFilter"));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for url in [
+            "",
+            "not a url",
+            "https://tr.rbxcdn.com/hash/150/Image/Png",
+            "https://tr.rbxcdn.com/hash/150/150/Image",
+        ] {
+            assert_eq!(url.parse::<RobloxCdnImage>(), Err(ParseCdnImageError));
+        }
+    }
+}
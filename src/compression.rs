@@ -0,0 +1,55 @@
+use crate::RoliError;
+use std::borrow::Cow;
+
+/// The bytes every zstd frame starts with.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compresses `bytes` with zstd if the `zstd` feature is enabled, and returns them
+/// as is otherwise.
+pub(crate) fn compress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, RoliError> {
+    #[cfg(feature = "zstd")]
+    return zstd::encode_all(bytes, 0)
+        .map(Cow::Owned)
+        .map_err(RoliError::IoError);
+
+    #[cfg(not(feature = "zstd"))]
+    Ok(Cow::Borrowed(bytes))
+}
+
+/// Decompresses `bytes` if they are a zstd frame, and returns them as is otherwise,
+/// so that data written before compression was enabled can still be read.
+///
+/// Returns [`RoliError::MalformedResponse`] for zstd frames if the `zstd` feature is
+/// disabled.
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, RoliError> {
+    if !bytes.starts_with(&ZSTD_MAGIC) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+
+    #[cfg(feature = "zstd")]
+    return zstd::decode_all(bytes)
+        .map(Cow::Owned)
+        .map_err(RoliError::IoError);
+
+    #[cfg(not(feature = "zstd"))]
+    Err(RoliError::MalformedResponse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let json = br#"{"items": [1, 2, 3]}"#.repeat(100);
+        let compressed = compress(&json).unwrap();
+
+        if cfg!(feature = "zstd") {
+            assert!(compressed.starts_with(&ZSTD_MAGIC));
+            assert!(compressed.len() < json.len());
+        }
+
+        assert_eq!(decompress(&compressed).unwrap(), &json[..]);
+        assert_eq!(decompress(b"[1]").unwrap(), &b"[1]"[..]);
+    }
+}
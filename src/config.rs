@@ -0,0 +1,395 @@
+use crate::items::ItemDetails;
+use crate::limits;
+use crate::{Client, ClientBuilder, Endpoint, RoliError};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// A configuration file for bots built on top of this crate.
+///
+/// Every field has a default, so a config file only needs to contain
+/// the values it wants to change.
+///
+/// # Example
+/// ```
+/// let config = roli::config::BotConfig::from_toml_str(
+///     r#"
+///     roli_verification = "xxx"
+///
+///     [poll_intervals]
+///     deals_activity_seconds = 5
+///
+///     [filters]
+///     min_value = 10000
+///     "#,
+/// )
+/// .unwrap();
+///
+/// let client = roli::Client::from_config(&config);
+/// assert!(client.contains_roli_verification());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotConfig {
+    /// The roli_verification token used for authenticated endpoints.
+    pub roli_verification: Option<String>,
+    /// Client side rate limits, enforced by the client made with [`Client::from_config`].
+    pub rate_limits: RateLimitConfig,
+    /// How often the bot should poll each endpoint. These are for the bot to apply
+    /// when it creates its streams.
+    pub poll_intervals: PollIntervalConfig,
+    /// Which items the bot should care about. These are for the bot to apply, such as
+    /// with [`FilterConfig::matches`].
+    pub filters: FilterConfig,
+}
+
+/// Client side rate limits for a bot.
+///
+/// [`Client::from_config`] applies these with [`ClientBuilder::set_requests_per_minute`]
+/// and [`ClientBuilder::set_trade_ads_per_day`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// The maximum amount of requests the client makes per minute, across all endpoints.
+    /// `None` means the client does not limit itself.
+    pub requests_per_minute: Option<u32>,
+    /// The maximum amount of trade ads the client posts per 24 hours.
+    pub trade_ads_per_day: u32,
+}
+
+/// The intervals (in seconds) at which a bot should poll each endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PollIntervalConfig {
+    /// The interval for [`Client::all_item_details`].
+    pub all_item_details_seconds: u64,
+    /// The interval for [`Client::deals_activity`].
+    pub deals_activity_seconds: u64,
+    /// The interval for [`Client::recent_sales`].
+    pub recent_sales_seconds: u64,
+    /// The interval for [`Client::recent_trade_ads`].
+    pub recent_trade_ads_seconds: u64,
+    /// The interval for [`Client::player_profile`], per player.
+    pub player_profile_seconds: u64,
+}
+
+/// Filters used to decide which items a bot should act on.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterConfig {
+    /// If not empty, only these item ids are considered.
+    pub item_ids: Vec<u64>,
+    /// The minimum value (or rap if the item is unvalued) an item must have.
+    pub min_value: Option<u64>,
+    /// The maximum value (or rap if the item is unvalued) an item can have.
+    pub max_value: Option<u64>,
+    /// The minimum percentage below value (or rap) a price must be to count as a deal.
+    pub min_deal_percent: Option<u8>,
+    /// Whether projected items should be ignored.
+    pub skip_projected: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: None,
+            trade_ads_per_day: limits::TRADE_ADS_PER_DAY,
+        }
+    }
+}
+
+impl Default for PollIntervalConfig {
+    fn default() -> Self {
+        Self {
+            all_item_details_seconds: limits::ALL_ITEM_DETAILS_POLL_INTERVAL.as_secs(),
+            deals_activity_seconds: limits::DEALS_ACTIVITY_POLL_INTERVAL.as_secs(),
+            recent_sales_seconds: limits::RECENT_SALES_POLL_INTERVAL.as_secs(),
+            recent_trade_ads_seconds: limits::RECENT_TRADE_ADS_POLL_INTERVAL.as_secs(),
+            player_profile_seconds: limits::PLAYER_PROFILE_POLL_INTERVAL.as_secs(),
+        }
+    }
+}
+
+impl BotConfig {
+    /// Parses a [`BotConfig`] from a TOML string.
+    ///
+    /// Returns [`RoliError::InvalidConfig`] if the string is not a valid config, or if
+    /// it fails [`BotConfig::validate`].
+    pub fn from_toml_str(toml_str: &str) -> Result<Self, RoliError> {
+        let config: Self =
+            toml::from_str(toml_str).map_err(|e| RoliError::InvalidConfig(e.to_string()))?;
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks that the config is safe to run with.
+    ///
+    /// Returns [`RoliError::InvalidConfig`] if a poll interval is below the hard floor
+    /// for its endpoint (see [`limits::min_poll_interval`]).
+    pub fn validate(&self) -> Result<(), RoliError> {
+        self.poll_intervals.validate()
+    }
+
+    /// Reads and parses a [`BotConfig`] from a TOML file.
+    ///
+    /// Returns [`RoliError::InvalidConfig`] if the file cannot be read or is not a valid config.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RoliError> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| RoliError::InvalidConfig(e.to_string()))?;
+
+        Self::from_toml_str(&contents)
+    }
+
+    /// Serializes the config into a TOML string.
+    pub fn to_toml_string(&self) -> Result<String, RoliError> {
+        toml::to_string(self).map_err(|e| RoliError::InvalidConfig(e.to_string()))
+    }
+}
+
+impl PollIntervalConfig {
+    /// Returns [`RoliError::InvalidConfig`] if an interval is below the hard floor for
+    /// its endpoint (see [`limits::min_poll_interval`]).
+    pub fn validate(&self) -> Result<(), RoliError> {
+        limits::check_poll_interval(Endpoint::AllItemDetails, self.all_item_details())?;
+        limits::check_poll_interval(Endpoint::DealsActivity, self.deals_activity())?;
+        limits::check_poll_interval(Endpoint::RecentSales, self.recent_sales())?;
+        limits::check_poll_interval(Endpoint::RecentTradeAds, self.recent_trade_ads())?;
+        limits::check_poll_interval(Endpoint::PlayerProfile, self.player_profile())
+    }
+
+    /// The interval for [`Client::all_item_details`] as a [`Duration`].
+    pub fn all_item_details(&self) -> Duration {
+        Duration::from_secs(self.all_item_details_seconds)
+    }
+
+    /// The interval for [`Client::deals_activity`] as a [`Duration`].
+    pub fn deals_activity(&self) -> Duration {
+        Duration::from_secs(self.deals_activity_seconds)
+    }
+
+    /// The interval for [`Client::recent_sales`] as a [`Duration`].
+    pub fn recent_sales(&self) -> Duration {
+        Duration::from_secs(self.recent_sales_seconds)
+    }
+
+    /// The interval for [`Client::recent_trade_ads`] as a [`Duration`].
+    pub fn recent_trade_ads(&self) -> Duration {
+        Duration::from_secs(self.recent_trade_ads_seconds)
+    }
+
+    /// The interval for [`Client::player_profile`] as a [`Duration`].
+    pub fn player_profile(&self) -> Duration {
+        Duration::from_secs(self.player_profile_seconds)
+    }
+}
+
+impl FilterConfig {
+    /// Returns whether an item passes the item id, value, and projected filters.
+    ///
+    /// Unvalued items are compared using their rap.
+    pub fn matches(&self, item: &ItemDetails) -> bool {
+        if !self.item_ids.is_empty() && !self.item_ids.contains(&item.item_id) {
+            return false;
+        }
+
+        if self.skip_projected && item.projected {
+            return false;
+        }
+
+        let value = item.value_or_rap();
+
+        if let Some(min_value) = self.min_value {
+            if value < min_value {
+                return false;
+            }
+        }
+
+        if let Some(max_value) = self.max_value {
+            if value > max_value {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Returns whether `price` is far enough below `value` to count as a deal.
+    ///
+    /// Always returns true if `min_deal_percent` is not set.
+    pub fn is_deal(&self, price: u64, value: u64) -> bool {
+        match self.min_deal_percent {
+            Some(min_deal_percent) => {
+                if value == 0 || price >= value {
+                    return false;
+                }
+
+                (value - price) * 100 / value >= min_deal_percent as u64
+            }
+            None => true,
+        }
+    }
+}
+
+impl Client {
+    /// Constructs a [`Client`] using the roli_verification token and rate limits in a
+    /// [`BotConfig`].
+    pub fn from_config(config: &BotConfig) -> Self {
+        let mut builder = ClientBuilder::new()
+            .set_requests_per_minute(config.rate_limits.requests_per_minute)
+            .set_trade_ads_per_day(Some(config.rate_limits.trade_ads_per_day));
+
+        if let Some(roli_verification) = &config.roli_verification {
+            builder = builder.set_roli_verification(roli_verification.clone());
+        }
+
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = BotConfig::from_toml_str("").unwrap();
+        assert_eq!(config, BotConfig::default());
+        assert_eq!(config.poll_intervals.deals_activity_seconds, 3);
+        assert_eq!(config.rate_limits.trade_ads_per_day, 55);
+    }
+
+    #[test]
+    fn test_partial_config() {
+        let config = BotConfig::from_toml_str(
+            r#"
+            roli_verification = "abc"
+
+            [poll_intervals]
+            recent_sales_seconds = 10
+
+            [filters]
+            item_ids = [1, 2]
+            skip_projected = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.roli_verification, Some("abc".to_string()));
+        assert_eq!(config.poll_intervals.recent_sales_seconds, 10);
+        assert_eq!(config.poll_intervals.deals_activity_seconds, 3);
+        assert_eq!(config.filters.item_ids, vec![1, 2]);
+        assert!(config.filters.skip_projected);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let result = BotConfig::from_toml_str("poll_intervals = 5");
+        assert!(matches!(result, Err(RoliError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_poll_interval_floor() {
+        let result = BotConfig::from_toml_str(
+            r#"
+            [poll_intervals]
+            all_item_details_seconds = 5
+            "#,
+        );
+
+        assert!(matches!(result, Err(RoliError::InvalidConfig(_))));
+
+        let result = BotConfig::from_toml_str(
+            r#"
+            [poll_intervals]
+            deals_activity_seconds = 0
+            "#,
+        );
+
+        assert!(matches!(result, Err(RoliError::InvalidConfig(_))));
+        assert!(BotConfig::default().validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_from_config_rate_limits() {
+        let config = BotConfig::from_toml_str(
+            r#"
+            roli_verification = "abc"
+
+            [rate_limits]
+            requests_per_minute = 0
+            trade_ads_per_day = 0
+            "#,
+        )
+        .unwrap();
+
+        let client = Client::from_config(&config);
+
+        assert!(matches!(
+            client.recent_sales().await,
+            Err(RoliError::TooManyRequests)
+        ));
+
+        let params = crate::trade_ads::CreateTradeAdParams {
+            player_id: 1,
+            offer_item_ids: vec![1],
+            request_item_ids: vec![2],
+            request_tags: Vec::new(),
+        };
+
+        assert!(matches!(
+            client.create_trade_ad(params).await,
+            Err(RoliError::CooldownNotExpired)
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let config = BotConfig {
+            roli_verification: Some("abc".to_string()),
+            ..Default::default()
+        };
+
+        let toml_string = config.to_toml_string().unwrap();
+        assert_eq!(BotConfig::from_toml_str(&toml_string).unwrap(), config);
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let filters = FilterConfig {
+            min_value: Some(1000),
+            skip_projected: true,
+            ..Default::default()
+        };
+
+        let mut item = ItemDetails {
+            item_id: 1,
+            rap: 1500,
+            ..Default::default()
+        };
+
+        assert!(filters.matches(&item));
+
+        item.projected = true;
+        assert!(!filters.matches(&item));
+
+        item.projected = false;
+        item.valued = true;
+        item.value = 500;
+        assert!(!filters.matches(&item));
+    }
+
+    #[test]
+    fn test_is_deal() {
+        let filters = FilterConfig {
+            min_deal_percent: Some(20),
+            ..Default::default()
+        };
+
+        assert!(filters.is_deal(800, 1000));
+        assert!(!filters.is_deal(850, 1000));
+        assert!(!filters.is_deal(1200, 1000));
+        assert!(FilterConfig::default().is_deal(1200, 1000));
+    }
+}
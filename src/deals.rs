@@ -1,6 +1,9 @@
-use crate::{Client, Code, RoliError};
+use crate::items::{ItemDetails, ItemInfoProvider};
+use crate::{Client, Code, Endpoint, RoliError};
 use reqwest::header;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
 
 const DEALS_ACTIVITY_API: &str = "https://www.rolimons.com/api/activity2";
 
@@ -11,6 +14,11 @@ pub enum Activity {
     PriceUpdate(PriceUpdate),
     /// A variant for a rap update activity.
     RapUpdate(RapUpdate),
+    /// A variant for an activity of a kind this crate does not know about yet.
+    ///
+    /// If the `strict-parsing` feature is enabled, these are returned as
+    /// [`RoliError::MalformedResponse`] instead.
+    Other(OtherActivity),
 }
 
 /// A struct for a deal on the Rolimons deals page.
@@ -22,6 +30,7 @@ pub enum Activity {
 )]
 pub struct PriceUpdate {
     /// The timestamp of the activity in unix time.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
     pub timestamp: u64,
     /// The unique identifier of the item being sold.
     pub item_id: u64,
@@ -38,6 +47,7 @@ pub struct PriceUpdate {
 )]
 pub struct RapUpdate {
     /// The timestamp of the activity in unix time.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
     pub timestamp: u64,
     /// The unique identifier of the item being sold.
     pub item_id: u64,
@@ -45,8 +55,264 @@ pub struct RapUpdate {
     pub rap: u64,
 }
 
+/// An activity of an unknown kind on the Rolimons deals page, kept so that consumers
+/// can still react to it (e.g. by clearing a stale deal for the item).
+///
+/// Please submit an issue or pull request if you know what a kind means.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize, Copy,
+)]
+pub struct OtherActivity {
+    /// The timestamp of the activity in unix time.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
+    pub timestamp: u64,
+    /// The raw kind of the activity (the second value in the json). Price updates
+    /// are kind 0 and rap updates are kind 1.
+    pub kind: i64,
+    /// The unique identifier of the item the activity is about.
+    pub item_id: u64,
+    /// The raw fifth value in the json, which is the price or rap for known kinds.
+    pub value: i64,
+}
+
+/// A [`PriceUpdate`] paired with the Rolimons details of the item being sold.
+///
+/// This is what the Rolimons deals page shows as a deal.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct Deal {
+    /// The price update of the item.
+    pub price_update: PriceUpdate,
+    /// The details of the item being sold.
+    pub item: ItemDetails,
+}
+
+impl Deal {
+    /// Pairs a price update with the details of the item being sold.
+    pub fn new(price_update: PriceUpdate, item: ItemDetails) -> Self {
+        Self { price_update, item }
+    }
+
+    /// The percentage below the item's value (or rap if unvalued) the item is being sold at,
+    /// which is the "deal %" shown on the deals page.
+    ///
+    /// Negative if the item is being sold above its value. Returns 0 if the item has no value or rap.
+    pub fn deal_percent(&self) -> f64 {
+        crate::market_activity::discount_percent(self.price_update.price, self.item.value_or_rap())
+    }
+}
+
+/// Turns the deals activity feed into ranked [`Deal`]s, the same way the Rolimons deals
+/// page does.
+///
+/// The site checks each listing against the most recent rap it has seen in the feed,
+/// rather than the rap in the item details (which can be minutes behind). The evaluator
+/// does the same, and remembers the latest [`RapUpdate`] for each item between calls,
+/// so it should be kept around and fed every poll of [`Client::deals_activity`].
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::deals::DealEvaluator;
+/// use roli::items::ItemDetailsTable;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+/// let mut evaluator = DealEvaluator::new();
+///
+/// let activities = client.deals_activity().await?;
+///
+/// for deal in evaluator.rank(&activities, &table, 30.0) {
+///     println!(
+///         "{} for {} ({:.1}% off)",
+///         deal.item.item_name,
+///         deal.price_update.price,
+///         deal.deal_percent()
+///     );
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DealEvaluator {
+    latest_raps: HashMap<u64, RapUpdate>,
+}
+
+impl DealEvaluator {
+    /// Creates an evaluator that has not seen any rap updates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the rap updates in `activities`, keeping the most recent one per item.
+    pub fn observe(&mut self, activities: &[Activity]) {
+        for activity in activities {
+            if let Activity::RapUpdate(rap_update) = activity {
+                let latest = self
+                    .latest_raps
+                    .entry(rap_update.item_id)
+                    .or_insert(*rap_update);
+
+                if rap_update.timestamp >= latest.timestamp {
+                    *latest = *rap_update;
+                }
+            }
+        }
+    }
+
+    /// Returns the most recent rap seen in the feed for an item, if any.
+    pub fn rap(&self, item_id: u64) -> Option<u64> {
+        self.latest_raps.get(&item_id).map(|x| x.rap)
+    }
+
+    /// Observes `activities`, then returns the most recent listing of each item that is
+    /// at least `min_discount` percent below the item's value (or rap if unvalued).
+    ///
+    /// The rap of each deal's item is replaced with the most recent rap seen in the
+    /// feed. Items that are not in `table` are skipped. Deals are sorted by discount,
+    /// then by value, highest first.
+    pub fn rank(
+        &mut self,
+        activities: &[Activity],
+        table: &impl ItemInfoProvider,
+        min_discount: f64,
+    ) -> Vec<Deal> {
+        self.observe(activities);
+
+        let mut latest_prices = HashMap::<u64, PriceUpdate>::new();
+
+        for activity in activities {
+            if let Activity::PriceUpdate(price_update) = activity {
+                let latest = latest_prices
+                    .entry(price_update.item_id)
+                    .or_insert(*price_update);
+
+                if price_update.timestamp >= latest.timestamp {
+                    *latest = *price_update;
+                }
+            }
+        }
+
+        let mut deals = latest_prices
+            .into_values()
+            .filter_map(|price_update| {
+                let mut item = table.item_details(price_update.item_id)?.into_owned();
+
+                if let Some(rap) = self.rap(item.item_id) {
+                    item.rap = rap;
+                }
+
+                Some(Deal::new(price_update, item))
+            })
+            .filter(|deal| deal.item.value_or_rap() > 0 && deal.deal_percent() >= min_discount)
+            .collect::<Vec<_>>();
+
+        deals.sort_by(|a, b| {
+            b.deal_percent()
+                .total_cmp(&a.deal_percent())
+                .then_with(|| b.item.value_or_rap().cmp(&a.item.value_or_rap()))
+                .then_with(|| a.item.item_id.cmp(&b.item.item_id))
+        });
+
+        deals
+    }
+}
+
+/// How much an item's rap moved within a window of time, as returned by [`volatility`].
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Volatility {
+    /// The unique identifier of the item.
+    pub item_id: u64,
+    /// The amount of rap updates in the window.
+    pub samples: usize,
+    /// The mean rap of the item in the window.
+    pub mean_rap: f64,
+    /// The population standard deviation of the item's rap in the window.
+    pub std_dev: f64,
+    /// How far the highest rap in the window is above the lowest, as a percentage of
+    /// the lowest. Returns 0 if the lowest rap is 0.
+    pub swing_percent: f64,
+}
+
+/// Calculates the volatility of an item's rap from rap updates accumulated from
+/// [`Client::deals_activity`].
+///
+/// Only the updates for `item_id` within `window` of the newest update for the item are
+/// used. Returns `None` if there are no updates for the item.
+///
+/// A high standard deviation or swing over a short window is a sign that the rap is being
+/// manipulated, so deals on the item are less likely to be real.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::deals::{self, Activity};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let mut rap_updates = Vec::new();
+///
+/// for _ in 0..10 {
+///     for activity in client.deals_activity().await? {
+///         if let Activity::RapUpdate(rap_update) = activity {
+///             rap_updates.push(rap_update);
+///         }
+///     }
+///
+///     tokio::time::sleep(Duration::from_secs(60)).await;
+/// }
+///
+/// if let Some(volatility) = deals::volatility(&rap_updates, 1365767, Duration::from_secs(3600)) {
+///     println!("Rap swung {:.1}% in the last hour", volatility.swing_percent);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn volatility(rap_updates: &[RapUpdate], item_id: u64, window: Duration) -> Option<Volatility> {
+    let item_updates = rap_updates.iter().filter(|x| x.item_id == item_id);
+    let newest = item_updates.clone().map(|x| x.timestamp).max()?;
+    let window_start = newest.saturating_sub(window.as_secs());
+
+    // Updates can be repeated when activity is polled more often than it changes.
+    let mut raps = item_updates
+        .filter(|x| x.timestamp >= window_start)
+        .map(|x| (x.timestamp, x.rap))
+        .collect::<Vec<_>>();
+    raps.sort_unstable();
+    raps.dedup();
+
+    let samples = raps.len();
+    let mean_rap = raps.iter().map(|(_, rap)| *rap as f64).sum::<f64>() / samples as f64;
+    let variance = raps
+        .iter()
+        .map(|(_, rap)| (*rap as f64 - mean_rap).powi(2))
+        .sum::<f64>()
+        / samples as f64;
+
+    let min = raps.iter().map(|(_, rap)| *rap).min().unwrap_or(0);
+    let max = raps.iter().map(|(_, rap)| *rap).max().unwrap_or(0);
+    let swing_percent = match min {
+        0 => 0.0,
+        _ => (max - min) as f64 / min as f64 * 100.0,
+    };
+
+    Some(Volatility {
+        item_id,
+        samples,
+        mean_rap,
+        std_dev: variance.sqrt(),
+        swing_percent,
+    })
+}
+
 /// Used for holding the raw json response from <https://www.rolimons.com/api/activity2>.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct DealsActivityResponse {
     success: bool,
     activities: Vec<Vec<Code>>,
@@ -84,15 +350,15 @@ impl Activity {
         // The fourth value of these is unknown, but some analysis of the client code
         // makes this value appear to only be used internally, if at all.
 
-        let is_price_update = codes[1].to_i64()? == 0;
+        let kind = codes[1].to_i64()?;
 
-        let timestamp = codes[0].to_i64()? as u64;
+        let timestamp = codes[0].to_u64_lenient()?;
 
-        let item_id = codes[2].to_i64()? as u64;
+        let item_id = codes[2].to_u64_lenient()?;
 
-        match is_price_update {
-            true => {
-                let price = codes[4].to_i64()? as u64;
+        match kind {
+            0 => {
+                let price = codes[4].to_u64_lenient()?;
 
                 Ok(Activity::PriceUpdate(PriceUpdate {
                     timestamp,
@@ -100,8 +366,8 @@ impl Activity {
                     price,
                 }))
             }
-            false => {
-                let rap = codes[4].to_i64()? as u64;
+            1 => {
+                let rap = codes[4].to_u64_lenient()?;
 
                 Ok(Activity::RapUpdate(RapUpdate {
                     timestamp,
@@ -109,6 +375,12 @@ impl Activity {
                     rap,
                 }))
             }
+            _ => crate::unknown_code(Activity::Other(OtherActivity {
+                timestamp,
+                kind,
+                item_id,
+                value: codes[4].to_i64()?,
+            })),
         }
     }
 }
@@ -120,7 +392,7 @@ impl Client {
     ///
     /// Provides chunks of information on new deals, a cache is likely required for
     /// full use of the api. Returns a Vec of [`Activity`] on success. An [`Activity`] contains either
-    /// a [`PriceUpdate`] or [`RapUpdate`].
+    /// a [`PriceUpdate`] or [`RapUpdate`], or an [`OtherActivity`] for kinds that are not known yet.
     ///
     /// On the Rolimons deals page, this api is polled roughly every 3 seconds.
     ///
@@ -137,50 +409,34 @@ impl Client {
     /// # }
     /// ```
     pub async fn deals_activity(&self) -> Result<Vec<Activity>, RoliError> {
-        let request_result = self
+        let request = self
             .reqwest_client
             .get(DEALS_ACTIVITY_API)
-            .header(header::USER_AGENT, crate::USER_AGENT)
-            .send()
-            .await;
-
-        match request_result {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                match status_code {
-                    200 => {
-                        let raw = match response.json::<DealsActivityResponse>().await {
-                            Ok(x) => x,
-                            Err(_) => return Err(RoliError::MalformedResponse),
-                        };
-
-                        if !raw.success {
-                            return Err(RoliError::RequestReturnedUnsuccessful);
-                        }
-
-                        let mut activities = Vec::new();
-
-                        for raw_activity_codes in raw.activities {
-                            let activity = Activity::from_raw(raw_activity_codes)?;
-                            activities.push(activity)
-                        }
-
-                        Ok(activities)
-                    }
-                    429 => Err(RoliError::TooManyRequests),
-                    500 => Err(RoliError::InternalServerError),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
-            Err(e) => Err(RoliError::ReqwestError(e)),
+            .header(header::USER_AGENT, self.user_agent());
+
+        let raw = self
+            .request_json::<DealsActivityResponse>(Endpoint::DealsActivity, request)
+            .await?;
+
+        if !raw.success {
+            return Err(RoliError::RequestReturnedUnsuccessful);
         }
+
+        let mut activities = Vec::new();
+
+        for raw_activity_codes in raw.activities {
+            let activity = Activity::from_raw(raw_activity_codes)?;
+            activities.push(activity)
+        }
+
+        Ok(activities)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::items::ItemDetailsTable;
 
     #[test]
     fn test_valid_price_update() {
@@ -220,6 +476,95 @@ mod tests {
         assert_eq!(Activity::from_raw(codes).unwrap(), activity);
     }
 
+    #[test]
+    fn test_deal_percent() {
+        let price_update = PriceUpdate {
+            price: 80,
+            ..Default::default()
+        };
+
+        let unvalued = ItemDetails {
+            rap: 100,
+            ..Default::default()
+        };
+
+        assert_eq!(Deal::new(price_update, unvalued).deal_percent(), 20.0);
+        assert_eq!(
+            Deal::new(price_update, ItemDetails::default()).deal_percent(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_deal_evaluator_rank() {
+        let item = |item_id, rap| ItemDetails {
+            item_id,
+            rap,
+            ..Default::default()
+        };
+        let price_update = |timestamp, item_id, price| {
+            Activity::PriceUpdate(PriceUpdate {
+                timestamp,
+                item_id,
+                price,
+            })
+        };
+
+        let table = ItemDetailsTable::new(vec![item(1, 1000), item(2, 1000), item(3, 100)]);
+        let mut evaluator = DealEvaluator::new();
+
+        let activities = vec![
+            // The older listing of item 1 is ignored.
+            price_update(1, 1, 100),
+            price_update(2, 1, 700),
+            price_update(2, 2, 500),
+            price_update(2, 3, 50),
+            price_update(2, 4, 1),
+            // Item 3's rap went up, which makes its listing a better deal.
+            Activity::RapUpdate(RapUpdate {
+                timestamp: 1,
+                item_id: 3,
+                rap: 200,
+            }),
+        ];
+
+        let deals = evaluator.rank(&activities, &table, 30.0);
+
+        assert_eq!(
+            deals
+                .iter()
+                .map(|x| (x.item.item_id, x.deal_percent()))
+                .collect::<Vec<_>>(),
+            vec![(3, 75.0), (2, 50.0), (1, 30.0)]
+        );
+        assert_eq!(evaluator.rap(3), Some(200));
+    }
+
+    #[test]
+    fn test_volatility() {
+        let rap_update = |timestamp, item_id, rap| RapUpdate {
+            timestamp,
+            item_id,
+            rap,
+        };
+
+        let rap_updates = vec![
+            rap_update(0, 1, 1000),
+            rap_update(100, 1, 100),
+            rap_update(150, 2, 5000),
+            rap_update(200, 1, 300),
+            rap_update(200, 1, 300),
+        ];
+
+        let item_volatility = volatility(&rap_updates, 1, Duration::from_secs(100)).unwrap();
+
+        assert_eq!(item_volatility.samples, 2);
+        assert_eq!(item_volatility.mean_rap, 200.0);
+        assert_eq!(item_volatility.std_dev, 100.0);
+        assert_eq!(item_volatility.swing_percent, 200.0);
+        assert!(volatility(&rap_updates, 3, Duration::from_secs(100)).is_none());
+    }
+
     #[test]
     fn test_invalid_codes_length() {
         let codes = vec![
@@ -284,6 +629,28 @@ mod tests {
         assert!(Activity::from_raw(codes).is_err());
     }
 
+    #[test]
+    fn test_negative_price() {
+        let codes = vec![
+            Code::Integer(1678939600),
+            Code::Integer(0),
+            Code::String(String::from("3016210752")),
+            Code::Integer(0),
+            Code::Integer(-1),
+        ];
+
+        let result = Activity::from_raw(codes);
+
+        if cfg!(feature = "strict-parsing") {
+            assert!(result.is_err());
+        } else {
+            assert!(matches!(
+                result,
+                Ok(Activity::PriceUpdate(PriceUpdate { price: 0, .. }))
+            ));
+        }
+    }
+
     #[test]
     fn test_invalid_rap() {
         let codes = vec![
@@ -296,4 +663,31 @@ mod tests {
 
         assert!(Activity::from_raw(codes).is_err());
     }
+
+    #[test]
+    fn test_unknown_kind() {
+        let codes = vec![
+            Code::Integer(1678939605),
+            Code::Integer(2),
+            Code::String(String::from("3016210752")),
+            Code::Integer(0),
+            Code::Integer(-1),
+        ];
+
+        let result = Activity::from_raw(codes);
+
+        if cfg!(feature = "strict-parsing") {
+            assert!(result.is_err());
+        } else {
+            assert_eq!(
+                result.unwrap(),
+                Activity::Other(OtherActivity {
+                    timestamp: 1678939605,
+                    kind: 2,
+                    item_id: 3016210752,
+                    value: -1,
+                })
+            );
+        }
+    }
 }
@@ -0,0 +1,164 @@
+use crate::deals::Deal;
+use crate::items::ItemDetails;
+use crate::market_activity::EnrichedSale;
+use crate::trade_ads::TradeAd;
+use serenity::builder::{CreateEmbed, CreateEmbedFooter};
+use serenity::model::Timestamp;
+
+const ITEM_URL: &str = "https://www.rolimons.com/item/";
+const PLAYER_URL: &str = "https://www.rolimons.com/player/";
+const ITEM_SALE_URL: &str = "https://www.rolimons.com/itemsale/";
+
+/// The colour used for deal embeds (Rolimons' green).
+pub const DEAL_COLOUR: u32 = 0x2ecc71;
+/// The colour used for sale embeds.
+pub const SALE_COLOUR: u32 = 0x3498db;
+/// The colour used for trade ad embeds.
+pub const TRADE_AD_COLOUR: u32 = 0xf1c40f;
+
+/// A type that can be turned into a serenity [`CreateEmbed`].
+///
+/// # Example
+/// ```no_run
+/// use roli::discord::ToEmbed;
+/// # use std::error::Error;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let trade_ads = client.recent_trade_ads().await?;
+///
+/// let embeds = trade_ads.iter().map(|x| x.to_embed()).collect::<Vec<_>>();
+/// # Ok(())
+/// # }
+/// ```
+pub trait ToEmbed {
+    /// Creates an embed describing `self`.
+    fn to_embed(&self) -> CreateEmbed;
+}
+
+impl ToEmbed for Deal {
+    fn to_embed(&self) -> CreateEmbed {
+        let mut embed = item_embed(&self.item)
+            .colour(DEAL_COLOUR)
+            .description(format!(
+                "Selling for **{}** ({:.1}% off)",
+                self.price_update.price,
+                self.deal_percent()
+            ))
+            .fields(item_fields(&self.item));
+
+        if let Ok(timestamp) = Timestamp::from_unix_timestamp(self.price_update.timestamp as i64) {
+            embed = embed.timestamp(timestamp);
+        }
+
+        embed
+    }
+}
+
+impl ToEmbed for EnrichedSale {
+    fn to_embed(&self) -> CreateEmbed {
+        let mut embed = item_embed(&self.item)
+            .colour(SALE_COLOUR)
+            .url(format!("{}{}", ITEM_SALE_URL, self.sale.sale_id))
+            .description(format!(
+                "Sold for **{}** ({:.1}% off)",
+                self.sale.sale_price,
+                self.discount_percent()
+            ))
+            .fields(item_fields(&self.item))
+            .field(
+                "RAP Change",
+                format!("{} -> {}", self.sale.old_rap, self.sale.new_rap),
+                true,
+            );
+
+        if let Some(value_updated_at) = self.value_updated_at {
+            embed = embed.field("Value Updated", format!("<t:{}:R>", value_updated_at), true);
+        }
+
+        if let Ok(timestamp) = Timestamp::from_unix_timestamp(self.sale.timestamp as i64) {
+            embed = embed.timestamp(timestamp);
+        }
+
+        embed
+    }
+}
+
+impl ToEmbed for TradeAd {
+    fn to_embed(&self) -> CreateEmbed {
+        let mut offer = self
+            .offer
+            .items
+            .iter()
+            .map(|item_id| item_link(*item_id))
+            .collect::<Vec<_>>();
+
+        if let Some(robux) = self.offer.robux {
+            offer.push(format!("{} Robux", robux));
+        }
+
+        let mut request = self
+            .request
+            .items
+            .iter()
+            .map(|item_id| item_link(*item_id))
+            .collect::<Vec<_>>();
+
+        request.extend(self.request.tags.iter().map(|tag| format!("{:?}", tag)));
+
+        let mut embed = CreateEmbed::new()
+            .title(format!("Trade Ad by {}", self.username))
+            .url(format!("{}{}", PLAYER_URL, self.user_id))
+            .colour(TRADE_AD_COLOUR)
+            .field("Offer", list_or_none(offer), true)
+            .field("Request", list_or_none(request), true)
+            .footer(CreateEmbedFooter::new(format!(
+                "Trade Ad {}",
+                self.trade_id
+            )));
+
+        if let Ok(timestamp) = Timestamp::from_unix_timestamp(self.timestamp as i64) {
+            embed = embed.timestamp(timestamp);
+        }
+
+        embed
+    }
+}
+
+fn item_embed(item: &ItemDetails) -> CreateEmbed {
+    let title = match &item.acronym {
+        Some(acronym) => format!("{} ({})", item.item_name, acronym),
+        None => item.item_name.clone(),
+    };
+
+    CreateEmbed::new()
+        .title(title)
+        .url(format!("{}{}", ITEM_URL, item.item_id))
+}
+
+fn item_fields(item: &ItemDetails) -> Vec<(&'static str, String, bool)> {
+    let value = if item.valued {
+        item.value.to_string()
+    } else {
+        "None".to_string()
+    };
+
+    vec![
+        ("RAP", item.rap.to_string(), true),
+        ("Value", value, true),
+        ("Demand", format!("{:?}", item.demand), true),
+    ]
+}
+
+fn item_link(item_id: u64) -> String {
+    format!("[{}]({}{})", item_id, ITEM_URL, item_id)
+}
+
+fn list_or_none(list: Vec<String>) -> String {
+    if list.is_empty() {
+        "None".to_string()
+    } else {
+        list.join("\n")
+    }
+}
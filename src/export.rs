@@ -0,0 +1,320 @@
+use crate::items::ItemDetails;
+use crate::market_activity::Sale;
+use crate::trade_ads::TradeAd;
+use crate::RoliError;
+use arrow_array::builder::{ListBuilder, StringBuilder, UInt64Builder};
+use arrow_array::{
+    ArrayRef, BooleanArray, RecordBatch, StringArray, TimestampSecondArray, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use std::sync::Arc;
+
+/// A type that can be converted into rows of an Arrow [`RecordBatch`].
+///
+/// Implemented for [`Sale`], [`ItemDetails`], and [`TradeAd`]. Timestamps are
+/// exported as second precision Arrow timestamps.
+pub trait ArrowRecord: Sized {
+    /// The Arrow schema used for a batch of this type.
+    fn schema() -> SchemaRef;
+
+    /// Converts a slice of this type into a [`RecordBatch`] using [`ArrowRecord::schema`].
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, RoliError>;
+}
+
+/// Converts a slice of [`Sale`], [`ItemDetails`], or [`TradeAd`] into an Arrow [`RecordBatch`].
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let sales = client.recent_sales().await?;
+/// let batch = roli::export::to_arrow(&sales)?;
+/// println!("Exported {} sales", batch.num_rows());
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_arrow<T: ArrowRecord>(rows: &[T]) -> Result<RecordBatch, RoliError> {
+    T::to_record_batch(rows)
+}
+
+/// Writes a slice of [`Sale`], [`ItemDetails`], or [`TradeAd`] to a Parquet file at `path`.
+///
+/// The file is created if it does not exist and overwritten if it does.
+#[cfg(feature = "parquet")]
+pub fn to_parquet<T: ArrowRecord>(
+    rows: &[T],
+    path: impl AsRef<std::path::Path>,
+) -> Result<(), RoliError> {
+    use parquet::arrow::ArrowWriter;
+    use parquet::errors::ParquetError;
+
+    let batch = T::to_record_batch(rows)?;
+
+    let file = std::fs::File::create(path)
+        .map_err(|e| RoliError::ParquetError(ParquetError::External(Box::new(e))))?;
+
+    let mut writer =
+        ArrowWriter::try_new(file, batch.schema(), None).map_err(RoliError::ParquetError)?;
+
+    writer.write(&batch).map_err(RoliError::ParquetError)?;
+    writer.close().map_err(RoliError::ParquetError)?;
+
+    Ok(())
+}
+
+fn timestamp_field(name: &str) -> Field {
+    Field::new(name, DataType::Timestamp(TimeUnit::Second, None), false)
+}
+
+fn u64_list_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::List(Arc::new(Field::new("item", DataType::UInt64, true))),
+        false,
+    )
+}
+
+impl ArrowRecord for Sale {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("sale_id", DataType::UInt64, false),
+            timestamp_field("timestamp"),
+            Field::new("item_id", DataType::UInt64, false),
+            Field::new("old_rap", DataType::UInt64, false),
+            Field::new("new_rap", DataType::UInt64, false),
+            Field::new("sale_price", DataType::UInt64, false),
+        ]))
+    }
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, RoliError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|x| x.sale_id),
+            )),
+            Arc::new(TimestampSecondArray::from_iter_values(
+                rows.iter().map(|x| x.timestamp as i64),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|x| x.item_id),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|x| x.old_rap),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|x| x.new_rap),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|x| x.sale_price),
+            )),
+        ];
+
+        RecordBatch::try_new(Self::schema(), columns).map_err(RoliError::ArrowError)
+    }
+}
+
+impl ArrowRecord for ItemDetails {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("item_id", DataType::UInt64, false),
+            Field::new("item_name", DataType::Utf8, false),
+            Field::new("acronym", DataType::Utf8, true),
+            Field::new("rap", DataType::UInt64, false),
+            Field::new("valued", DataType::Boolean, false),
+            Field::new("value", DataType::UInt64, false),
+            Field::new("demand", DataType::Utf8, false),
+            Field::new("trend", DataType::Utf8, false),
+            Field::new("projected", DataType::Boolean, false),
+            Field::new("hyped", DataType::Boolean, false),
+            Field::new("rare", DataType::Boolean, false),
+        ]))
+    }
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, RoliError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|x| x.item_id),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|x| x.item_name.as_str()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|x| x.acronym.as_deref()),
+            )),
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|x| x.rap))),
+            Arc::new(BooleanArray::from_iter(rows.iter().map(|x| Some(x.valued)))),
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|x| x.value))),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|x| format!("{:?}", x.demand)),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|x| format!("{:?}", x.trend)),
+            )),
+            Arc::new(BooleanArray::from_iter(
+                rows.iter().map(|x| Some(x.projected)),
+            )),
+            Arc::new(BooleanArray::from_iter(rows.iter().map(|x| Some(x.hyped)))),
+            Arc::new(BooleanArray::from_iter(rows.iter().map(|x| Some(x.rare)))),
+        ];
+
+        RecordBatch::try_new(Self::schema(), columns).map_err(RoliError::ArrowError)
+    }
+}
+
+impl ArrowRecord for TradeAd {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("trade_id", DataType::UInt64, false),
+            timestamp_field("timestamp"),
+            Field::new("user_id", DataType::UInt64, false),
+            Field::new("username", DataType::Utf8, false),
+            u64_list_field("offer_items"),
+            Field::new("offer_robux", DataType::UInt64, true),
+            u64_list_field("request_items"),
+            Field::new(
+                "request_tags",
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+                false,
+            ),
+        ]))
+    }
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, RoliError> {
+        let mut offer_items = ListBuilder::new(UInt64Builder::new());
+        let mut request_items = ListBuilder::new(UInt64Builder::new());
+        let mut request_tags = ListBuilder::new(StringBuilder::new());
+
+        for trade_ad in rows {
+            offer_items.values().append_slice(&trade_ad.offer.items);
+            offer_items.append(true);
+
+            request_items.values().append_slice(&trade_ad.request.items);
+            request_items.append(true);
+
+            for tag in &trade_ad.request.tags {
+                request_tags.values().append_value(format!("{:?}", tag));
+            }
+            request_tags.append(true);
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|x| x.trade_id),
+            )),
+            Arc::new(TimestampSecondArray::from_iter_values(
+                rows.iter().map(|x| x.timestamp as i64),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|x| x.user_id),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|x| x.username.as_str()),
+            )),
+            Arc::new(offer_items.finish()),
+            Arc::new(UInt64Array::from_iter(rows.iter().map(|x| x.offer.robux))),
+            Arc::new(request_items.finish()),
+            Arc::new(request_tags.finish()),
+        ];
+
+        RecordBatch::try_new(Self::schema(), columns).map_err(RoliError::ArrowError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trade_ads::{Offer, Request, RequestTag};
+    use arrow_array::{Array, ListArray};
+
+    #[test]
+    fn test_sales_to_arrow() {
+        let sales = vec![
+            Sale {
+                item_id: 1,
+                sale_price: 100,
+                ..Default::default()
+            },
+            Sale {
+                item_id: 2,
+                sale_price: 200,
+                ..Default::default()
+            },
+        ];
+
+        let batch = to_arrow(&sales).unwrap();
+
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 6);
+
+        let prices = batch
+            .column_by_name("sale_price")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+
+        assert_eq!(prices.values(), &[100, 200]);
+    }
+
+    #[test]
+    fn test_items_to_arrow() {
+        let items = vec![ItemDetails {
+            item_id: 1,
+            item_name: "Test item name".to_string(),
+            acronym: None,
+            ..Default::default()
+        }];
+
+        let batch = to_arrow(&items).unwrap();
+
+        assert_eq!(batch.num_rows(), 1);
+        assert!(batch.column_by_name("acronym").unwrap().is_null(0));
+    }
+
+    #[test]
+    fn test_trade_ads_to_arrow() {
+        let trade_ads = vec![TradeAd {
+            trade_id: 1,
+            offer: Offer {
+                items: vec![1, 2],
+                robux: None,
+            },
+            request: Request {
+                items: vec![3],
+                tags: vec![RequestTag::Any, RequestTag::Demand],
+            },
+            ..Default::default()
+        }];
+
+        let batch = to_arrow(&trade_ads).unwrap();
+
+        let offer_items = batch
+            .column_by_name("offer_items")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+
+        assert_eq!(offer_items.value(0).len(), 2);
+        assert!(batch.column_by_name("offer_robux").unwrap().is_null(0));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_to_parquet() {
+        let path = std::env::temp_dir().join("roli_test_to_parquet.parquet");
+
+        let sales = vec![Sale {
+            item_id: 1,
+            ..Default::default()
+        }];
+
+        to_parquet(&sales, &path).unwrap();
+
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert!(metadata.len() > 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+const SUFFIXES: [&str; 4] = ["K", "M", "B", "T"];
+
+/// Formats a value the short way Rolimons does, with three significant digits and a
+/// K, M, B, or T suffix, such as `1.23M`. Values under 1000 are returned as is.
+///
+/// # Example
+/// ```
+/// use roli::fmt::format_value;
+///
+/// assert_eq!(format_value(950), "950");
+/// assert_eq!(format_value(12_345), "12.3K");
+/// assert_eq!(format_value(1_234_567), "1.23M");
+/// assert_eq!(format_value(2_000_000_000), "2B");
+/// ```
+pub fn format_value(value: u64) -> String {
+    if value < 1000 {
+        return value.to_string();
+    }
+
+    let mut scaled = value as f64;
+    let mut suffix = 0;
+
+    loop {
+        scaled /= 1000.0;
+
+        // Rounding can carry into the next suffix, e.g. 999_999 is 1M, not 1000K.
+        let decimals = match scaled {
+            x if x < 10.0 => 2,
+            x if x < 100.0 => 1,
+            _ => 0,
+        };
+        let factor = 10f64.powi(decimals);
+        let rounded = (scaled * factor).round() / factor;
+
+        if rounded < 1000.0 || suffix == SUFFIXES.len() - 1 {
+            let formatted = format!("{:.*}", decimals as usize, rounded);
+            let trimmed = match formatted.contains('.') {
+                true => formatted.trim_end_matches('0').trim_end_matches('.'),
+                false => &formatted,
+            };
+
+            return format!("{}{}", trimmed, SUFFIXES[suffix]);
+        }
+
+        suffix += 1;
+    }
+}
+
+/// Formats a number with a comma between every group of three digits, such as
+/// `1,234,567`, which is how Rolimons shows values and raps on item pages.
+///
+/// # Example
+/// ```
+/// assert_eq!(roli::fmt::format_with_commas(1_234_567), "1,234,567");
+/// ```
+pub fn format_with_commas(value: u64) -> String {
+    let digits = value.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+/// A value or rap that displays with commas (`1,234,567`), or in the short form
+/// (`1.23M`) with the alternate flag (`{:#}`).
+///
+/// # Example
+/// ```
+/// use roli::fmt::Value;
+///
+/// assert_eq!(Value(1_234_567).to_string(), "1,234,567");
+/// assert_eq!(format!("{:#}", Value(1_234_567)), "1.23M");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Value(pub u64);
+
+/// An amount of robux that displays with the `R$` prefix and commas (`R$1,234`), or
+/// in the short form (`R$1.23K`) with the alternate flag (`{:#}`).
+///
+/// # Example
+/// ```
+/// use roli::fmt::Robux;
+///
+/// assert_eq!(Robux(1234).to_string(), "R$1,234");
+/// assert_eq!(format!("{:#}", Robux(1234)), "R$1.23K");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Robux(pub u64);
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.alternate() {
+            true => f.pad(&format_value(self.0)),
+            false => f.pad(&format_with_commas(self.0)),
+        }
+    }
+}
+
+impl fmt::Display for Robux {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.alternate() {
+            true => f.pad(&format!("R${}", format_value(self.0))),
+            false => f.pad(&format!("R${}", format_with_commas(self.0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(0), "0");
+        assert_eq!(format_value(1000), "1K");
+        assert_eq!(format_value(1050), "1.05K");
+        assert_eq!(format_value(123_456), "123K");
+        assert_eq!(format_value(999_999), "1M");
+        assert_eq!(format_value(1_200_000), "1.2M");
+        assert_eq!(format_value(5_000_000_000_000_000), "5000T");
+    }
+
+    #[test]
+    fn test_format_with_commas() {
+        assert_eq!(format_with_commas(0), "0");
+        assert_eq!(format_with_commas(999), "999");
+        assert_eq!(format_with_commas(1000), "1,000");
+        assert_eq!(format_with_commas(123_456), "123,456");
+    }
+}
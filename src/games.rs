@@ -1,12 +1,19 @@
+use crate::cdn::RobloxCdnImage;
 use crate::RoliError;
-use crate::{Client, Code};
+use crate::{Client, Code, Endpoint, ResponseMetadata};
 use reqwest::header;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// The version of the schema [`Game`] is parsed with, recorded in [`GamesSnapshot::metadata`].
+///
+/// This is bumped whenever a change to parsing would make old snapshots read differently.
+pub const SCHEMA_VERSION: u32 = 1;
+
 const GAMES_LIST_URL: &str = "https://www.rolimons.com/gameapi/gamelist";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct GamesListResponse {
     success: bool,
     game_count: i64,
@@ -24,10 +31,141 @@ pub struct Game {
     /// The amount of players currently playing the game.
     pub players_active: u64,
     /// The thumbnail url of the game. This comes from Roblox's cdn and
-    /// not Rolimons.
+    /// not Rolimons. Use [`Game::thumbnail`] for a parsed form of it.
     pub thumbnail_url: String,
 }
 
+/// A list of games with the helpers dashboards usually need on top of
+/// [`Client::games_list`].
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::games::GameTable;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let games = GameTable::new(client.games_list().await?);
+///
+/// println!("{} players across all games", games.total_players());
+///
+/// for game in games.top_by_players(10) {
+///     println!("{}: {}", game.name, game.players_active);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameTable {
+    games: Vec<Game>,
+}
+
+/// The Rolimons game list at a point in time, as returned by [`Client::games_list_snapshot`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GamesSnapshot {
+    /// When the snapshot was taken and the schema version it was parsed with.
+    #[serde(default)]
+    pub metadata: ResponseMetadata,
+    /// Every game in the snapshot.
+    pub games: GameTable,
+}
+
+impl Game {
+    /// Parses [`Game::thumbnail_url`], returning `None` if it is not a Roblox cdn url.
+    pub fn thumbnail(&self) -> Option<RobloxCdnImage> {
+        self.thumbnail_url.parse().ok()
+    }
+}
+
+impl GamesSnapshot {
+    /// Creates a snapshot from the games fetched at `fetched_at`, tagged with the
+    /// current [`SCHEMA_VERSION`].
+    pub fn new(fetched_at: u64, games: Vec<Game>) -> Self {
+        Self {
+            metadata: ResponseMetadata::new(SCHEMA_VERSION, fetched_at),
+            games: GameTable::new(games),
+        }
+    }
+}
+
+impl GameTable {
+    /// Creates a table from a list of games.
+    pub fn new(games: Vec<Game>) -> Self {
+        Self { games }
+    }
+
+    /// Returns the game with the given Roblox id, if it is in the table.
+    pub fn get(&self, id: u64) -> Option<&Game> {
+        self.games.iter().find(|game| game.id == id)
+    }
+
+    /// Returns the amount of games in the table.
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    /// Returns whether the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    /// Returns an iterator over the games in the table.
+    pub fn iter(&self) -> impl Iterator<Item = &Game> {
+        self.games.iter()
+    }
+
+    /// Returns every game sorted from most to least active players.
+    ///
+    /// Games with the same amount of players are ordered by id.
+    pub fn sorted_by_players(&self) -> Vec<&Game> {
+        let mut games = self.games.iter().collect::<Vec<_>>();
+
+        games.sort_by(|a, b| {
+            b.players_active
+                .cmp(&a.players_active)
+                .then(a.id.cmp(&b.id))
+        });
+
+        games
+    }
+
+    /// Returns the `n` games with the most active players, from most to least.
+    pub fn top_by_players(&self, n: usize) -> Vec<&Game> {
+        let mut games = self.sorted_by_players();
+        games.truncate(n);
+        games
+    }
+
+    /// Returns every game whose name contains `query`, ignoring case.
+    pub fn find_by_name_contains(&self, query: &str) -> Vec<&Game> {
+        let query = query.to_lowercase();
+
+        self.games
+            .iter()
+            .filter(|game| game.name.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Returns the total amount of active players across every game in the table.
+    pub fn total_players(&self) -> u64 {
+        self.games.iter().map(|game| game.players_active).sum()
+    }
+}
+
+impl FromIterator<Game> for GameTable {
+    fn from_iter<T: IntoIterator<Item = Game>>(iter: T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl From<Vec<Game>> for GameTable {
+    fn from(games: Vec<Game>) -> Self {
+        Self::new(games)
+    }
+}
+
 impl Client {
     /// Returns the Rolimons list of games.
     ///
@@ -55,60 +193,99 @@ impl Client {
     /// # }
     /// ```
     pub async fn games_list(&self) -> Result<Vec<Game>, RoliError> {
-        let request_result = self
+        let request = self
             .reqwest_client
             .get(GAMES_LIST_URL)
-            .header(header::USER_AGENT, crate::USER_AGENT)
-            .send()
-            .await;
-
-        match request_result {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                match status_code {
-                    200 => {
-                        let raw = match response.json::<GamesListResponse>().await {
-                            Ok(x) => x,
-                            Err(_) => return Err(RoliError::MalformedResponse),
-                        };
-
-                        if !raw.success {
-                            return Err(RoliError::RequestReturnedUnsuccessful);
-                        }
-
-                        let mut games = Vec::new();
-
-                        for (id, game) in raw.games {
-                            let id = match id.parse::<u64>() {
-                                Ok(x) => x,
-                                Err(_) => return Err(RoliError::MalformedResponse),
-                            };
-
-                            let name = game[0].to_string();
-                            let players_active = match game[1].to_i64() {
-                                Ok(x) => x as u64,
-                                Err(_) => return Err(RoliError::MalformedResponse),
-                            };
-
-                            let thumbnail_url = game[2].to_string();
-
-                            games.push(Game {
-                                id,
-                                name,
-                                players_active,
-                                thumbnail_url,
-                            });
-                        }
-
-                        Ok(games)
-                    }
-                    429 => Err(RoliError::TooManyRequests),
-                    500 => Err(RoliError::InternalServerError),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
-            Err(e) => Err(RoliError::ReqwestError(e)),
+            .header(header::USER_AGENT, self.user_agent());
+
+        let raw = self
+            .request_json::<GamesListResponse>(Endpoint::GamesList, request)
+            .await?;
+
+        if !raw.success {
+            return Err(RoliError::RequestReturnedUnsuccessful);
         }
+
+        let mut games = Vec::new();
+
+        for (id, game) in raw.games {
+            let id = match id.parse::<u64>() {
+                Ok(x) => x,
+                Err(_) => return Err(RoliError::MalformedResponse),
+            };
+
+            let name = game[0].to_string();
+            let players_active = match game[1].to_i64() {
+                Ok(x) => x as u64,
+                Err(_) => return Err(RoliError::MalformedResponse),
+            };
+
+            let thumbnail_url = game[2].to_string();
+
+            games.push(Game {
+                id,
+                name,
+                players_active,
+                thumbnail_url,
+            });
+        }
+
+        Ok(games)
+    }
+
+    /// The same as [`Client::games_list`], but returns a [`GamesSnapshot`] tagged
+    /// with the time it was fetched and the schema version it was parsed with.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let snapshot = client.games_list_snapshot().await?;
+    /// println!("{} games", snapshot.games.len());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn games_list_snapshot(&self) -> Result<GamesSnapshot, RoliError> {
+        let games = self.games_list().await?;
+
+        Ok(GamesSnapshot::new(crate::unix_timestamp(), games))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(id: u64, name: &str, players_active: u64) -> Game {
+        Game {
+            id,
+            name: name.to_string(),
+            players_active,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_game_table() {
+        let games = GameTable::new(vec![
+            game(1, "Jailbreak", 50_000),
+            game(2, "Adopt Me!", 200_000),
+            game(3, "Jailbreak Simulator", 50_000),
+            game(4, "Brookhaven", 400_000),
+        ]);
+
+        let top = games.top_by_players(3);
+        assert_eq!(top.iter().map(|x| x.id).collect::<Vec<_>>(), vec![4, 2, 1]);
+
+        let found = games.find_by_name_contains("JAILBREAK");
+        assert_eq!(found.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1, 3]);
+
+        assert_eq!(games.total_players(), 700_000);
+        assert_eq!(games.get(2).unwrap().name, "Adopt Me!");
+        assert_eq!(games.top_by_players(10).len(), 4);
     }
 }
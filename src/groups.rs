@@ -1,11 +1,14 @@
+use crate::cdn::RobloxCdnImage;
 use crate::RoliError;
-use crate::{Client, Code};
+use crate::{Client, Code, Endpoint};
 use reqwest::header;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 const GROUP_SEARCH_URL: &str = "https://www.rolimons.com/groupapi/search?searchstring=";
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct GroupSearchResponse {
     success: bool,
     result_count: i64,
@@ -23,11 +26,118 @@ pub struct GroupSearchResult {
     pub name: String,
     /// The amount of members in the group.
     pub member_count: u64,
-    /// The thumbnail url of the group. This comes from Roblox's cdn.
+    /// The thumbnail url of the group. This comes from Roblox's cdn. Use
+    /// [`GroupSearchResult::thumbnail`] for a parsed form of it.
     pub thumbnail_url: String,
 }
 
+/// A change between two snapshots of a set of groups, as returned by [`diff`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum GroupChange {
+    /// The group's member count went up.
+    MembersGained {
+        /// The group, with its new member count.
+        group: GroupSearchResult,
+        /// The member count of the group in the old snapshot.
+        previous_member_count: u64,
+    },
+    /// The group's member count went down.
+    MembersLost {
+        /// The group, with its new member count.
+        group: GroupSearchResult,
+        /// The member count of the group in the old snapshot.
+        previous_member_count: u64,
+    },
+    /// The group was not in the old snapshot.
+    NewlyTracked(GroupSearchResult),
+}
+
+impl GroupChange {
+    /// Returns the group the change is about.
+    pub fn group(&self) -> &GroupSearchResult {
+        match self {
+            Self::MembersGained { group, .. } => group,
+            Self::MembersLost { group, .. } => group,
+            Self::NewlyTracked(group) => group,
+        }
+    }
+
+    /// Returns the change in member count. This is the full member count for
+    /// [`GroupChange::NewlyTracked`].
+    pub fn member_delta(&self) -> i64 {
+        match self {
+            Self::MembersGained {
+                group,
+                previous_member_count,
+            }
+            | Self::MembersLost {
+                group,
+                previous_member_count,
+            } => group.member_count as i64 - *previous_member_count as i64,
+            Self::NewlyTracked(group) => group.member_count as i64,
+        }
+    }
+}
+
+/// Compares two snapshots of groups (such as two [`Client::group_search`] results)
+/// and returns every change between them, in the order the groups appear in `new`.
+///
+/// Groups that are in `old` but missing from `new` are ignored, as search results
+/// do not always contain every group.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// #
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let old = client.group_search("Tetra").await?;
+/// // ...some time later...
+/// let new = client.group_search("Tetra").await?;
+///
+/// for change in roli::groups::diff(&old, &new) {
+///     println!("{}: {:+}", change.group().name, change.member_delta());
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn diff(old: &[GroupSearchResult], new: &[GroupSearchResult]) -> Vec<GroupChange> {
+    let old = old
+        .iter()
+        .map(|group| (group.id, group.member_count))
+        .collect::<HashMap<_, _>>();
+
+    new.iter()
+        .filter_map(|group| {
+            let previous_member_count = match old.get(&group.id) {
+                Some(x) => *x,
+                None => return Some(GroupChange::NewlyTracked(group.clone())),
+            };
+
+            match group.member_count.cmp(&previous_member_count) {
+                std::cmp::Ordering::Greater => Some(GroupChange::MembersGained {
+                    group: group.clone(),
+                    previous_member_count,
+                }),
+                std::cmp::Ordering::Less => Some(GroupChange::MembersLost {
+                    group: group.clone(),
+                    previous_member_count,
+                }),
+                std::cmp::Ordering::Equal => None,
+            }
+        })
+        .collect()
+}
+
 impl GroupSearchResult {
+    /// Parses [`GroupSearchResult::thumbnail_url`], returning `None` if it is not a
+    /// Roblox cdn url.
+    pub fn thumbnail(&self) -> Option<RobloxCdnImage> {
+        self.thumbnail_url.parse().ok()
+    }
+
     /// Converts a vector of [`Code`] into a [`GroupSearchResult`].
     fn from_raw(codes: Vec<Code>) -> Result<Self, RoliError> {
         // Follows form of:
@@ -54,9 +164,9 @@ impl GroupSearchResult {
             return Err(RoliError::MalformedResponse);
         }
 
-        let id = codes[0].to_i64()? as u64;
+        let id = codes[0].to_u64_lenient()?;
         let name = codes[1].to_string();
-        let member_count = codes[5].to_i64()? as u64;
+        let member_count = codes[5].to_u64_lenient()?;
         let thumbnail_url = codes[6].to_string();
 
         Ok(Self {
@@ -94,42 +204,66 @@ impl Client {
     ) -> Result<Vec<GroupSearchResult>, RoliError> {
         let formatted_url = format!("{}{}", GROUP_SEARCH_URL, group_name);
 
-        let request_result = self
+        let request = self
             .reqwest_client
             .get(formatted_url)
-            .header(header::USER_AGENT, crate::USER_AGENT)
-            .send()
-            .await;
-
-        match request_result {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                match status_code {
-                    200 => {
-                        let raw = match response.json::<GroupSearchResponse>().await {
-                            Ok(x) => x,
-                            Err(_) => return Err(RoliError::MalformedResponse),
-                        };
-
-                        if !raw.success {
-                            return Err(RoliError::RequestReturnedUnsuccessful);
-                        }
-
-                        let mut search_outputs = Vec::new();
-
-                        for group in raw.groups {
-                            search_outputs.push(GroupSearchResult::from_raw(group)?);
-                        }
-
-                        Ok(search_outputs)
-                    }
-                    429 => Err(RoliError::TooManyRequests),
-                    500 => Err(RoliError::InternalServerError),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
-            Err(e) => Err(RoliError::ReqwestError(e)),
+            .header(header::USER_AGENT, self.user_agent());
+
+        let raw = self
+            .request_json::<GroupSearchResponse>(Endpoint::GroupSearch, request)
+            .await?;
+
+        if !raw.success {
+            return Err(RoliError::RequestReturnedUnsuccessful);
+        }
+
+        let mut search_outputs = Vec::new();
+
+        for group in raw.groups {
+            search_outputs.push(GroupSearchResult::from_raw(group)?);
+        }
+
+        Ok(search_outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(id: u64, member_count: u64) -> GroupSearchResult {
+        GroupSearchResult {
+            id,
+            member_count,
+            ..Default::default()
         }
     }
+
+    #[test]
+    fn test_diff() {
+        let old = vec![group(1, 100), group(2, 200), group(3, 300), group(4, 400)];
+        let new = vec![group(1, 150), group(2, 190), group(3, 300), group(5, 50)];
+
+        let changes = diff(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                GroupChange::MembersGained {
+                    group: group(1, 150),
+                    previous_member_count: 100
+                },
+                GroupChange::MembersLost {
+                    group: group(2, 190),
+                    previous_member_count: 200
+                },
+                GroupChange::NewlyTracked(group(5, 50)),
+            ]
+        );
+
+        assert_eq!(
+            changes.iter().map(|x| x.member_delta()).collect::<Vec<_>>(),
+            vec![50, -10, 50]
+        );
+    }
 }
@@ -0,0 +1,212 @@
+use crate::deals::Deal;
+use crate::items::{ItemChange, ItemInfoProvider};
+use crate::market_activity::Sale;
+use crate::trade_ads::TradeAd;
+use crate::{Event, RoliError};
+use futures_util::{Stream, StreamExt};
+use std::borrow::Cow;
+use std::future::Future;
+
+/// Callbacks for the events produced by this crate's feeds and streams.
+///
+/// Every method does nothing by default, so a handler only implements the events it
+/// cares about. Handlers are driven by [`run`] (or [`dispatch`] for single events),
+/// which is an alternative to writing a `select` loop over several streams by hand.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use futures_util::{stream, StreamExt};
+/// use roli::deals::Deal;
+/// use roli::handler::{self, EventHandler};
+/// use roli::items::ItemDetailsTable;
+/// use roli::market_activity::Sale;
+/// use roli::Event;
+/// use std::time::Duration;
+///
+/// struct Notifier;
+///
+/// impl EventHandler for Notifier {
+///     async fn on_sale(&self, sale: Sale) {
+///         println!("{} sold for {}", sale.item_id, sale.sale_price);
+///     }
+///
+///     async fn on_deal(&self, deal: Deal) {
+///         println!("{} is {:.1}% off", deal.item.item_name, deal.deal_percent());
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+///
+/// let sales = client.sale_stream(Duration::from_secs(3)).map(|x| x.map(Event::from));
+/// let trade_ads = client
+///     .trade_ad_stream(Duration::from_secs(60))
+///     .map(|x| x.map(Event::from));
+///
+/// handler::run(&Notifier, stream::select(sales, trade_ads), &table).await;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub trait EventHandler: Send + Sync {
+    /// Called for every sale.
+    fn on_sale(&self, sale: Sale) -> impl Future<Output = ()> + Send {
+        let _ = sale;
+        async {}
+    }
+
+    /// Called for every price update of an item in the item details table, paired
+    /// with the item's details.
+    fn on_deal(&self, deal: Deal) -> impl Future<Output = ()> + Send {
+        let _ = deal;
+        async {}
+    }
+
+    /// Called for every trade ad.
+    fn on_trade_ad(&self, trade_ad: TradeAd) -> impl Future<Output = ()> + Send {
+        let _ = trade_ad;
+        async {}
+    }
+
+    /// Called for every change in an item's details.
+    fn on_item_change(&self, item_change: ItemChange) -> impl Future<Output = ()> + Send {
+        let _ = item_change;
+        async {}
+    }
+
+    /// Called for every event without a method of its own, such as rap updates and
+    /// price updates of items that are not in the item details table.
+    fn on_event(&self, event: Event) -> impl Future<Output = ()> + Send {
+        let _ = event;
+        async {}
+    }
+
+    /// Called for every error yielded by the stream passed to [`run`].
+    fn on_error(&self, error: RoliError) -> impl Future<Output = ()> + Send {
+        let _ = error;
+        async {}
+    }
+}
+
+/// Calls the method of `handler` that matches `event`.
+///
+/// Price updates are paired with their item in `table` and passed to
+/// [`EventHandler::on_deal`]. Price updates of items missing from `table` are passed
+/// to [`EventHandler::on_event`] instead.
+pub async fn dispatch<H: EventHandler>(handler: &H, event: Event, table: &impl ItemInfoProvider) {
+    match event {
+        Event::Sale(sale) => handler.on_sale(sale).await,
+        Event::PriceUpdate(price_update) => match table
+            .item_details(price_update.item_id)
+            .map(Cow::into_owned)
+        {
+            Some(item) => handler.on_deal(Deal::new(price_update, item)).await,
+            None => handler.on_event(Event::PriceUpdate(price_update)).await,
+        },
+        Event::TradeAd(trade_ad) => handler.on_trade_ad(trade_ad).await,
+        Event::ItemChange(item_change) => handler.on_item_change(item_change).await,
+        event => handler.on_event(event).await,
+    }
+}
+
+/// Drives `handler` with every item of `events` until the stream ends, one event at a
+/// time. Errors are passed to [`EventHandler::on_error`] and do not stop the loop.
+///
+/// Streams of different event types can be merged into one with [`Event::from`] and
+/// `futures_util::stream::select`.
+pub async fn run<H, S>(handler: &H, events: S, table: &impl ItemInfoProvider)
+where
+    H: EventHandler,
+    S: Stream<Item = Result<Event, RoliError>>,
+{
+    futures_util::pin_mut!(events);
+
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(event) => dispatch(handler, event, table).await,
+            Err(e) => handler.on_error(e).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deals::{PriceUpdate, RapUpdate};
+    use crate::items::{ItemDetails, ItemDetailsTable};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl Recorder {
+        fn record(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+    }
+
+    impl EventHandler for Recorder {
+        async fn on_sale(&self, sale: Sale) {
+            self.record(format!("sale {}", sale.item_id));
+        }
+
+        async fn on_deal(&self, deal: Deal) {
+            self.record(format!("deal {}", deal.item.item_name));
+        }
+
+        async fn on_event(&self, event: Event) {
+            self.record(format!("event {}", event.kind()));
+        }
+
+        async fn on_error(&self, error: RoliError) {
+            self.record(format!("error {}", error));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let table = ItemDetailsTable::new(vec![ItemDetails {
+            item_id: 1,
+            item_name: "Test Item".to_string(),
+            ..Default::default()
+        }]);
+
+        let price_update = |item_id| {
+            Event::PriceUpdate(PriceUpdate {
+                item_id,
+                ..Default::default()
+            })
+        };
+
+        let events = futures_util::stream::iter(vec![
+            Ok(Event::Sale(Sale {
+                item_id: 1,
+                ..Default::default()
+            })),
+            Ok(price_update(1)),
+            Ok(price_update(2)),
+            Ok(Event::RapUpdate(RapUpdate::default())),
+            Ok(Event::TradeAd(TradeAd::default())),
+            Err(RoliError::TooManyRequests),
+        ]);
+
+        let recorder = Recorder::default();
+        run(&recorder, events, &table).await;
+
+        assert_eq!(
+            recorder.calls.into_inner().unwrap(),
+            vec![
+                "sale 1",
+                "deal Test Item",
+                "event price_update",
+                "event rap_update",
+                "error Too Many Requests",
+            ]
+        );
+    }
+}
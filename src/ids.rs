@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+macro_rules! id {
+    ($(#[$attr:meta])* $name:ident, $kind:ident) => {
+        $(#[$attr])*
+        #[derive(
+            Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub u64);
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        /// Parses either a plain id or a url of the same kind (see [`parse_url`]).
+        impl FromStr for $name {
+            type Err = ParseIdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let s = s.trim();
+
+                if let Ok(id) = s.parse() {
+                    return Ok(Self(id));
+                }
+
+                match parse_url(s)? {
+                    Id::$kind(id) => Ok(id),
+                    _ => Err(ParseIdError),
+                }
+            }
+        }
+    };
+}
+
+id!(
+    /// The id of a limited item, which is the same on Rolimons and Roblox.
+    ItemId,
+    Item
+);
+
+id!(
+    /// The id of a player, which is their Roblox user id.
+    PlayerId,
+    Player
+);
+
+id!(
+    /// The Roblox id of a group.
+    GroupId,
+    Group
+);
+
+id!(
+    /// The Rolimons id of a sale, as found in [`Sale::sale_id`](crate::market_activity::Sale::sale_id).
+    SaleId,
+    Sale
+);
+
+id!(
+    /// The Roblox place id of a game.
+    GameId,
+    Game
+);
+
+/// An id extracted from a Rolimons or Roblox url by [`parse_url`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Id {
+    /// From `rolimons.com/item/{id}` or `roblox.com/catalog/{id}`.
+    Item(ItemId),
+    /// From `rolimons.com/player/{id}` or `roblox.com/users/{id}`.
+    Player(PlayerId),
+    /// From `rolimons.com/group/{id}`, `roblox.com/groups/{id}`, or `roblox.com/communities/{id}`.
+    Group(GroupId),
+    /// From `rolimons.com/itemsale/{id}`.
+    Sale(SaleId),
+    /// From `rolimons.com/game/{id}` or `roblox.com/games/{id}`.
+    Game(GameId),
+}
+
+/// The error returned when a string is not an id or a supported url.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ParseIdError;
+
+/// Extracts the id from a pasted Rolimons or Roblox url.
+///
+/// The scheme, `www.` (or any other subdomain), trailing path segments such as
+/// Roblox's item names, query strings, and fragments are all optional.
+///
+/// # Example
+/// ```
+/// use roli::ids::{self, Id, ItemId, PlayerId};
+///
+/// assert_eq!(
+///     ids::parse_url("https://www.rolimons.com/item/1365767"),
+///     Ok(Id::Item(ItemId(1365767)))
+/// );
+/// assert_eq!(
+///     ids::parse_url("roblox.com/users/2207291/profile"),
+///     Ok(Id::Player(PlayerId(2207291)))
+/// );
+///
+/// // Chat commands can accept either form.
+/// assert_eq!("1365767".parse(), Ok(ItemId(1365767)));
+/// assert_eq!("<https://www.roblox.com/catalog/1365767/Valkyrie-Helm>".parse(), Ok(ItemId(1365767)));
+/// ```
+pub fn parse_url(url: &str) -> Result<Id, ParseIdError> {
+    // Discord wraps urls in angle brackets to suppress embeds.
+    let url = url
+        .trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .trim();
+
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+
+    let mut segments = url.split('/').filter(|x| !x.is_empty());
+
+    let host = segments.next().ok_or(ParseIdError)?.to_lowercase();
+    let kind = segments.next().ok_or(ParseIdError)?.to_lowercase();
+    let id = segments
+        .next()
+        .and_then(|x| x.parse::<u64>().ok())
+        .ok_or(ParseIdError)?;
+
+    if is_host(&host, "rolimons.com") {
+        match kind.as_str() {
+            "item" => Ok(Id::Item(ItemId(id))),
+            "player" => Ok(Id::Player(PlayerId(id))),
+            "group" | "groups" => Ok(Id::Group(GroupId(id))),
+            "itemsale" => Ok(Id::Sale(SaleId(id))),
+            "game" => Ok(Id::Game(GameId(id))),
+            _ => Err(ParseIdError),
+        }
+    } else if is_host(&host, "roblox.com") {
+        match kind.as_str() {
+            "catalog" => Ok(Id::Item(ItemId(id))),
+            "users" => Ok(Id::Player(PlayerId(id))),
+            "groups" | "communities" => Ok(Id::Group(GroupId(id))),
+            "games" => Ok(Id::Game(GameId(id))),
+            _ => Err(ParseIdError),
+        }
+    } else {
+        Err(ParseIdError)
+    }
+}
+
+/// Returns whether `host` is `domain` or a subdomain of it.
+fn is_host(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|x| x.ends_with('.'))
+}
+
+impl fmt::Display for ParseIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not an id or a supported Rolimons or Roblox url")
+    }
+}
+
+impl std::error::Error for ParseIdError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        let cases = [
+            (
+                "https://www.rolimons.com/item/1365767",
+                Id::Item(ItemId(1365767)),
+            ),
+            ("rolimons.com/player/2207291", Id::Player(PlayerId(2207291))),
+            (
+                "https://www.rolimons.com/group/4843918",
+                Id::Group(GroupId(4843918)),
+            ),
+            (
+                "https://www.rolimons.com/itemsale/4991002",
+                Id::Sale(SaleId(4991002)),
+            ),
+            (
+                "https://www.rolimons.com/game/606849621",
+                Id::Game(GameId(606849621)),
+            ),
+            (
+                "https://www.roblox.com/catalog/1365767/Valkyrie-Helm?ref=1",
+                Id::Item(ItemId(1365767)),
+            ),
+            (
+                "https://web.roblox.com/users/2207291/profile",
+                Id::Player(PlayerId(2207291)),
+            ),
+            (
+                "https://www.roblox.com/groups/4843918/Tetra-Games#!/about",
+                Id::Group(GroupId(4843918)),
+            ),
+            (
+                "https://www.roblox.com/communities/4843918",
+                Id::Group(GroupId(4843918)),
+            ),
+            (
+                "<https://www.roblox.com/games/606849621/Jailbreak>",
+                Id::Game(GameId(606849621)),
+            ),
+        ];
+
+        for (url, id) in cases {
+            assert_eq!(parse_url(url), Ok(id), "{}", url);
+        }
+
+        for url in [
+            "",
+            "1365767",
+            "https://www.rolimons.com/item/",
+            "https://www.rolimons.com/trades/123",
+            "https://notrolimons.com/item/123",
+            "https://www.roblox.com/item/123",
+        ] {
+            assert_eq!(parse_url(url), Err(ParseIdError), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(" 123 ".parse(), Ok(ItemId(123)));
+        assert_eq!("https://www.rolimons.com/item/123".parse(), Ok(ItemId(123)));
+        assert_eq!(
+            "https://www.rolimons.com/player/123".parse::<ItemId>(),
+            Err(ParseIdError)
+        );
+        assert_eq!(u64::from(PlayerId(5)), 5);
+        assert_eq!(SaleId(7).to_string(), "7");
+    }
+}
@@ -1,7 +1,22 @@
-use crate::{Client, Code, RoliError};
+use crate::market_activity::Sale;
+use crate::{Client, Code, Endpoint, ResponseMetadata, RoliError};
 use reqwest::header;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Contains the translation between Roblox asset ids and Rolimons item ids.
+pub mod id_translation;
+/// Contains a heuristic detector for items that are likely projected.
+pub mod projected_detector;
+/// Contains an analyzer for item trends across multiple snapshots.
+pub mod trend_analyzer;
+
+/// The version of the schema [`ItemDetails`] is parsed with, recorded in [`Snapshot::metadata`].
+///
+/// This is bumped whenever a change to parsing would make old snapshots read differently.
+pub const SCHEMA_VERSION: u32 = 1;
 
 const ITEM_DETAILS_API: &str = "https://www.rolimons.com/itemapi/itemdetails";
 
@@ -72,15 +87,144 @@ pub struct ItemDetails {
     pub rare: bool,
 }
 
+/// A strategy used by [`rank_by`] to score items.
+///
+/// Higher scores are ranked first.
+#[derive(Clone, Copy, Debug)]
+pub enum RankingStrategy<'a> {
+    /// Scores an item by how much value each robux of rap buys (`value / rap`).
+    /// Unvalued items and items without rap score 0.
+    ValueDensity,
+    /// Scores an item by its value (or rap if unvalued) multiplied by [`Demand::multiplier`].
+    DemandAdjustedValue,
+    /// Scores an item by how many times it appears in the given sales, which is
+    /// a proxy for how easily it can be sold.
+    Liquidity(&'a [Sale]),
+    /// Scores an item with a user provided function, which can be used to combine
+    /// other strategies.
+    Custom(fn(&ItemDetails) -> f64),
+}
+
+/// An item and the score it was given by a [`RankingStrategy`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RankedItem<'a> {
+    /// The item that was ranked.
+    pub item: &'a ItemDetails,
+    /// The score given to the item.
+    pub score: f64,
+}
+
+/// A lookup table of [`ItemDetails`] keyed by item id.
+///
+/// This is usually built once from [`Client::all_item_details`] and passed to
+/// anything that needs to resolve item ids, like [`crate::matcher::score_trade`].
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::ItemDetailsTable;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+///
+/// if let Some(item) = table.get(1365767) {
+///     println!("{} is worth {}", item.item_name, item.value_or_rap());
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemDetailsTable {
+    items: HashMap<u64, ItemDetails>,
+}
+
+/// A change in an item's details between two tables, as returned by [`diff`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct ItemChange {
+    /// The details of the item in the old table, or `None` if the item is new.
+    pub previous: Option<ItemDetails>,
+    /// The details of the item in the new table.
+    pub current: ItemDetails,
+}
+
+/// An overview of a set of items, as returned by [`summary`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketSummary {
+    /// The amount of items.
+    pub item_count: usize,
+    /// The amount of valued items.
+    pub valued_count: usize,
+    /// The amount of projected items.
+    pub projected_count: usize,
+    /// The amount of hyped items.
+    pub hyped_count: usize,
+    /// The amount of rare items.
+    pub rare_count: usize,
+    /// The amount of items with each demand. Demands no item has are not included.
+    pub demand_counts: BTreeMap<Demand, usize>,
+    /// The amount of items with each trend. Trends no item has are not included.
+    pub trend_counts: BTreeMap<Trend, usize>,
+    /// The sum of every item's [`ItemDetails::value_or_rap`].
+    pub total_value: u64,
+    /// The sum of every item's rap.
+    pub total_rap: u64,
+}
+
+/// The details of every item at a point in time.
+///
+/// Snapshots are usually taken by calling [`Client::all_item_details_snapshot`]
+/// on an interval, and are compared by tools like
+/// [`TrendAnalyzer`](trend_analyzer::TrendAnalyzer).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// When the snapshot was taken and the schema version it was parsed with.
+    #[serde(default)]
+    pub metadata: ResponseMetadata,
+    /// The details of every item in the snapshot.
+    pub items: ItemDetailsTable,
+}
+
 /// Used for holding the raw json response from <https://www.rolimons.com/itemapi/itemdetails>.
 #[derive(Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct AllItemDetailsResponse {
     success: bool,
     item_count: u64,
     items: HashMap<String, Vec<Code>>,
 }
 
+impl Demand {
+    /// A multiplier used to weigh an item's value by its demand.
+    ///
+    /// `Normal` and `Unassigned` demand are neutral (1.0), with each step up or down
+    /// changing the multiplier by 0.2.
+    pub fn multiplier(&self) -> f64 {
+        match self {
+            Self::Unassigned => 1.0,
+            Self::Terrible => 0.6,
+            Self::Low => 0.8,
+            Self::Normal => 1.0,
+            Self::High => 1.2,
+            Self::Amazing => 1.4,
+        }
+    }
+}
+
 impl ItemDetails {
+    /// Returns the value of the item if it is valued, otherwise returns its rap.
+    ///
+    /// This is the same number Rolimons uses when adding up the worth of an inventory.
+    pub fn value_or_rap(&self) -> u64 {
+        if self.valued {
+            self.value
+        } else {
+            self.rap
+        }
+    }
+
     fn from_raw(item_id: u64, codes: Vec<Code>) -> Result<Self, RoliError> {
         let item_name = codes[0].to_string();
 
@@ -94,11 +238,11 @@ impl ItemDetails {
 
         // For these lines below, we return a ItemsError::MalformedResponse if we cannot parse
         // the value to an i64.
-        let rap = codes[2].to_i64()? as u64;
+        let rap = codes[2].to_u64_lenient()?;
 
         let valued = codes[3].to_i64()? != -1;
 
-        let value = codes[4].to_i64()? as u64;
+        let value = codes[4].to_u64_lenient()?;
 
         let demand = match codes[5].to_i64()? {
             -1 => Demand::Unassigned,
@@ -107,7 +251,7 @@ impl ItemDetails {
             2 => Demand::Normal,
             3 => Demand::High,
             4 => Demand::Amazing,
-            _ => return Err(RoliError::MalformedResponse),
+            _ => crate::unknown_code(Demand::Unassigned)?,
         };
 
         let trend = match codes[6].to_i64()? {
@@ -117,26 +261,14 @@ impl ItemDetails {
             2 => Trend::Stable,
             3 => Trend::Raising,
             4 => Trend::Fluctuating,
-            _ => return Err(RoliError::MalformedResponse),
+            _ => crate::unknown_code(Trend::Unassigned)?,
         };
 
-        let projected = match codes[7].to_i64()? {
-            1 => true,
-            -1 => false,
-            _ => return Err(RoliError::MalformedResponse),
-        };
+        let projected = codes[7].to_bool()?;
 
-        let hyped = match codes[8].to_i64()? {
-            1 => true,
-            -1 => false,
-            _ => return Err(RoliError::MalformedResponse),
-        };
+        let hyped = codes[8].to_bool()?;
 
-        let rare = match codes[9].to_i64()? {
-            1 => true,
-            -1 => false,
-            _ => return Err(RoliError::MalformedResponse),
-        };
+        let rare = codes[9].to_bool()?;
 
         Ok(ItemDetails {
             item_id,
@@ -154,6 +286,416 @@ impl ItemDetails {
     }
 }
 
+impl RankingStrategy<'_> {
+    fn scorer(&self) -> Box<dyn Fn(&ItemDetails) -> f64 + '_> {
+        match self {
+            Self::ValueDensity => Box::new(|item| {
+                if !item.valued || item.rap == 0 {
+                    return 0.0;
+                }
+
+                item.value as f64 / item.rap as f64
+            }),
+            Self::DemandAdjustedValue => {
+                Box::new(|item| item.value_or_rap() as f64 * item.demand.multiplier())
+            }
+            Self::Liquidity(sales) => {
+                let mut sale_counts = HashMap::new();
+
+                for sale in sales.iter() {
+                    *sale_counts.entry(sale.item_id).or_insert(0_u64) += 1;
+                }
+
+                Box::new(move |item| sale_counts.get(&item.item_id).copied().unwrap_or(0) as f64)
+            }
+            Self::Custom(f) => Box::new(f),
+        }
+    }
+}
+
+/// Ranks items using a [`RankingStrategy`], returning them sorted from highest to lowest score.
+///
+/// Items with equal scores are ordered by item id.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::{self, RankingStrategy};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let all_item_details = client.all_item_details().await?;
+/// let sales = client.recent_sales().await?;
+///
+/// let most_liquid = items::rank_by(&all_item_details, RankingStrategy::Liquidity(&sales));
+///
+/// for ranked in most_liquid.iter().take(10) {
+///     println!("{}: {}", ranked.item.item_name, ranked.score);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn rank_by<'a>(items: &'a [ItemDetails], strategy: RankingStrategy) -> Vec<RankedItem<'a>> {
+    let scorer = strategy.scorer();
+
+    let mut ranked = items
+        .iter()
+        .map(|item| RankedItem {
+            item,
+            score: scorer(item),
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.item.item_id.cmp(&b.item.item_id))
+    });
+
+    ranked
+}
+
+/// Returns when each item's value was last seen changing across `snapshots`, as a
+/// map of item ids to the `fetched_at` of the first snapshot with the current value.
+///
+/// Rolimons does not expose this through its api, so it can only be as precise as
+/// the interval the snapshots were taken on. Items whose value never changed in
+/// `snapshots` are not included, as their last update happened before the first one.
+/// Snapshots do not need to be in order.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let mut snapshots = Vec::new();
+///
+/// for _ in 0..10 {
+///     snapshots.push(client.all_item_details_snapshot().await?);
+///     tokio::time::sleep(Duration::from_secs(600)).await;
+/// }
+///
+/// for (item_id, updated_at) in items::value_updated_at(&snapshots) {
+///     println!("{} was revalued at {}", item_id, updated_at);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn value_updated_at(snapshots: &[Snapshot]) -> HashMap<u64, u64> {
+    let mut snapshots = snapshots.iter().collect::<Vec<_>>();
+    snapshots.sort_by_key(|snapshot| snapshot.metadata.fetched_at);
+
+    let mut latest_values = HashMap::<u64, u64>::new();
+    let mut updated_at = HashMap::new();
+
+    for snapshot in snapshots {
+        for item in snapshot.items.iter() {
+            let value = if item.valued { item.value } else { 0 };
+
+            if let Some(previous) = latest_values.insert(item.item_id, value) {
+                if previous != value {
+                    updated_at.insert(item.item_id, snapshot.metadata.fetched_at);
+                }
+            }
+        }
+    }
+
+    updated_at
+}
+
+/// Returns the items in the newest of `snapshots` that are not in any snapshot taken
+/// before `timestamp`, sorted by item id. This answers questions like "which limiteds
+/// started being tracked this week".
+///
+/// Rolimons does not expose when it started tracking an item, so this can only be as
+/// precise as the interval the snapshots were taken on. Returns nothing if no snapshot
+/// was taken before `timestamp`, as every item would otherwise look new. Snapshots do
+/// not need to be in order.
+///
+/// To answer the same question from snapshots kept in SQLite, use
+/// `SqliteStore::items_added_since` from the `sqlite` feature.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::{self, Snapshot};
+///
+/// # fn load_snapshots() -> Vec<Snapshot> { Vec::new() }
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// let snapshots = load_snapshots();
+/// let week_ago = 1700000000 - 7 * 24 * 60 * 60;
+///
+/// for item in items::added_since(&snapshots, week_ago) {
+///     println!("Newly tracked: {}", item.item_name);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn added_since(snapshots: &[Snapshot], timestamp: u64) -> Vec<ItemDetails> {
+    let (before, after): (Vec<_>, Vec<_>) = snapshots
+        .iter()
+        .partition(|snapshot| snapshot.metadata.fetched_at < timestamp);
+
+    let Some(newest) = after
+        .into_iter()
+        .max_by_key(|snapshot| snapshot.metadata.fetched_at)
+    else {
+        return Vec::new();
+    };
+
+    if before.is_empty() {
+        return Vec::new();
+    }
+
+    let mut added = newest
+        .items
+        .iter()
+        .filter(|item| {
+            !before
+                .iter()
+                .any(|snapshot| snapshot.items.contains(item.item_id))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    added.sort_by_key(|item| item.item_id);
+    added
+}
+
+/// Counts `items` by demand, trend, and flags, and totals their value and rap.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::{self, Demand};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let summary = items::summary(&client.all_item_details().await?);
+///
+/// println!(
+///     "{} items worth {} in total, {} with amazing demand",
+///     summary.item_count,
+///     summary.total_value,
+///     summary.demand_counts.get(&Demand::Amazing).unwrap_or(&0)
+/// );
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn summary(items: &[ItemDetails]) -> MarketSummary {
+    let mut summary = MarketSummary {
+        item_count: items.len(),
+        ..Default::default()
+    };
+
+    for item in items {
+        summary.valued_count += item.valued as usize;
+        summary.projected_count += item.projected as usize;
+        summary.hyped_count += item.hyped as usize;
+        summary.rare_count += item.rare as usize;
+
+        *summary.demand_counts.entry(item.demand).or_default() += 1;
+        *summary.trend_counts.entry(item.trend).or_default() += 1;
+
+        summary.total_value += item.value_or_rap();
+        summary.total_rap += item.rap;
+    }
+
+    summary
+}
+
+impl ItemChange {
+    /// Returns the id of the item that changed.
+    pub fn item_id(&self) -> u64 {
+        self.current.item_id
+    }
+}
+
+/// Compares two tables (such as the items of two [`Snapshot`]s) and returns every
+/// item whose details changed, or that is new in `new`, sorted by item id.
+///
+/// Items that are in `old` but missing from `new` are ignored.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let old = client.all_item_details_snapshot().await?;
+/// // ...some time later...
+/// let new = client.all_item_details_snapshot().await?;
+///
+/// for change in items::diff(&old.items, &new.items) {
+///     if change.previous.is_none() {
+///         println!("New item: {}", change.current.item_name);
+///     }
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn diff(old: &ItemDetailsTable, new: &ItemDetailsTable) -> Vec<ItemChange> {
+    let mut changes = new
+        .iter()
+        .filter_map(|current| {
+            let previous = old.get(current.item_id);
+
+            if previous == Some(current) {
+                return None;
+            }
+
+            Some(ItemChange {
+                previous: previous.cloned(),
+                current: current.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    changes.sort_by_key(|change| change.item_id());
+    changes
+}
+
+impl ItemDetailsTable {
+    /// Creates a table from a list of items. Later duplicates of an item id replace earlier ones.
+    pub fn new(items: Vec<ItemDetails>) -> Self {
+        items.into_iter().collect()
+    }
+
+    /// Returns the details of an item, if it is in the table.
+    pub fn get(&self, item_id: u64) -> Option<&ItemDetails> {
+        self.items.get(&item_id)
+    }
+
+    /// Returns whether an item is in the table.
+    pub fn contains(&self, item_id: u64) -> bool {
+        self.items.contains_key(&item_id)
+    }
+
+    /// Inserts an item, returning the details it replaced, if any.
+    pub fn insert(&mut self, item: ItemDetails) -> Option<ItemDetails> {
+        self.items.insert(item.item_id, item)
+    }
+
+    /// Returns the amount of items in the table.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns whether the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns an iterator over the items in the table, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &ItemDetails> {
+        self.items.values()
+    }
+}
+
+/// A source of item details that enrichment helpers (such as [`handler::run`](crate::handler::run),
+/// [`DealEvaluator::rank`](crate::deals::DealEvaluator::rank), and
+/// [`PlayerProfile::inventory_value`](crate::players::PlayerProfile::inventory_value))
+/// look items up in.
+///
+/// This is implemented by [`ItemDetailsTable`], [`Snapshot`], and `ItemCache` (which
+/// looks items up in its current snapshot without refreshing it), and can be
+/// implemented for a user's own database. Items the provider does not know about are
+/// skipped by the helpers rather than failing the whole batch.
+///
+/// # Example
+/// ```
+/// use roli::items::{ItemDetails, ItemInfoProvider};
+/// use std::borrow::Cow;
+///
+/// struct Database;
+///
+/// impl ItemInfoProvider for Database {
+///     fn item_details(&self, item_id: u64) -> Option<Cow<'_, ItemDetails>> {
+///         // Look the item up in the database here.
+///         Some(Cow::Owned(ItemDetails {
+///             item_id,
+///             rap: 1000,
+///             ..Default::default()
+///         }))
+///     }
+/// }
+///
+/// let report = roli::matcher::score_trade(&[1], &[2], &Database);
+/// assert_eq!(report.offer.value, 1000);
+/// ```
+pub trait ItemInfoProvider {
+    /// Returns the details of an item, or `None` if the provider does not know it.
+    fn item_details(&self, item_id: u64) -> Option<Cow<'_, ItemDetails>>;
+}
+
+impl ItemInfoProvider for ItemDetailsTable {
+    fn item_details(&self, item_id: u64) -> Option<Cow<'_, ItemDetails>> {
+        self.get(item_id).map(Cow::Borrowed)
+    }
+}
+
+impl ItemInfoProvider for Snapshot {
+    fn item_details(&self, item_id: u64) -> Option<Cow<'_, ItemDetails>> {
+        self.items.item_details(item_id)
+    }
+}
+
+impl ItemInfoProvider for HashMap<u64, ItemDetails> {
+    fn item_details(&self, item_id: u64) -> Option<Cow<'_, ItemDetails>> {
+        self.get(&item_id).map(Cow::Borrowed)
+    }
+}
+
+impl<T: ItemInfoProvider + ?Sized> ItemInfoProvider for &T {
+    fn item_details(&self, item_id: u64) -> Option<Cow<'_, ItemDetails>> {
+        (**self).item_details(item_id)
+    }
+}
+
+impl<T: ItemInfoProvider + ?Sized> ItemInfoProvider for Arc<T> {
+    fn item_details(&self, item_id: u64) -> Option<Cow<'_, ItemDetails>> {
+        (**self).item_details(item_id)
+    }
+}
+
+impl FromIterator<ItemDetails> for ItemDetailsTable {
+    fn from_iter<T: IntoIterator<Item = ItemDetails>>(iter: T) -> Self {
+        Self {
+            items: iter.into_iter().map(|item| (item.item_id, item)).collect(),
+        }
+    }
+}
+
+impl From<Vec<ItemDetails>> for ItemDetailsTable {
+    fn from(items: Vec<ItemDetails>) -> Self {
+        Self::new(items)
+    }
+}
+
+impl Snapshot {
+    /// Creates a snapshot from the items fetched at `fetched_at`, tagged with the
+    /// current [`SCHEMA_VERSION`].
+    pub fn new(fetched_at: u64, items: Vec<ItemDetails>) -> Self {
+        Self {
+            metadata: ResponseMetadata::new(SCHEMA_VERSION, fetched_at),
+            items: ItemDetailsTable::new(items),
+        }
+    }
+}
+
 impl AllItemDetailsResponse {
     fn into_vec(self) -> Result<Vec<ItemDetails>, RoliError> {
         let mut item_details_vec = Vec::new();
@@ -195,39 +737,44 @@ impl Client {
     /// # }
     /// ```
     pub async fn all_item_details(&self) -> Result<Vec<ItemDetails>, RoliError> {
-        let request_result = self
+        let request = self
             .reqwest_client
             .get(ITEM_DETAILS_API)
-            .header(header::USER_AGENT, crate::USER_AGENT)
-            .send()
-            .await;
-
-        match request_result {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                match status_code {
-                    200 => {
-                        let raw = match response.json::<AllItemDetailsResponse>().await {
-                            Ok(x) => x,
-                            Err(_) => return Err(RoliError::MalformedResponse),
-                        };
-
-                        if !raw.success {
-                            return Err(RoliError::RequestReturnedUnsuccessful);
-                        }
-
-                        let item_details = raw.into_vec()?;
-
-                        Ok(item_details)
-                    }
-                    429 => Err(RoliError::TooManyRequests),
-                    500 => Err(RoliError::InternalServerError),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
-            Err(e) => Err(RoliError::ReqwestError(e)),
+            .header(header::USER_AGENT, self.user_agent());
+
+        let raw = self
+            .request_json::<AllItemDetailsResponse>(Endpoint::AllItemDetails, request)
+            .await?;
+
+        if !raw.success {
+            return Err(RoliError::RequestReturnedUnsuccessful);
         }
+
+        let item_details = raw.into_vec()?;
+
+        Ok(item_details)
+    }
+
+    /// The same as [`Client::all_item_details`], but returns a [`Snapshot`] tagged
+    /// with the time it was fetched and the schema version it was parsed with.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let snapshot = client.all_item_details_snapshot().await?;
+    /// println!("Fetched at {}", snapshot.metadata.fetched_at);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn all_item_details_snapshot(&self) -> Result<Snapshot, RoliError> {
+        let items = self.all_item_details().await?;
+
+        Ok(Snapshot::new(crate::unix_timestamp(), items))
     }
 }
 
@@ -290,4 +837,231 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_snapshot_metadata() {
+        let snapshot = Snapshot::new(1_700_000_000, vec![ItemDetails::default()]);
+        assert_eq!(snapshot.metadata.schema_version, SCHEMA_VERSION);
+        assert_eq!(snapshot.metadata.fetched_at, 1_700_000_000);
+
+        // Snapshots saved before metadata was recorded read as version 0.
+        let json = r#"{"items":{"items":{}}}"#;
+        let archived = serde_json::from_str::<Snapshot>(json).unwrap();
+        assert_eq!(archived.metadata.schema_version, 0);
+    }
+
+    #[test]
+    fn test_diff() {
+        let item = |item_id, rap| ItemDetails {
+            item_id,
+            rap,
+            ..Default::default()
+        };
+
+        let old = ItemDetailsTable::new(vec![item(1, 100), item(2, 200), item(3, 300)]);
+        let new = ItemDetailsTable::new(vec![item(4, 400), item(2, 250), item(1, 100)]);
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                ItemChange {
+                    previous: Some(item(2, 200)),
+                    current: item(2, 250),
+                },
+                ItemChange {
+                    previous: None,
+                    current: item(4, 400),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_value_updated_at() {
+        let item = |item_id, value| ItemDetails {
+            item_id,
+            valued: true,
+            value,
+            ..Default::default()
+        };
+
+        let snapshots = vec![
+            Snapshot::new(300, vec![item(1, 2000), item(2, 500)]),
+            Snapshot::new(100, vec![item(1, 1000), item(2, 500)]),
+            Snapshot::new(200, vec![item(1, 2000), item(2, 500)]),
+        ];
+
+        let updated_at = value_updated_at(&snapshots);
+
+        assert_eq!(updated_at.get(&1), Some(&200));
+        assert_eq!(updated_at.get(&2), None);
+    }
+
+    #[test]
+    fn test_added_since() {
+        let item = |item_id| ItemDetails {
+            item_id,
+            ..Default::default()
+        };
+
+        let snapshots = vec![
+            Snapshot::new(300, vec![item(1), item(2), item(3)]),
+            Snapshot::new(100, vec![item(1)]),
+            Snapshot::new(200, vec![item(1), item(2)]),
+        ];
+
+        let added = added_since(&snapshots, 150);
+        assert_eq!(
+            added.iter().map(|x| x.item_id).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+
+        assert_eq!(added_since(&snapshots, 250).len(), 1);
+        assert!(added_since(&snapshots, 50).is_empty());
+        assert!(added_since(&snapshots, 400).is_empty());
+    }
+
+    #[test]
+    fn test_summary() {
+        let items = vec![
+            ItemDetails {
+                item_id: 1,
+                rap: 100,
+                valued: true,
+                value: 500,
+                demand: Demand::High,
+                trend: Trend::Stable,
+                rare: true,
+                ..Default::default()
+            },
+            ItemDetails {
+                item_id: 2,
+                rap: 300,
+                demand: Demand::High,
+                projected: true,
+                ..Default::default()
+            },
+        ];
+
+        let summary = summary(&items);
+        assert_eq!(summary.item_count, 2);
+        assert_eq!(summary.valued_count, 1);
+        assert_eq!((summary.projected_count, summary.rare_count), (1, 1));
+        assert_eq!(summary.hyped_count, 0);
+        assert_eq!(summary.demand_counts, BTreeMap::from([(Demand::High, 2)]));
+        assert_eq!(
+            summary.trend_counts,
+            BTreeMap::from([(Trend::Unassigned, 1), (Trend::Stable, 1)])
+        );
+        assert_eq!(summary.total_value, 800);
+        assert_eq!(summary.total_rap, 400);
+    }
+
+    #[test]
+    fn test_from_raw_unknown_codes() {
+        let codes = vec![
+            Code::String("Test item name".to_string()),
+            Code::String("".to_string()),
+            Code::Integer(100),
+            Code::Integer(-1),
+            Code::Integer(-1),
+            Code::Integer(9),
+            Code::Integer(9),
+            Code::Integer(0),
+            Code::Integer(-1),
+            Code::Integer(-1),
+        ];
+
+        let result = ItemDetails::from_raw(123, codes);
+
+        if cfg!(feature = "strict-parsing") {
+            assert!(result.is_err());
+        } else {
+            let item_details = result.unwrap();
+            assert_eq!(item_details.demand, Demand::Unassigned);
+            assert_eq!(item_details.trend, Trend::Unassigned);
+            assert!(!item_details.projected);
+        }
+    }
+
+    fn item(item_id: u64, rap: u64, value: Option<u64>, demand: Demand) -> ItemDetails {
+        ItemDetails {
+            item_id,
+            rap,
+            valued: value.is_some(),
+            value: value.unwrap_or_default(),
+            demand,
+            ..Default::default()
+        }
+    }
+
+    fn ranked_ids(ranked: Vec<RankedItem>) -> Vec<u64> {
+        ranked.iter().map(|x| x.item.item_id).collect()
+    }
+
+    #[test]
+    fn test_rank_by_value_density() {
+        let items = vec![
+            item(1, 100, Some(150), Demand::Normal),
+            item(2, 100, Some(300), Demand::Normal),
+            item(3, 100, None, Demand::Normal),
+        ];
+
+        let ranked = rank_by(&items, RankingStrategy::ValueDensity);
+
+        assert_eq!(ranked_ids(ranked), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_rank_by_demand_adjusted_value() {
+        let items = vec![
+            item(1, 0, Some(1000), Demand::Terrible),
+            item(2, 0, Some(900), Demand::Amazing),
+            item(3, 950, None, Demand::Unassigned),
+        ];
+
+        let ranked = rank_by(&items, RankingStrategy::DemandAdjustedValue);
+
+        assert_eq!(ranked_ids(ranked), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_rank_by_liquidity() {
+        let items = vec![
+            item(1, 0, None, Demand::Normal),
+            item(2, 0, None, Demand::Normal),
+        ];
+
+        let sales = vec![
+            Sale {
+                item_id: 2,
+                ..Default::default()
+            },
+            Sale {
+                item_id: 2,
+                ..Default::default()
+            },
+            Sale {
+                item_id: 1,
+                ..Default::default()
+            },
+        ];
+
+        let ranked = rank_by(&items, RankingStrategy::Liquidity(&sales));
+
+        assert_eq!(ranked[0].score, 2.0);
+        assert_eq!(ranked_ids(ranked), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_rank_by_custom() {
+        let items = vec![
+            item(1, 500, None, Demand::Normal),
+            item(2, 100, None, Demand::Normal),
+        ];
+
+        let ranked = rank_by(&items, RankingStrategy::Custom(|item| -(item.rap as f64)));
+
+        assert_eq!(ranked_ids(ranked), vec![2, 1]);
+    }
 }
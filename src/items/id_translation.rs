@@ -0,0 +1,151 @@
+use crate::items::{ItemDetails, ItemDetailsTable};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Translates between the asset ids used by Roblox and the item ids used by Rolimons.
+///
+/// For almost every item the two ids are the same, but some items (such as bundles
+/// and special serials) are listed on Rolimons under a different id than the one
+/// Roblox's inventory APIs return. Ids without a translation are assumed to be the
+/// same on both sites.
+///
+/// This crate does not ship any translations, as Rolimons does not publish them.
+/// They are added with [`IdTranslator::insert`], or deserialized from a file as a
+/// map of Roblox ids to Rolimons ids.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::id_translation::IdTranslator;
+/// use roli::items::ItemDetailsTable;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+///
+/// let mut translator = IdTranslator::new();
+/// translator.insert(1000, 2000);
+///
+/// // Asset ids returned by a Roblox inventory api.
+/// for roblox_id in [1000, 1365767] {
+///     if let Some(item) = translator.lookup(roblox_id, &table) {
+///         println!("{} is worth {}", item.item_name, item.value_or_rap());
+///     }
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "HashMap<u64, u64>", into = "HashMap<u64, u64>")]
+pub struct IdTranslator {
+    to_rolimons: HashMap<u64, u64>,
+    to_roblox: HashMap<u64, u64>,
+}
+
+impl IdTranslator {
+    /// Creates a translator without any translations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a translation, replacing any existing translation for either id.
+    pub fn insert(&mut self, roblox_id: u64, rolimons_id: u64) {
+        if let Some(old_rolimons_id) = self.to_rolimons.insert(roblox_id, rolimons_id) {
+            self.to_roblox.remove(&old_rolimons_id);
+        }
+
+        if let Some(old_roblox_id) = self.to_roblox.insert(rolimons_id, roblox_id) {
+            if old_roblox_id != roblox_id {
+                self.to_rolimons.remove(&old_roblox_id);
+            }
+        }
+    }
+
+    /// Returns the Rolimons item id for a Roblox asset id.
+    pub fn to_rolimons(&self, roblox_id: u64) -> u64 {
+        self.to_rolimons
+            .get(&roblox_id)
+            .copied()
+            .unwrap_or(roblox_id)
+    }
+
+    /// Returns the Roblox asset id for a Rolimons item id.
+    pub fn to_roblox(&self, rolimons_id: u64) -> u64 {
+        self.to_roblox
+            .get(&rolimons_id)
+            .copied()
+            .unwrap_or(rolimons_id)
+    }
+
+    /// Returns the details of the item with the Roblox asset id `roblox_id`, if it is
+    /// in `table`.
+    pub fn lookup<'a>(
+        &self,
+        roblox_id: u64,
+        table: &'a ItemDetailsTable,
+    ) -> Option<&'a ItemDetails> {
+        table.get(self.to_rolimons(roblox_id))
+    }
+
+    /// Returns the amount of translations.
+    pub fn len(&self) -> usize {
+        self.to_rolimons.len()
+    }
+
+    /// Returns whether there are no translations.
+    pub fn is_empty(&self) -> bool {
+        self.to_rolimons.is_empty()
+    }
+}
+
+impl From<HashMap<u64, u64>> for IdTranslator {
+    fn from(to_rolimons: HashMap<u64, u64>) -> Self {
+        let mut translator = Self::new();
+
+        for (roblox_id, rolimons_id) in to_rolimons {
+            translator.insert(roblox_id, rolimons_id);
+        }
+
+        translator
+    }
+}
+
+impl From<IdTranslator> for HashMap<u64, u64> {
+    fn from(translator: IdTranslator) -> Self {
+        translator.to_rolimons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translation() {
+        let mut translator = IdTranslator::new();
+        translator.insert(1000, 2000);
+
+        assert_eq!(translator.to_rolimons(1000), 2000);
+        assert_eq!(translator.to_roblox(2000), 1000);
+        assert_eq!(translator.to_rolimons(5), 5);
+
+        // Replacing a translation removes the old reverse mapping.
+        translator.insert(1000, 3000);
+        assert_eq!(translator.to_roblox(2000), 2000);
+        assert_eq!(translator.len(), 1);
+
+        let table = ItemDetailsTable::new(vec![ItemDetails {
+            item_id: 3000,
+            ..Default::default()
+        }]);
+        assert_eq!(translator.lookup(1000, &table).unwrap().item_id, 3000);
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let translator: IdTranslator = serde_json::from_str(r#"{"1000": 2000}"#).unwrap();
+        assert_eq!(translator.to_roblox(2000), 1000);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::items::ItemDetails;
+use crate::market_activity::Sale;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The thresholds used by [`ProjectedDetector`].
+///
+/// The defaults are meant to be a reasonable starting point for deal bots, and
+/// should be tuned against the items you actually trade.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Thresholds {
+    /// The ratio of the median recent sale price to the item's baseline (its value,
+    /// or its rap before the recent sales if unvalued) at which the price spike
+    /// signal is at full strength.
+    pub price_spike_ratio: f64,
+    /// The ratio of the item's current rap to its rap before the recent sales at
+    /// which the rap increase signal is at full strength.
+    pub rap_increase_ratio: f64,
+    /// The minimum amount of recent sales needed before an item is scored at all.
+    pub min_sales: usize,
+    /// The minimum confidence an item needs to be returned from [`ProjectedDetector::scan`].
+    pub min_confidence: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            price_spike_ratio: 1.5,
+            rap_increase_ratio: 1.2,
+            min_sales: 2,
+            min_confidence: 0.5,
+        }
+    }
+}
+
+/// The result of scoring an item with [`ProjectedDetector`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProjectedScore {
+    /// The id of the item.
+    pub item_id: u64,
+    /// How likely the item is to be projected, from 0.0 to 1.0.
+    pub confidence: f64,
+    /// The amount of recent sales the score is based on.
+    pub sales: usize,
+    /// The median price of the recent sales.
+    pub median_price: u64,
+    /// The price the sales were compared against. This is the item's value if it
+    /// is valued, or its rap before the first recent sale otherwise.
+    pub baseline: u64,
+    /// `median_price / baseline`.
+    pub price_spike_ratio: f64,
+    /// The rap after the latest recent sale divided by the rap before the first one.
+    pub rap_increase_ratio: f64,
+}
+
+/// A heuristic detector that flags items that are likely projected before
+/// Rolimons marks them as such.
+///
+/// An item is considered likely projected when its recent sales are well above
+/// its baseline price and have pushed its rap up with them. This is only a
+/// heuristic, and confidence scores should be used to filter deals rather than
+/// taken as fact.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::projected_detector::ProjectedDetector;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let all_item_details = client.all_item_details().await?;
+/// let sales = client.recent_sales().await?;
+///
+/// for score in ProjectedDetector::default().scan(&all_item_details, &sales) {
+///     println!("{} is likely projected ({:.2})", score.item_id, score.confidence);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectedDetector {
+    /// The thresholds used when scoring items.
+    pub thresholds: Thresholds,
+}
+
+impl ProjectedDetector {
+    /// Creates a detector with the given thresholds.
+    pub fn new(thresholds: Thresholds) -> Self {
+        Self { thresholds }
+    }
+
+    /// Scores a single item against its recent sales. Sales of other items are ignored.
+    ///
+    /// Returns `None` if the item is already marked as projected by Rolimons, has
+    /// fewer than [`Thresholds::min_sales`] recent sales, or has no baseline price.
+    pub fn score(&self, item: &ItemDetails, sales: &[Sale]) -> Option<ProjectedScore> {
+        let sales = sales
+            .iter()
+            .filter(|sale| sale.item_id == item.item_id)
+            .collect::<Vec<_>>();
+
+        self.score_sales(item, sales)
+    }
+
+    /// Scores every item that has recent sales, returning the ones with a confidence
+    /// of at least [`Thresholds::min_confidence`], sorted from most to least confident.
+    pub fn scan(&self, items: &[ItemDetails], sales: &[Sale]) -> Vec<ProjectedScore> {
+        let mut sales_by_item = HashMap::<u64, Vec<&Sale>>::new();
+
+        for sale in sales {
+            sales_by_item.entry(sale.item_id).or_default().push(sale);
+        }
+
+        let mut scores = items
+            .iter()
+            .filter_map(|item| {
+                let sales = sales_by_item.remove(&item.item_id)?;
+                self.score_sales(item, sales)
+            })
+            .filter(|score| score.confidence >= self.thresholds.min_confidence)
+            .collect::<Vec<_>>();
+
+        scores.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then(a.item_id.cmp(&b.item_id))
+        });
+
+        scores
+    }
+
+    fn score_sales(&self, item: &ItemDetails, mut sales: Vec<&Sale>) -> Option<ProjectedScore> {
+        if item.projected || sales.is_empty() || sales.len() < self.thresholds.min_sales {
+            return None;
+        }
+
+        sales.sort_by_key(|sale| sale.timestamp);
+
+        let starting_rap = sales[0].old_rap;
+        let latest_rap = sales[sales.len() - 1].new_rap;
+
+        let baseline = if item.valued {
+            item.value
+        } else {
+            starting_rap
+        };
+
+        if baseline == 0 || starting_rap == 0 {
+            return None;
+        }
+
+        let mut prices = sales.iter().map(|sale| sale.sale_price).collect::<Vec<_>>();
+        prices.sort_unstable();
+        let median_price = prices[prices.len() / 2];
+
+        let price_spike_ratio = median_price as f64 / baseline as f64;
+        let rap_increase_ratio = latest_rap as f64 / starting_rap as f64;
+
+        // A sale spike is the main signal, so it is weighted twice as much as the rap
+        // moving with it.
+        let spike_signal = signal(price_spike_ratio, self.thresholds.price_spike_ratio);
+        let rap_signal = signal(rap_increase_ratio, self.thresholds.rap_increase_ratio);
+        let confidence = (spike_signal * 2.0 + rap_signal) / 3.0;
+
+        Some(ProjectedScore {
+            item_id: item.item_id,
+            confidence,
+            sales: sales.len(),
+            median_price,
+            baseline,
+            price_spike_ratio,
+            rap_increase_ratio,
+        })
+    }
+}
+
+/// Maps a ratio to 0.0 at 1.0 (no change) and 1.0 at `full_ratio`, clamping outside of that.
+fn signal(ratio: f64, full_ratio: f64) -> f64 {
+    if full_ratio <= 1.0 {
+        return if ratio > 1.0 { 1.0 } else { 0.0 };
+    }
+
+    ((ratio - 1.0) / (full_ratio - 1.0)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sale(item_id: u64, old_rap: u64, price: u64, timestamp: u64) -> Sale {
+        Sale {
+            item_id,
+            old_rap,
+            new_rap: old_rap + price.saturating_sub(old_rap) / 10,
+            sale_price: price,
+            sale_id: timestamp,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_score_flags_price_spike() {
+        let item = ItemDetails {
+            item_id: 1,
+            rap: 1000,
+            ..Default::default()
+        };
+
+        let sales = vec![
+            sale(1, 1000, 2000, 1),
+            sale(1, 1100, 2100, 2),
+            sale(1, 1200, 2200, 3),
+            sale(2, 10, 10_000, 4),
+        ];
+
+        let score = ProjectedDetector::default().score(&item, &sales).unwrap();
+
+        assert_eq!(score.sales, 3);
+        assert_eq!(score.baseline, 1000);
+        assert_eq!(score.median_price, 2100);
+        assert_eq!(score.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_score_ignores_stable_and_projected_items() {
+        let item = ItemDetails {
+            item_id: 1,
+            rap: 1000,
+            valued: true,
+            value: 1000,
+            ..Default::default()
+        };
+
+        let sales = vec![sale(1, 1000, 1000, 1), sale(1, 1000, 990, 2)];
+        let detector = ProjectedDetector::default();
+
+        assert_eq!(detector.score(&item, &sales).unwrap().confidence, 0.0);
+        assert!(detector
+            .scan(std::slice::from_ref(&item), &sales)
+            .is_empty());
+
+        let projected = ItemDetails {
+            projected: true,
+            ..item
+        };
+
+        assert!(detector.score(&projected, &sales).is_none());
+    }
+
+    #[test]
+    fn test_score_requires_min_sales() {
+        let item = ItemDetails {
+            item_id: 1,
+            rap: 1000,
+            ..Default::default()
+        };
+
+        let sales = vec![sale(1, 1000, 5000, 1)];
+
+        assert!(ProjectedDetector::default().score(&item, &sales).is_none());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::items::{Snapshot, Trend};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The direction of a [`TrendReport::breakout`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Copy)]
+pub enum Breakout {
+    /// The latest price broke out above the moving average.
+    Up,
+    /// The latest price broke out below the moving average.
+    Down,
+}
+
+/// The analysis of a single item across a series of snapshots.
+///
+/// Prices are the item's [`value_or_rap`](crate::items::ItemDetails::value_or_rap)
+/// in each snapshot.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrendReport {
+    /// The id of the item.
+    pub item_id: u64,
+    /// The amount of snapshots the item appeared in.
+    pub samples: usize,
+    /// The latest price of the item.
+    pub latest_price: u64,
+    /// The average price over the last [`TrendAnalyzer::window`] snapshots.
+    pub moving_average: f64,
+    /// The standard deviation of the price over the last [`TrendAnalyzer::window`]
+    /// snapshots, divided by [`TrendReport::moving_average`].
+    pub volatility: f64,
+    /// The percent change in price from the first snapshot to the latest one.
+    pub change_percent: f64,
+    /// Set if the latest price moved further from the average of the snapshots
+    /// before it than [`TrendAnalyzer::breakout_percent`].
+    pub breakout: Option<Breakout>,
+    /// The trend computed from the snapshots.
+    pub trend: Trend,
+    /// The trend Rolimons assigned the item in the latest snapshot.
+    pub rolimons_trend: Trend,
+}
+
+impl TrendReport {
+    /// Returns whether the computed trend agrees with Rolimons' label.
+    pub fn matches_rolimons(&self) -> bool {
+        self.trend == self.rolimons_trend
+    }
+}
+
+/// Computes moving averages, volatility, and breakouts for items across a series
+/// of [`Snapshot`]s, and labels each item with a [`Trend`] so it can be compared
+/// against Rolimons' own labels.
+///
+/// The thresholds are public so they can be tuned, and [`TrendAnalyzer::default`]
+/// provides a reasonable starting point.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::trend_analyzer::TrendAnalyzer;
+/// use roli::items::Snapshot;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let mut snapshots = Vec::new();
+///
+/// for _ in 0..10 {
+///     snapshots.push(client.all_item_details_snapshot().await?);
+///     tokio::time::sleep(Duration::from_secs(600)).await;
+/// }
+///
+/// for report in TrendAnalyzer::default().analyze(&snapshots) {
+///     if !report.matches_rolimons() {
+///         println!("{}: {:?} vs {:?}", report.item_id, report.trend, report.rolimons_trend);
+///     }
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrendAnalyzer {
+    /// The amount of most recent snapshots used for the moving average and volatility.
+    pub window: usize,
+    /// The percent change at or above which an item is [`Trend::Raising`], and at or
+    /// below the negative of which it is [`Trend::Lowering`].
+    pub change_percent: f64,
+    /// The volatility above which an item is [`Trend::Unstable`].
+    pub unstable_volatility: f64,
+    /// The volatility above which an item is [`Trend::Fluctuating`].
+    pub fluctuating_volatility: f64,
+    /// How far, in percent, the latest price must be from the average of the
+    /// window before it to count as a breakout.
+    pub breakout_percent: f64,
+}
+
+impl Default for TrendAnalyzer {
+    fn default() -> Self {
+        Self {
+            window: 5,
+            change_percent: 5.0,
+            unstable_volatility: 0.05,
+            fluctuating_volatility: 0.15,
+            breakout_percent: 10.0,
+        }
+    }
+}
+
+impl TrendAnalyzer {
+    /// Analyzes every item that appears in `snapshots`, returning reports sorted by item id.
+    ///
+    /// Snapshots do not need to be in order. Items that appear in only one snapshot
+    /// are labeled [`Trend::Unassigned`].
+    pub fn analyze(&self, snapshots: &[Snapshot]) -> Vec<TrendReport> {
+        let mut snapshots = snapshots.iter().collect::<Vec<_>>();
+        snapshots.sort_by_key(|snapshot| snapshot.metadata.fetched_at);
+
+        let mut series = BTreeMap::<u64, (Vec<u64>, Trend)>::new();
+
+        for snapshot in snapshots {
+            for item in snapshot.items.iter() {
+                let (prices, rolimons_trend) = series.entry(item.item_id).or_default();
+                prices.push(item.value_or_rap());
+                *rolimons_trend = item.trend;
+            }
+        }
+
+        series
+            .into_iter()
+            .map(|(item_id, (prices, rolimons_trend))| {
+                self.report(item_id, &prices, rolimons_trend)
+            })
+            .collect()
+    }
+
+    fn report(&self, item_id: u64, prices: &[u64], rolimons_trend: Trend) -> TrendReport {
+        let window = &prices[prices.len().saturating_sub(self.window.max(1))..];
+        let latest_price = prices[prices.len() - 1];
+
+        let moving_average = mean(window);
+        let volatility = if moving_average == 0.0 {
+            0.0
+        } else {
+            standard_deviation(window, moving_average) / moving_average
+        };
+
+        let first_price = prices[0];
+        let change_percent = if first_price == 0 {
+            0.0
+        } else {
+            (latest_price as f64 - first_price as f64) / first_price as f64 * 100.0
+        };
+
+        let breakout = if window.len() < 2 {
+            None
+        } else {
+            let previous_average = mean(&window[..window.len() - 1]);
+
+            if previous_average == 0.0 {
+                None
+            } else {
+                let deviation = (latest_price as f64 - previous_average) / previous_average * 100.0;
+
+                if deviation >= self.breakout_percent {
+                    Some(Breakout::Up)
+                } else if deviation <= -self.breakout_percent {
+                    Some(Breakout::Down)
+                } else {
+                    None
+                }
+            }
+        };
+
+        let trend = if prices.len() < 2 {
+            Trend::Unassigned
+        } else if volatility > self.fluctuating_volatility {
+            Trend::Fluctuating
+        } else if change_percent >= self.change_percent {
+            Trend::Raising
+        } else if change_percent <= -self.change_percent {
+            Trend::Lowering
+        } else if volatility > self.unstable_volatility {
+            Trend::Unstable
+        } else {
+            Trend::Stable
+        };
+
+        TrendReport {
+            item_id,
+            samples: prices.len(),
+            latest_price,
+            moving_average,
+            volatility,
+            change_percent,
+            breakout,
+            trend,
+            rolimons_trend,
+        }
+    }
+}
+
+fn mean(prices: &[u64]) -> f64 {
+    prices.iter().map(|x| *x as f64).sum::<f64>() / prices.len() as f64
+}
+
+fn standard_deviation(prices: &[u64], mean: f64) -> f64 {
+    let variance = prices
+        .iter()
+        .map(|x| (*x as f64 - mean).powi(2))
+        .sum::<f64>()
+        / prices.len() as f64;
+
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ItemDetails;
+
+    fn snapshots(prices: &[u64], rolimons_trend: Trend) -> Vec<Snapshot> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, rap)| {
+                let item = ItemDetails {
+                    item_id: 1,
+                    rap: *rap,
+                    trend: rolimons_trend,
+                    ..Default::default()
+                };
+
+                Snapshot::new(i as u64, vec![item])
+            })
+            .collect()
+    }
+
+    fn analyze(prices: &[u64]) -> TrendReport {
+        TrendAnalyzer::default().analyze(&snapshots(prices, Trend::Stable))[0]
+    }
+
+    #[test]
+    fn test_analyze_trends() {
+        assert_eq!(analyze(&[1000]).trend, Trend::Unassigned);
+        assert_eq!(analyze(&[1000, 1001, 999, 1000]).trend, Trend::Stable);
+        assert_eq!(analyze(&[1000, 1010, 1030, 1060]).trend, Trend::Raising);
+        assert_eq!(analyze(&[1000, 990, 970, 940]).trend, Trend::Lowering);
+        assert_eq!(analyze(&[1000, 1080, 920, 1010]).trend, Trend::Unstable);
+        assert_eq!(analyze(&[1000, 1500, 700, 1000]).trend, Trend::Fluctuating);
+    }
+
+    #[test]
+    fn test_analyze_breakout_and_moving_average() {
+        let report = analyze(&[1000, 1000, 1000, 1000, 1000, 1200]);
+
+        assert_eq!(report.samples, 6);
+        assert_eq!(report.latest_price, 1200);
+        assert!((report.moving_average - 1040.0).abs() < 1e-9);
+        assert_eq!(report.breakout, Some(Breakout::Up));
+        assert_eq!(analyze(&[1000, 1000, 850]).breakout, Some(Breakout::Down));
+    }
+
+    #[test]
+    fn test_matches_rolimons() {
+        let mut unordered = snapshots(&[1000, 1001, 999], Trend::Stable);
+        unordered.reverse();
+
+        let report = TrendAnalyzer::default().analyze(&unordered)[0];
+
+        assert_eq!(report.latest_price, 999);
+        assert!(report.matches_rolimons());
+    }
+}
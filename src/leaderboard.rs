@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A player's row on the Rolimons leaderboard.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    /// The player's position on the leaderboard, starting at 1.
+    pub rank: u64,
+    /// The user id of the player.
+    pub user_id: u64,
+    /// The username of the player.
+    pub username: String,
+    /// The total value of the player's inventory.
+    pub value: u64,
+    /// The total rap of the player's inventory.
+    pub rap: u64,
+}
+
+/// A change in a player's rank between two snapshots of the leaderboard, as
+/// returned by [`diff`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RankChange {
+    /// The player moved up the leaderboard.
+    Climbed {
+        /// The player's entry in the new snapshot.
+        entry: LeaderboardEntry,
+        /// The player's rank in the old snapshot.
+        previous_rank: u64,
+        /// The player's value in the old snapshot.
+        previous_value: u64,
+    },
+    /// The player moved down the leaderboard.
+    Fell {
+        /// The player's entry in the new snapshot.
+        entry: LeaderboardEntry,
+        /// The player's rank in the old snapshot.
+        previous_rank: u64,
+        /// The player's value in the old snapshot.
+        previous_value: u64,
+    },
+    /// The player was not in the old snapshot.
+    Entered(LeaderboardEntry),
+}
+
+impl RankChange {
+    /// Returns the player's entry in the new snapshot.
+    pub fn entry(&self) -> &LeaderboardEntry {
+        match self {
+            Self::Climbed { entry, .. } => entry,
+            Self::Fell { entry, .. } => entry,
+            Self::Entered(entry) => entry,
+        }
+    }
+
+    /// Returns how many places the player moved, positive when climbing. This is
+    /// 0 for [`RankChange::Entered`].
+    pub fn rank_delta(&self) -> i64 {
+        match self {
+            Self::Climbed {
+                entry,
+                previous_rank,
+                ..
+            }
+            | Self::Fell {
+                entry,
+                previous_rank,
+                ..
+            } => *previous_rank as i64 - entry.rank as i64,
+            Self::Entered(_) => 0,
+        }
+    }
+
+    /// Returns the change in the player's value. This is the full value for
+    /// [`RankChange::Entered`].
+    pub fn value_delta(&self) -> i64 {
+        match self {
+            Self::Climbed {
+                entry,
+                previous_value,
+                ..
+            }
+            | Self::Fell {
+                entry,
+                previous_value,
+                ..
+            } => entry.value as i64 - *previous_value as i64,
+            Self::Entered(entry) => entry.value as i64,
+        }
+    }
+}
+
+/// Compares two snapshots of the leaderboard and returns every rank change between
+/// them, in the order the players appear in `new`.
+///
+/// Players whose rank did not change are skipped, even if their value did. Players
+/// that are in `old` but missing from `new` are ignored, as a snapshot may only
+/// cover some of the leaderboard's pages.
+///
+/// # Example
+/// ```
+/// use roli::leaderboard::{self, LeaderboardEntry};
+///
+/// let entry = |rank, user_id, value| LeaderboardEntry {
+///     rank,
+///     user_id,
+///     value,
+///     ..Default::default()
+/// };
+///
+/// let old = vec![entry(1, 10, 5000), entry(2, 20, 4000)];
+/// let new = vec![entry(1, 20, 6000), entry(2, 10, 5000)];
+///
+/// for change in leaderboard::diff(&old, &new) {
+///     println!("{}: {:+} places", change.entry().user_id, change.rank_delta());
+/// }
+/// ```
+pub fn diff(old: &[LeaderboardEntry], new: &[LeaderboardEntry]) -> Vec<RankChange> {
+    let old = old
+        .iter()
+        .map(|entry| (entry.user_id, (entry.rank, entry.value)))
+        .collect::<HashMap<_, _>>();
+
+    new.iter()
+        .filter_map(|entry| {
+            let (previous_rank, previous_value) = match old.get(&entry.user_id) {
+                Some(x) => *x,
+                None => return Some(RankChange::Entered(entry.clone())),
+            };
+
+            match entry.rank.cmp(&previous_rank) {
+                std::cmp::Ordering::Less => Some(RankChange::Climbed {
+                    entry: entry.clone(),
+                    previous_rank,
+                    previous_value,
+                }),
+                std::cmp::Ordering::Greater => Some(RankChange::Fell {
+                    entry: entry.clone(),
+                    previous_rank,
+                    previous_value,
+                }),
+                std::cmp::Ordering::Equal => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(rank: u64, user_id: u64, value: u64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank,
+            user_id,
+            value,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = vec![entry(1, 10, 5000), entry(2, 20, 4000), entry(3, 30, 3000)];
+        let new = vec![entry(1, 20, 6000), entry(2, 10, 5000), entry(3, 40, 3500)];
+
+        let changes = diff(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                RankChange::Climbed {
+                    entry: entry(1, 20, 6000),
+                    previous_rank: 2,
+                    previous_value: 4000
+                },
+                RankChange::Fell {
+                    entry: entry(2, 10, 5000),
+                    previous_rank: 1,
+                    previous_value: 5000
+                },
+                RankChange::Entered(entry(3, 40, 3500)),
+            ]
+        );
+
+        assert_eq!(
+            changes.iter().map(|x| x.rank_delta()).collect::<Vec<_>>(),
+            vec![1, -1, 0]
+        );
+
+        assert_eq!(
+            changes.iter().map(|x| x.value_delta()).collect::<Vec<_>>(),
+            vec![2000, 0, 3500]
+        );
+    }
+}
@@ -41,6 +41,23 @@
 //!     Ok(())   
 //! }
 //! ```
+//!
+//! # Runtimes
+//!
+//! The endpoint methods only depend on `reqwest` and `futures`, so they can be
+//! awaited from any executor that can drive `reqwest` (`tokio`, `async-std` with
+//! its tokio compatibility layer, etc.).
+//!
+//! Anything that needs to sleep between requests or refresh in the background,
+//! like [`streams`] and [`cache`], uses tokio and is gated behind the default
+//! `async-rt` feature. Disable default features to drop the `tokio` dependency:
+//!
+//! ```toml
+//! roli = { version = "0.7", default-features = false }
+//! ```
+//!
+//! All futures and streams returned by this crate are `Send`, so they can be
+//! spawned onto multi-threaded runtimes.
 
 // todo: please pub use ItemDetails
 // todo: make it so roli_verification can be set on creation
@@ -50,21 +67,89 @@
 #![warn(missing_docs)]
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
 
+/// Contains aggregate summaries of the raw market feeds.
+pub mod analytics;
+/// Contains the [`RoliApi`](api::RoliApi) trait, implemented by [`Client`] and by mocks.
+pub mod api;
+/// Contains the audit log of every request a [`Client`] makes.
+#[cfg(feature = "audit")]
+pub mod audit;
+/// Contains a backtester for running deal strategies over historical sales and deals activity.
+pub mod backtest;
+/// Contains caches of endpoint responses with stale-while-revalidate semantics.
+#[cfg(feature = "async-rt")]
+pub mod cache;
+/// Contains a parsed form of the Roblox cdn thumbnail urls found on games and groups,
+/// and the avatar headshot url of players.
+pub mod cdn;
+/// Contains the config file used by bots built on this crate.
+#[cfg(feature = "config")]
+pub mod config;
 /// Contains all the endpoints associated with the deals page.
 pub mod deals;
+/// Contains serenity embed builders for deals, sales, and trade ads.
+#[cfg(feature = "serenity")]
+pub mod discord;
+/// Contains Arrow and Parquet exporters for sales, items, and trade ads.
+#[cfg(feature = "arrow")]
+pub mod export;
+/// Contains helpers for formatting values and robux the way Rolimons shows them.
+pub mod fmt;
 /// Contains all the endpoints associated with games.
 pub mod games;
 /// Contains all the endpoints associated with groups.
 pub mod groups;
+/// Contains the [`EventHandler`](handler::EventHandler) trait for reacting to events with callbacks.
+pub mod handler;
+/// Contains typed ids and parsers that extract them from pasted Rolimons and Roblox urls.
+pub mod ids;
 /// Contains all the endpoints associated with getting item details.
 pub mod items;
+/// Contains the player leaderboard and diffing of ranks between snapshots of it.
+pub mod leaderboard;
+/// Contains Rolimons' current rate limits and cooldowns, and suggested poll intervals.
+pub mod limits;
 /// Contains all the endpoints associated with the market activity page.
 pub mod market_activity;
+/// Contains the trade fairness scorer used by auto accept and decline bots.
+pub mod matcher;
+/// Contains the names of the metrics recorded when the `metrics` feature is enabled.
+pub mod metrics;
 /// Contains all the endpoints associated with players.
 pub mod players;
+/// Contains deserializers for the loosely typed values found in raw Rolimons payloads.
+pub mod serde_helpers;
+/// Contains the [`StateStore`](state::StateStore) trait used to keep cache snapshots and stream cursors.
+pub mod state;
+/// Contains the per-endpoint health report returned by [`Client::status_report`] and
+/// the overall [`ServiceStatus`](status::ServiceStatus) returned by [`Client::service_status`].
+pub mod status;
+/// Contains a SQLite store for historical item snapshots, sales, and trade ads.
+#[cfg(feature = "sqlite")]
+pub mod store;
+/// Contains polling streams built on top of the endpoints.
+#[cfg(feature = "async-rt")]
+pub mod streams;
+/// Contains fake data generators and a mock client for testing code built on this crate.
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 /// Contains all the endpoints associated with the trade ads page.
 pub mod trade_ads;
+/// Contains general purpose utilities used by the streams, for consumers that poll the endpoints themselves.
+pub mod util;
+/// Contains watch lists of items and alert thresholds that can be loaded from files, and
+/// alerts for changes to the value and rap of watched items.
+pub mod watchlist;
+
+mod auth;
+mod compression;
+#[cfg(feature = "raw")]
+mod raw;
+mod request;
 
 // Re-export reqwest so people can use the correct version.
 pub use reqwest;
@@ -72,13 +157,76 @@ pub use reqwest;
 const USER_AGENT: &str =
     "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:101.0) Gecko/20100101 Firefox/101.0";
 
+/// The headers a [`Client`] identifies itself with, set with
+/// [`ClientBuilder::set_header_profile`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum HeaderProfile {
+    /// Sends the user agent of a desktop browser. This is the default, and what
+    /// the authenticated endpoints expect.
+    #[default]
+    Browser,
+    /// Declares the client as a bot, with a user agent of the form
+    /// `{name} (+{contact}) roli/{version}` so that Rolimons knows who to contact
+    /// about its traffic.
+    ///
+    /// Authenticated endpoints may reject requests that don't look like they come
+    /// from a browser. The name and contact must only contain visible ASCII characters
+    /// and spaces, which [`ClientBuilder::try_build`] checks.
+    DeclaredBot {
+        /// The name of the bot, optionally with its own version, such as `"TradeBot/1.2"`.
+        name: String,
+        /// A url or email address the bot's operator can be reached at.
+        contact: String,
+    },
+}
+
+impl HeaderProfile {
+    /// Returns the user agent sent with this profile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::HeaderProfile;
+    /// let profile = HeaderProfile::DeclaredBot {
+    ///     name: "TradeBot/1.2".to_string(),
+    ///     contact: "https://example.com/bot".to_string(),
+    /// };
+    ///
+    /// assert!(profile.user_agent().starts_with("TradeBot/1.2 (+https://example.com/bot) roli/"));
+    /// ```
+    pub fn user_agent(&self) -> String {
+        match self {
+            Self::Browser => USER_AGENT.to_string(),
+            Self::DeclaredBot { name, contact } => {
+                format!("{} (+{}) roli/{}", name, contact, env!("CARGO_PKG_VERSION"))
+            }
+        }
+    }
+
+    /// Returns the user agent header of this profile, or `None` for [`HeaderProfile::Browser`].
+    fn user_agent_header(&self) -> Result<Option<reqwest::header::HeaderValue>, RoliError> {
+        match self {
+            Self::Browser => Ok(None),
+            Self::DeclaredBot { .. } => reqwest::header::HeaderValue::from_str(&self.user_agent())
+                .map(Some)
+                .map_err(|_| {
+                    RoliError::InvalidConfig(
+                        "the user agent of the header profile can not be put in a header"
+                            .to_string(),
+                    )
+                }),
+        }
+    }
+}
+
 /// The universal error used in this crate.
 #[derive(thiserror::Error, Debug, Default)]
 pub enum RoliError {
     /// Used when an endpoint returns `success: false`.
     #[error("Request Returned Unsuccessful")]
     RequestReturnedUnsuccessful,
-    /// Used when an endpoint returns status code 429.
+    /// Used when an endpoint returns status code 429, or when a request is not sent
+    /// because it would go over [`ClientBuilder::set_requests_per_minute`].
     #[default]
     #[error("Too Many Requests")]
     TooManyRequests,
@@ -88,6 +236,10 @@ pub enum RoliError {
     /// Used when the response from an API endpoint is malformed.
     #[error("Malformed Response")]
     MalformedResponse,
+    /// Used when an endpoint returns status code 200 with an html page (such as a
+    /// maintenance page) instead of json.
+    #[error("Unexpected Html Response")]
+    UnexpectedHtmlResponse,
     /// Used when roli_verification contains ASCII characters outside of the range 32-127.
     #[error("Roli Verification Contains Invalid Characters")]
     RoliVerificationContainsInvalidCharacters,
@@ -97,39 +249,176 @@ pub enum RoliError {
     /// Used when roli_verification is not set.
     #[error("Roli Verification Not Set")]
     RoliVerificationNotSet,
-    /// Used when a cooldown for something, such as making a trade ad, has not expired.
+    /// Used when a cooldown for something, such as making a trade ad, has not expired,
+    /// or when a trade ad is not sent because it would go over
+    /// [`ClientBuilder::set_trade_ads_per_day`].
     #[error("Cooldown Not Expired")]
     CooldownNotExpired,
+    /// Used when a trade ad breaks one of Rolimons' posting rules, before it is sent.
+    #[error("Trade Ad Violation {0}")]
+    TradeAdViolation(trade_ads::TradeAdViolation),
     /// Used for any status codes that do not fit any enum variants of this error.
     /// If you encounter this enum variant, please submit an issue so a variant can be
     /// made or the crate can be fixed.
     #[error("Unidentified Status Code {0}")]
     UnidentifiedStatusCode(u16),
+    /// Used when a `BotConfig` cannot be read or parsed, or when a
+    /// [`ClientBuilder`] has options that can not be used together.
+    #[error("Invalid Config {0}")]
+    InvalidConfig(String),
+    /// Used when a call made through [`Client::with_deadline`] does not finish in time.
+    #[cfg(feature = "async-rt")]
+    #[error("Deadline Exceeded")]
+    DeadlineExceeded,
+    /// Used for any io error, such as when reading or writing a `roli::state::FileState`.
+    #[error("Io Error {0}")]
+    IoError(std::io::Error),
+    /// Used for any error returned by Redis in `roli::state::redis`.
+    #[cfg(feature = "redis")]
+    #[error("Redis Error {0}")]
+    RedisError(redis::RedisError),
+    /// Used for any error returned by SQLite in `roli::store`.
+    #[cfg(feature = "sqlite")]
+    #[error("Sqlite Error {0}")]
+    SqliteError(rusqlite::Error),
+    /// Used for any error returned by Arrow in `roli::export`.
+    #[cfg(feature = "arrow")]
+    #[error("Arrow Error {0}")]
+    ArrowError(arrow_schema::ArrowError),
+    /// Used for any error returned by Parquet in `roli::export`.
+    #[cfg(feature = "parquet")]
+    #[error("Parquet Error {0}")]
+    ParquetError(parquet::errors::ParquetError),
     /// Used for any reqwest error that occurs.
     #[error("RequestError {0}")]
     ReqwestError(reqwest::Error),
+    /// Used when a request shared with identical requests (see
+    /// [`ClientBuilder::set_coalescing`]) failed before a response was received. Only
+    /// the caller that started the request gets the [`RoliError::ReqwestError`]; the
+    /// callers that joined it get this, with the error's message.
+    #[error("Coalesced Request Failed {0}")]
+    CoalescedRequestFailed(String),
+}
+
+/// The api endpoints wrapped by a [`Client`].
+///
+/// Used to label per-endpoint information, such as metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Endpoint {
+    /// [`Client::all_item_details`].
+    AllItemDetails,
+    /// [`Client::deals_activity`].
+    DealsActivity,
+    /// [`Client::recent_trade_ads`].
+    RecentTradeAds,
+    /// [`Client::create_trade_ad`].
+    CreateTradeAd,
+    /// [`Client::player_search`].
+    PlayerSearch,
+    /// [`Client::player_profile`].
+    PlayerProfile,
+    /// [`Client::games_list`].
+    GamesList,
+    /// [`Client::group_search`].
+    GroupSearch,
+    /// [`Client::recent_sales`].
+    RecentSales,
+}
+
+/// Any event produced by this crate's feeds and streams, in one serializable type.
+///
+/// Every event type converts into this with `From`, so events from different
+/// streams can be merged and forwarded to a message queue without a conversion
+/// layer per type. Events serialize as `{"type": "...", "data": {...}}`, where
+/// `type` is the same as [`Event::kind`].
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::Event;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+///
+/// for sale in client.recent_sales().await? {
+///     let event = Event::from(sale);
+///     let payload = serde_json::to_string(&event)?;
+///     println!("{}: {}", event.kind(), payload);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    /// A sale from [`Client::recent_sales`].
+    Sale(market_activity::Sale),
+    /// A price update from [`Client::deals_activity`].
+    PriceUpdate(deals::PriceUpdate),
+    /// A rap update from [`Client::deals_activity`].
+    RapUpdate(deals::RapUpdate),
+    /// An activity of an unknown kind from [`Client::deals_activity`].
+    OtherActivity(deals::OtherActivity),
+    /// A trade ad from [`Client::recent_trade_ads`] or a trade ad stream.
+    TradeAd(trade_ads::TradeAd),
+    /// A change in an item's details, from [`items::diff`].
+    ItemChange(items::ItemChange),
+    /// A change in a player's presence, from a presence stream.
+    #[cfg(feature = "async-rt")]
+    PresenceChange(streams::PresenceChange),
+    /// A change in a group's member count, from [`groups::diff`].
+    GroupChange(groups::GroupChange),
+    /// A change in a player's leaderboard rank, from [`leaderboard::diff`].
+    RankChange(leaderboard::RankChange),
+    /// A sale flagged by an [`analytics::AnomalyDetector`].
+    AnomalousSale(analytics::AnomalousSale),
 }
 
 /// Used for holding either an integer or a string in [`AllItemDetailsResponse`].
 /// This is necessary as (for some reason) numbers are represented as strings
 /// in the api response.
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 #[serde(untagged)]
 pub(crate) enum Code {
     Integer(i64),
+    Float(f64),
     String(String),
 }
 
 /// Used to interact with the rest of the rRolimons api wrapper.
 ///
 /// Contains any necessary authentication and the reqwest client. All
-/// `Client` methods make exactly one api call.
+/// `Client` methods make at most one api call.
+///
+/// Identical requests made at the same time (e.g. ten tasks calling
+/// [`Client::all_item_details`] at once) are coalesced into a single api call
+/// whose response is shared between them. This can be turned off per endpoint
+/// with [`ClientBuilder::set_coalescing`]. Clones of a client share in-flight
+/// requests.
 ///
 /// Created using a [`ClientBuilder`].
 #[derive(Clone, Debug, Default)]
 pub struct Client {
     roli_verification: Option<String>,
     reqwest_client: reqwest::Client,
+    /// `None` for [`HeaderProfile::Browser`].
+    user_agent: Option<reqwest::header::HeaderValue>,
+    coalescer: Arc<request::Coalescer>,
+    player_profile_cache: Arc<players::PlayerProfileCache>,
+    status: Arc<status::StatusTracker>,
+    request_budget: Option<Arc<request::RequestBudget>>,
+    trade_ad_budget: Option<Arc<request::RequestBudget>>,
+    #[cfg(feature = "async-rt")]
+    deadline: Option<Duration>,
+    /// Where the body of the last json response is kept, for the `*_with_raw` methods.
+    #[cfg(feature = "raw")]
+    raw_body: Option<Arc<std::sync::Mutex<Option<bytes::Bytes>>>>,
+    #[cfg(feature = "audit")]
+    audit_log: Option<audit::AuditLog>,
+    #[cfg(feature = "audit")]
+    replay: Option<Arc<audit::Replay>>,
 }
 
 /// Used to build a [`Client`].
@@ -139,28 +428,334 @@ pub struct Client {
 pub struct ClientBuilder {
     roli_verification: Option<String>,
     reqwest_client: Option<reqwest::Client>,
+    header_profile: HeaderProfile,
+    connection: request::ConnectionOptions,
+    uncoalesced_endpoints: HashSet<Endpoint>,
+    player_profile_cache: Option<(Duration, usize)>,
+    requests_per_minute: Option<u32>,
+    trade_ads_per_day: Option<u32>,
+    #[cfg(feature = "audit")]
+    audit_log: Option<audit::AuditLog>,
+    #[cfg(feature = "audit")]
+    replay: Option<Vec<audit::AuditEntry>>,
+}
+
+/// Describes a bulk response so that persisted copies of it are self-describing.
+///
+/// Parsers that read archived snapshots can branch on `schema_version` if the way
+/// this crate parses a response ever changes. A `schema_version` of 0 means the
+/// snapshot was saved before versions were recorded.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct ResponseMetadata {
+    /// The version of the schema the response was parsed with, such as
+    /// [`items::SCHEMA_VERSION`] or [`games::SCHEMA_VERSION`].
+    pub schema_version: u32,
+    /// The unix timestamp the response was fetched at.
+    pub fetched_at: u64,
+}
+
+impl ResponseMetadata {
+    /// Creates metadata for a response parsed with `schema_version` and fetched at `fetched_at`.
+    pub fn new(schema_version: u32, fetched_at: u64) -> Self {
+        Self {
+            schema_version,
+            fetched_at,
+        }
+    }
+}
+
+/// Returns the current unix timestamp in seconds.
+pub(crate) fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Returns `fallback` for an enum code this crate does not know about, or a
+/// [`RoliError::MalformedResponse`] if the `strict-parsing` feature is enabled.
+///
+/// Rolimons occasionally adds new codes, and bots would rather keep running with
+/// a default than fail every request until the crate is updated.
+fn unknown_code<T>(fallback: T) -> Result<T, RoliError> {
+    if cfg!(feature = "strict-parsing") {
+        Err(RoliError::MalformedResponse)
+    } else {
+        Ok(fallback)
+    }
+}
+
+/// Rounds a number that was not sent as a plain integer (a float, or a string with
+/// thousands separators or a fraction), or returns a [`RoliError::MalformedResponse`]
+/// if the `strict-parsing` feature is enabled.
+///
+/// Rolimons occasionally sends these for numbers that are integers everywhere else.
+fn coerce_number(x: f64) -> Result<i64, RoliError> {
+    if cfg!(feature = "strict-parsing") || !x.is_finite() || x.abs() >= i64::MAX as f64 {
+        return Err(RoliError::MalformedResponse);
+    }
+
+    Ok(x.round() as i64)
 }
 
 impl Code {
     /// Returns an i64 inside if the operation was successful, otherwise returns a [`RoliError::MalformedResponse`]
     /// (as [`Code`] is only used to parse responses).
+    ///
+    /// Floats and strings like `"1,234"` or `"1234.0"` are coerced (see [`coerce_number`]).
     fn to_i64(&self) -> Result<i64, RoliError> {
         match self {
             Self::Integer(x) => Ok(*x),
-            Self::String(x) => x.parse().map_err(|_| RoliError::MalformedResponse),
+            Self::Float(x) => coerce_number(*x),
+            Self::String(x) => match x.parse() {
+                Ok(x) => Ok(x),
+                Err(_) => x
+                    .chars()
+                    .filter(|c| !matches!(c, ',' | '_'))
+                    .collect::<String>()
+                    .trim()
+                    .parse()
+                    .map_err(|_| RoliError::MalformedResponse)
+                    .and_then(coerce_number),
+            },
+        }
+    }
+
+    /// The same as [`Code::to_i64`], but also returns a [`RoliError::MalformedResponse`]
+    /// if the number is negative.
+    fn to_u64(&self) -> Result<u64, RoliError> {
+        u64::try_from(self.to_i64()?).map_err(|_| RoliError::MalformedResponse)
+    }
+
+    /// The same as [`Code::to_u64`], but a negative number is treated as an unknown code:
+    /// unless the `strict-parsing` feature is enabled, it is clamped to 0 instead of
+    /// failing the whole response.
+    fn to_u64_lenient(&self) -> Result<u64, RoliError> {
+        let x = self.to_i64()?;
+
+        match u64::try_from(x) {
+            Ok(x) => Ok(x),
+            Err(_) => unknown_code(0),
+        }
+    }
+
+    /// Returns `None` if the code is -1, which Rolimons uses for missing numbers
+    /// (like the value of an unvalued item). Otherwise the same as [`Code::to_u64`].
+    fn to_optional_u64(&self) -> Result<Option<u64>, RoliError> {
+        match self.to_i64()? {
+            -1 => Ok(None),
+            _ => self.to_u64().map(Some),
+        }
+    }
+
+    /// Returns `true` for 1 and `false` for -1, which is how Rolimons encodes flags
+    /// (like whether an item is projected). Other values are treated as an unknown code.
+    fn to_bool(&self) -> Result<bool, RoliError> {
+        match self.to_i64()? {
+            1 => Ok(true),
+            -1 => Ok(false),
+            _ => unknown_code(false),
         }
     }
 }
 
+impl RoliError {
+    /// Returns whether the request that caused the error may succeed if it is made
+    /// again later.
+    ///
+    /// This is true for rate limits, server errors, timeouts, connection errors, and
+    /// maintenance pages, and false for errors that will happen again on every retry,
+    /// such as authentication errors and malformed responses.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use std::time::Duration;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    ///
+    /// let sales = loop {
+    ///     match client.recent_sales().await {
+    ///         Ok(sales) => break sales,
+    ///         Err(e) if e.is_retryable() => tokio::time::sleep(Duration::from_secs(5)).await,
+    ///         Err(e) => return Err(e.into()),
+    ///     }
+    /// };
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::TooManyRequests | Self::InternalServerError | Self::UnexpectedHtmlResponse => {
+                true
+            }
+            #[cfg(feature = "async-rt")]
+            Self::DeadlineExceeded => true,
+            Self::CoalescedRequestFailed(_) => true,
+            Self::UnidentifiedStatusCode(status_code) => is_retryable_status(*status_code),
+            Self::ReqwestError(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status()
+                        .is_some_and(|status| is_retryable_status(status.as_u16()))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns whether the error was caused by Rolimons rate limiting the client.
+    pub fn is_rate_limited(&self) -> bool {
+        match self {
+            Self::TooManyRequests => true,
+            Self::UnidentifiedStatusCode(status_code) => *status_code == 429,
+            Self::ReqwestError(e) => e.status().is_some_and(|status| status.as_u16() == 429),
+            _ => false,
+        }
+    }
+
+    /// Returns whether the error means Rolimons is down for maintenance: an html page
+    /// served instead of json, or status code 503.
+    ///
+    /// Rolimons does not announce maintenance through its api, so this is the only
+    /// signal available. See [`Client::service_status`].
+    pub fn is_maintenance(&self) -> bool {
+        match self {
+            Self::UnexpectedHtmlResponse => true,
+            Self::UnidentifiedStatusCode(status_code) => *status_code == 503,
+            Self::ReqwestError(e) => e.status().is_some_and(|status| status.as_u16() == 503),
+            _ => false,
+        }
+    }
+
+    /// Returns whether the error was caused by a missing, malformed, invalid, or expired
+    /// roli_verification token.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            Self::RoliVerificationContainsInvalidCharacters
+                | Self::RoliVerificationInvalidOrExpired
+                | Self::RoliVerificationNotSet
+        )
+    }
+}
+
+/// Returns whether a response with `status_code` may succeed if the request is retried.
+fn is_retryable_status(status_code: u16) -> bool {
+    matches!(status_code, 408 | 429 | 500..=599)
+}
+
+impl Endpoint {
+    /// Returns the snake case name of the endpoint, which is the same as the
+    /// name of the [`Client`] method that calls it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AllItemDetails => "all_item_details",
+            Self::DealsActivity => "deals_activity",
+            Self::RecentTradeAds => "recent_trade_ads",
+            Self::CreateTradeAd => "create_trade_ad",
+            Self::PlayerSearch => "player_search",
+            Self::PlayerProfile => "player_profile",
+            Self::GamesList => "games_list",
+            Self::GroupSearch => "group_search",
+            Self::RecentSales => "recent_sales",
+        }
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 impl std::fmt::Display for Code {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Integer(x) => write!(f, "{}", x),
+            Self::Float(x) => write!(f, "{}", x),
             Self::String(x) => write!(f, "{}", x),
         }
     }
 }
 
+impl Event {
+    /// Returns the name of the event's type in snake case (e.g. `"price_update"`),
+    /// which can be used as a topic or routing key.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Sale(_) => "sale",
+            Self::PriceUpdate(_) => "price_update",
+            Self::RapUpdate(_) => "rap_update",
+            Self::OtherActivity(_) => "other_activity",
+            Self::TradeAd(_) => "trade_ad",
+            Self::ItemChange(_) => "item_change",
+            #[cfg(feature = "async-rt")]
+            Self::PresenceChange(_) => "presence_change",
+            Self::GroupChange(_) => "group_change",
+            Self::RankChange(_) => "rank_change",
+            Self::AnomalousSale(_) => "anomalous_sale",
+        }
+    }
+}
+
+impl From<market_activity::Sale> for Event {
+    fn from(sale: market_activity::Sale) -> Self {
+        Self::Sale(sale)
+    }
+}
+
+impl From<deals::Activity> for Event {
+    fn from(activity: deals::Activity) -> Self {
+        match activity {
+            deals::Activity::PriceUpdate(x) => Self::PriceUpdate(x),
+            deals::Activity::RapUpdate(x) => Self::RapUpdate(x),
+            deals::Activity::Other(x) => Self::OtherActivity(x),
+        }
+    }
+}
+
+impl From<trade_ads::TradeAd> for Event {
+    fn from(trade_ad: trade_ads::TradeAd) -> Self {
+        Self::TradeAd(trade_ad)
+    }
+}
+
+impl From<items::ItemChange> for Event {
+    fn from(change: items::ItemChange) -> Self {
+        Self::ItemChange(change)
+    }
+}
+
+#[cfg(feature = "async-rt")]
+impl From<streams::PresenceChange> for Event {
+    fn from(change: streams::PresenceChange) -> Self {
+        Self::PresenceChange(change)
+    }
+}
+
+impl From<groups::GroupChange> for Event {
+    fn from(change: groups::GroupChange) -> Self {
+        Self::GroupChange(change)
+    }
+}
+
+impl From<leaderboard::RankChange> for Event {
+    fn from(change: leaderboard::RankChange) -> Self {
+        Self::RankChange(change)
+    }
+}
+
+impl From<analytics::AnomalousSale> for Event {
+    fn from(anomalous_sale: analytics::AnomalousSale) -> Self {
+        Self::AnomalousSale(anomalous_sale)
+    }
+}
+
 impl Client {
     #[deprecated(since = "0.6.7", note = "Use ClientBuilder::new().build() instead.")]
     /// Constructs a client without providing a roli verification token or custom
@@ -197,6 +792,58 @@ impl Client {
     pub fn contains_roli_verification(&self) -> bool {
         self.roli_verification.is_some()
     }
+
+    /// Returns a copy of the client where every call must finish within `deadline`,
+    /// otherwise it returns [`RoliError::DeadlineExceeded`].
+    ///
+    /// Unlike a reqwest timeout, the deadline covers the whole call, including
+    /// waiting on a coalesced request and parsing the response. The copy shares
+    /// in-flight requests and caches with this client.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    ///
+    /// loop {
+    ///     // Never overrun the 3 second tick.
+    ///     match client.with_deadline(Duration::from_secs(2)).recent_sales().await {
+    ///         Ok(sales) => println!("{} sales", sales.len()),
+    ///         Err(roli::RoliError::DeadlineExceeded) => println!("Skipping slow tick"),
+    ///         Err(e) => return Err(e.into()),
+    ///     }
+    ///
+    ///     tokio::time::sleep(Duration::from_secs(3)).await;
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "async-rt")]
+    pub fn with_deadline(&self, deadline: Duration) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self.clone()
+        }
+    }
+
+    /// The user agent every request is sent with, from the client's [`HeaderProfile`].
+    pub(crate) fn user_agent(&self) -> reqwest::header::HeaderValue {
+        match &self.user_agent {
+            Some(user_agent) => user_agent.clone(),
+            None => reqwest::header::HeaderValue::from_static(USER_AGENT),
+        }
+    }
+
+    /// Returns the audit log set with [`ClientBuilder::set_audit_log`], if any.
+    ///
+    /// Only available with the `audit` feature.
+    #[cfg(feature = "audit")]
+    pub fn audit_log(&self) -> Option<&audit::AuditLog> {
+        self.audit_log.as_ref()
+    }
 }
 
 impl ClientBuilder {
@@ -205,17 +852,125 @@ impl ClientBuilder {
         Self {
             roli_verification: None,
             reqwest_client: None,
+            header_profile: HeaderProfile::default(),
+            connection: request::ConnectionOptions::default(),
+            uncoalesced_endpoints: HashSet::new(),
+            player_profile_cache: None,
+            requests_per_minute: None,
+            trade_ads_per_day: None,
+            #[cfg(feature = "audit")]
+            audit_log: None,
+            #[cfg(feature = "audit")]
+            replay: None,
         }
     }
 
     /// Builds the `Client` struct using the values set in this builder. Uses default values for any unset fields.
+    ///
+    /// # Panics
+    /// Panics if no reqwest client was set and one can not be created (the same as
+    /// [`reqwest::Client::new`]), or if the name or contact of a
+    /// [`HeaderProfile::DeclaredBot`] contains characters that can't be sent in a header
+    /// (such as control characters). Use [`ClientBuilder::try_build`] to get an error
+    /// instead, especially when the profile comes from user input.
     pub fn build(self) -> Client {
-        let reqwest_client = self.reqwest_client.unwrap_or_default();
+        let reqwest_client = match self.reqwest_client {
+            Some(reqwest_client) => reqwest_client,
+            None => self
+                .connection
+                .reqwest_client()
+                .expect("failed to create the reqwest client"),
+        };
+
+        let user_agent = self
+            .header_profile
+            .user_agent_header()
+            .expect("the user agent of the header profile is not a valid header");
 
         Client {
             roli_verification: self.roli_verification,
             reqwest_client,
+            user_agent,
+            coalescer: Arc::new(request::Coalescer::new(self.uncoalesced_endpoints)),
+            player_profile_cache: Arc::new(match self.player_profile_cache {
+                Some((ttl, capacity)) => players::PlayerProfileCache::new(ttl, capacity),
+                None => players::PlayerProfileCache::default(),
+            }),
+            status: Arc::default(),
+            request_budget: self.requests_per_minute.map(|requests_per_minute| {
+                Arc::new(request::RequestBudget::new(
+                    requests_per_minute,
+                    Duration::from_secs(60),
+                ))
+            }),
+            trade_ad_budget: self.trade_ads_per_day.map(|trade_ads_per_day| {
+                Arc::new(request::RequestBudget::new(
+                    trade_ads_per_day,
+                    Duration::from_secs(24 * 60 * 60),
+                ))
+            }),
+            #[cfg(feature = "async-rt")]
+            deadline: None,
+            #[cfg(feature = "raw")]
+            raw_body: None,
+            #[cfg(feature = "audit")]
+            audit_log: self.audit_log,
+            #[cfg(feature = "audit")]
+            replay: self
+                .replay
+                .map(|entries| Arc::new(audit::Replay::new(entries))),
+        }
+    }
+
+    /// The same as [`ClientBuilder::build`], but checks the options first so that
+    /// misconfiguration fails here instead of on the first request.
+    ///
+    /// Returns [`RoliError::RoliVerificationContainsInvalidCharacters`] if
+    /// roli_verification can't be sent in a header, [`RoliError::InvalidConfig`] if
+    /// connection options are set alongside a reqwest client or the user agent of the
+    /// [`HeaderProfile`] can't be sent in a header, and
+    /// [`RoliError::ReqwestError`] if the reqwest client can't be created. With the
+    /// `audit` feature, also returns [`RoliError::InvalidConfig`] if both an audit log
+    /// and a replay are set, as replayed requests are never recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::{ClientBuilder, RoliError};
+    /// let result = ClientBuilder::new()
+    ///     .set_roli_verification("bad\nvalue".to_string())
+    ///     .try_build();
+    ///
+    /// assert!(matches!(
+    ///     result,
+    ///     Err(RoliError::RoliVerificationContainsInvalidCharacters)
+    /// ));
+    /// ```
+    pub fn try_build(mut self) -> Result<Client, RoliError> {
+        if let Some(roli_verification) = &self.roli_verification {
+            auth::roli_verification_cookie(roli_verification)?;
+        }
+
+        self.header_profile.user_agent_header()?;
+
+        if self.reqwest_client.is_some() && self.connection.is_set() {
+            return Err(RoliError::InvalidConfig(
+                "connection options can not be used with a custom reqwest client".to_string(),
+            ));
+        }
+
+        #[cfg(feature = "audit")]
+        if self.audit_log.is_some() && self.replay.is_some() {
+            return Err(RoliError::InvalidConfig(
+                "an audit log can not be used with a replay".to_string(),
+            ));
+        }
+
+        if self.reqwest_client.is_none() {
+            self.reqwest_client = Some(self.connection.reqwest_client()?);
         }
+
+        Ok(self.build())
     }
 
     /// Sets the value for the optional `roli_verification` field.
@@ -247,4 +1002,335 @@ impl ClientBuilder {
         self.reqwest_client = Some(reqwest_client);
         self
     }
+
+    /// Sets the [`HeaderProfile`] requests are sent with. Defaults to
+    /// [`HeaderProfile::Browser`].
+    ///
+    /// [`ClientBuilder::build`] panics if the user agent of the profile can't be sent in
+    /// a header, so use [`ClientBuilder::try_build`] if the name or contact are not
+    /// known to be valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::{ClientBuilder, HeaderProfile};
+    /// let client = ClientBuilder::new()
+    ///     .set_header_profile(HeaderProfile::DeclaredBot {
+    ///         name: "TradeBot/1.2".to_string(),
+    ///         contact: "bot@example.com".to_string(),
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn set_header_profile(mut self, header_profile: HeaderProfile) -> Self {
+        self.header_profile = header_profile;
+        self
+    }
+
+    /// Sets how long an idle connection is kept in the pool before it is closed.
+    /// `None` keeps idle connections open forever. Defaults to reqwest's 90 seconds.
+    ///
+    /// This and the other connection options only apply to the reqwest client the
+    /// builder creates, and are ignored if [`ClientBuilder::set_reqwest_client`] is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::ClientBuilder;
+    /// # use std::time::Duration;
+    /// // Keeps connections of a 3 second poller from being closed between polls.
+    /// let client = ClientBuilder::new()
+    ///     .set_pool_idle_timeout(Some(Duration::from_secs(300)))
+    ///     .set_pool_max_idle_per_host(4)
+    ///     .set_tcp_keepalive(Duration::from_secs(60))
+    ///     .build();
+    /// ```
+    pub fn set_pool_idle_timeout(mut self, pool_idle_timeout: Option<Duration>) -> Self {
+        self.connection.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+
+    /// Sets the maximum amount of idle connections kept in the pool per host.
+    /// Defaults to no limit.
+    pub fn set_pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.connection.pool_max_idle_per_host = Some(pool_max_idle_per_host);
+        self
+    }
+
+    /// Sets `SO_KEEPALIVE` on every socket with the given interval. Not set by default.
+    pub fn set_tcp_keepalive(mut self, tcp_keepalive: Duration) -> Self {
+        self.connection.tcp_keepalive = Some(tcp_keepalive);
+        self
+    }
+
+    /// Forces the HTTP version used. `true` only uses HTTP/2 (with prior knowledge)
+    /// and `false` only uses HTTP/1. By default the version is negotiated with the server.
+    pub fn set_http2(mut self, enabled: bool) -> Self {
+        self.connection.http2 = Some(enabled);
+        self
+    }
+
+    /// Sets whether identical in-flight requests to `endpoint` are coalesced into
+    /// one api call. Coalescing is enabled for every endpoint by default.
+    ///
+    /// [`Endpoint::CreateTradeAd`] is never coalesced, as it is not idempotent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::{ClientBuilder, Endpoint};
+    /// let client = ClientBuilder::new()
+    ///     .set_coalescing(Endpoint::PlayerProfile, false)
+    ///     .build();
+    /// ```
+    pub fn set_coalescing(mut self, endpoint: Endpoint, enabled: bool) -> Self {
+        if enabled {
+            self.uncoalesced_endpoints.remove(&endpoint);
+        } else {
+            self.uncoalesced_endpoints.insert(endpoint);
+        }
+
+        self
+    }
+
+    /// Sets the ttl and capacity of the cache used by [`Client::player_profile_cached`].
+    ///
+    /// `ttl` is raised to [`players::MIN_PLAYER_PROFILE_INTERVAL`] and `capacity` to 1
+    /// if they are lower.
+    /// Defaults to [`players::DEFAULT_PLAYER_PROFILE_TTL`] and
+    /// [`players::DEFAULT_PLAYER_PROFILE_CACHE_CAPACITY`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::ClientBuilder;
+    /// # use std::time::Duration;
+    /// let client = ClientBuilder::new()
+    ///     .set_player_profile_cache(Duration::from_secs(300), 100)
+    ///     .build();
+    /// ```
+    pub fn set_player_profile_cache(mut self, ttl: Duration, capacity: usize) -> Self {
+        self.player_profile_cache = Some((ttl, capacity));
+        self
+    }
+
+    /// Limits the client to `requests_per_minute` requests over any 60 seconds, across
+    /// all endpoints. Requests over the limit are not sent and return
+    /// [`RoliError::TooManyRequests`]. `None` (the default) means no limit.
+    ///
+    /// Clones of the client share the limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::ClientBuilder;
+    /// let client = ClientBuilder::new().set_requests_per_minute(Some(60)).build();
+    /// ```
+    pub fn set_requests_per_minute(mut self, requests_per_minute: Option<u32>) -> Self {
+        self.requests_per_minute = requests_per_minute;
+        self
+    }
+
+    /// Limits the client to posting `trade_ads_per_day` trade ads over any 24 hours with
+    /// [`Client::create_trade_ad`]. Trade ads over the limit are not sent and return
+    /// [`RoliError::CooldownNotExpired`]. `None` (the default) means no limit other than
+    /// the one Rolimons enforces ([`limits::TRADE_ADS_PER_DAY`]).
+    ///
+    /// Every trade ad that is sent counts towards the limit, even if Rolimons rejects it.
+    /// Clones of the client share the limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::ClientBuilder;
+    /// let client = ClientBuilder::new().set_trade_ads_per_day(Some(20)).build();
+    /// ```
+    pub fn set_trade_ads_per_day(mut self, trade_ads_per_day: Option<u32>) -> Self {
+        self.trade_ads_per_day = trade_ads_per_day;
+        self
+    }
+
+    /// Records every request the client makes in `audit_log`, which can be read
+    /// back with [`Client::audit_log`].
+    ///
+    /// Only available with the `audit` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roli::ClientBuilder;
+    /// use roli::audit::AuditLog;
+    ///
+    /// let client = ClientBuilder::new()
+    ///     .set_audit_log(AuditLog::new(100, 4096))
+    ///     .build();
+    /// assert!(client.audit_log().unwrap().is_empty());
+    /// ```
+    #[cfg(feature = "audit")]
+    pub fn set_audit_log(mut self, audit_log: audit::AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Answers requests with the responses recorded in `entries` instead of sending
+    /// them to Rolimons.
+    ///
+    /// Each request is answered with the next unused response recorded for the same
+    /// method and url, in the order they were recorded. Requests without a recorded
+    /// response return [`RoliError::UnidentifiedStatusCode`] with a status code of 404.
+    /// Entries without a response, or with a truncated body, can not be replayed
+    /// faithfully.
+    ///
+    /// Only available with the `audit` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use roli::audit::AuditLog;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let json_lines = std::fs::read_to_string("roli-audit.jsonl")?;
+    /// let client = roli::ClientBuilder::new()
+    ///     .set_replay(AuditLog::parse_json_lines(&json_lines)?)
+    ///     .build();
+    ///
+    /// // Parsed from the recorded response, without contacting Rolimons.
+    /// let sales = client.recent_sales().await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "audit")]
+    pub fn set_replay(mut self, entries: Vec<audit::AuditEntry>) -> Self {
+        self.replay = Some(entries);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_serialization() {
+        let event = Event::from(deals::Activity::RapUpdate(deals::RapUpdate {
+            timestamp: 1,
+            item_id: 2,
+            rap: 3,
+        }));
+
+        assert_eq!(event.kind(), "rap_update");
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], event.kind());
+        assert_eq!(json["data"]["rap"], 3);
+
+        assert_eq!(serde_json::from_value::<Event>(json).unwrap(), event);
+    }
+
+    #[test]
+    fn test_lenient_negative_clamps_to_zero() {
+        for code in [
+            Code::Integer(-1),
+            Code::Integer(i64::MIN),
+            Code::Float(-2.5),
+        ] {
+            let x = code.to_u64_lenient();
+
+            if cfg!(feature = "strict-parsing") {
+                assert!(matches!(x, Err(RoliError::MalformedResponse)));
+            } else {
+                assert_eq!(x.unwrap(), 0);
+            }
+        }
+
+        assert_eq!(Code::Integer(7).to_u64_lenient().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_code_coercion() {
+        assert_eq!(Code::String("1234".to_string()).to_u64().unwrap(), 1234);
+        assert!(Code::String("abc".to_string()).to_i64().is_err());
+        assert!(Code::Float(f64::NAN).to_i64().is_err());
+        assert!(Code::Integer(-5).to_u64().is_err());
+
+        let negative = Code::Integer(-5).to_u64_lenient();
+
+        if cfg!(feature = "strict-parsing") {
+            assert!(negative.is_err());
+        } else {
+            assert_eq!(negative.unwrap(), 0);
+        }
+
+        let codes = serde_json::from_str::<Vec<Code>>(r#"["1,234", 99.6, " 5_000 "]"#).unwrap();
+        let numbers = codes.iter().map(Code::to_i64).collect::<Vec<_>>();
+
+        if cfg!(feature = "strict-parsing") {
+            assert!(numbers.iter().all(Result::is_err));
+        } else {
+            assert_eq!(
+                numbers.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+                vec![1234, 100, 5000]
+            );
+        }
+    }
+
+    #[test]
+    fn test_error_classification() {
+        assert!(RoliError::TooManyRequests.is_retryable());
+        assert!(RoliError::TooManyRequests.is_rate_limited());
+        assert!(RoliError::UnidentifiedStatusCode(503).is_retryable());
+        assert!(!RoliError::UnidentifiedStatusCode(404).is_retryable());
+        assert!(!RoliError::MalformedResponse.is_retryable());
+        assert!(RoliError::UnexpectedHtmlResponse.is_maintenance());
+        assert!(RoliError::UnidentifiedStatusCode(503).is_maintenance());
+        assert!(!RoliError::InternalServerError.is_maintenance());
+
+        assert!(RoliError::RoliVerificationNotSet.is_auth_error());
+        assert!(!RoliError::RoliVerificationInvalidOrExpired.is_retryable());
+        assert!(!RoliError::CooldownNotExpired.is_auth_error());
+    }
+
+    #[test]
+    fn test_try_build_connection_options() {
+        assert!(ClientBuilder::new()
+            .set_pool_idle_timeout(None)
+            .set_pool_max_idle_per_host(2)
+            .set_http2(false)
+            .try_build()
+            .is_ok());
+
+        let result = ClientBuilder::new()
+            .set_reqwest_client(reqwest::Client::new())
+            .set_tcp_keepalive(Duration::from_secs(60))
+            .try_build();
+
+        assert!(matches!(result, Err(RoliError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_header_profile() {
+        assert_eq!(HeaderProfile::default().user_agent(), USER_AGENT);
+
+        let client = ClientBuilder::new()
+            .set_header_profile(HeaderProfile::DeclaredBot {
+                name: "TradeBot".to_string(),
+                contact: "bot@example.com".to_string(),
+            })
+            .build();
+        assert!(client
+            .user_agent()
+            .to_str()
+            .unwrap()
+            .starts_with("TradeBot (+bot@example.com) roli/"));
+
+        let result = ClientBuilder::new()
+            .set_header_profile(HeaderProfile::DeclaredBot {
+                name: "Trade\nBot".to_string(),
+                contact: String::new(),
+            })
+            .try_build();
+        assert!(matches!(result, Err(RoliError::InvalidConfig(_))));
+    }
 }
@@ -0,0 +1,112 @@
+use crate::{Endpoint, RoliError};
+use std::time::Duration;
+
+/// The time that has to pass between two trade ads posted by the same account.
+///
+/// Posting sooner returns [`RoliError::CooldownNotExpired`](crate::RoliError::CooldownNotExpired).
+pub const TRADE_AD_COOLDOWN: Duration = Duration::from_secs(15 * 60);
+
+/// The maximum amount of trade ads an account can post per 24 hours.
+pub const TRADE_ADS_PER_DAY: u32 = 55;
+
+/// The maximum amount of items that can be offered in a trade ad.
+pub const TRADE_AD_MAX_OFFER_ITEMS: usize = 4;
+
+/// The maximum amount of requested items and request tags (combined) in a trade ad.
+pub const TRADE_AD_MAX_REQUEST_SLOTS: usize = 4;
+
+/// The percentage of a limited's sale price Roblox keeps as marketplace tax. The
+/// seller receives the rest.
+pub const MARKETPLACE_TAX_PERCENT: u64 = 30;
+
+/// How long Rolimons caches the item details endpoint for, in seconds. Fetching
+/// [`Client::all_item_details`](crate::Client::all_item_details) more often than
+/// this returns the same data.
+pub const ITEMDETAILS_CACHE_SECONDS: u64 = 60;
+
+/// How far back [`Client::recent_trade_ads`](crate::Client::recent_trade_ads) goes.
+pub const RECENT_TRADE_ADS_WINDOW: Duration = Duration::from_secs(3 * 60);
+
+/// The minimum interval allowed between two [`Client::player_profile`](crate::Client::player_profile)
+/// calls for the same player inside of a stream or
+/// [`Client::player_profile_cached`](crate::Client::player_profile_cached).
+///
+/// The player profile endpoint is very intensive on Rolimons' servers, so any
+/// interval below this is raised to this value.
+pub const MIN_PLAYER_PROFILE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The minimum interval allowed between two polls of an endpoint that has no floor of
+/// its own (see [`min_poll_interval`]), so that a zero interval does not poll in a
+/// tight loop.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The minimum interval allowed between two polls of
+/// [`Client::all_item_details`](crate::Client::all_item_details).
+///
+/// Rolimons caches the endpoint for [`ITEMDETAILS_CACHE_SECONDS`], so polling it more
+/// often only adds load (and risks a ban) without returning newer data.
+pub const MIN_ALL_ITEM_DETAILS_INTERVAL: Duration = Duration::from_secs(ITEMDETAILS_CACHE_SECONDS);
+
+/// The suggested interval for polling [`Client::all_item_details`](crate::Client::all_item_details).
+pub const ALL_ITEM_DETAILS_POLL_INTERVAL: Duration = Duration::from_secs(ITEMDETAILS_CACHE_SECONDS);
+
+/// The suggested interval for polling [`Client::deals_activity`](crate::Client::deals_activity).
+pub const DEALS_ACTIVITY_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The suggested interval for polling [`Client::recent_sales`](crate::Client::recent_sales).
+pub const RECENT_SALES_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The suggested interval for polling [`Client::recent_trade_ads`](crate::Client::recent_trade_ads).
+pub const RECENT_TRADE_ADS_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The suggested interval for polling [`Client::player_profile`](crate::Client::player_profile)
+/// for a single player.
+pub const PLAYER_PROFILE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The interval streams poll at while Rolimons is down for maintenance (see
+/// [`RoliError::is_maintenance`]), instead of their usual interval.
+pub const MAINTENANCE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Returns the hard floor for how often `endpoint` can be polled.
+///
+/// These are [`MIN_ALL_ITEM_DETAILS_INTERVAL`] for
+/// [`Client::all_item_details`](crate::Client::all_item_details),
+/// [`MIN_PLAYER_PROFILE_INTERVAL`] for [`Client::player_profile`](crate::Client::player_profile)
+/// (per player), and [`MIN_POLL_INTERVAL`] for every other endpoint.
+pub fn min_poll_interval(endpoint: Endpoint) -> Duration {
+    match endpoint {
+        Endpoint::AllItemDetails => MIN_ALL_ITEM_DETAILS_INTERVAL,
+        Endpoint::PlayerProfile => MIN_PLAYER_PROFILE_INTERVAL,
+        _ => MIN_POLL_INTERVAL,
+    }
+}
+
+/// Checks that `interval` is not below the [`min_poll_interval`] of `endpoint`.
+///
+/// Returns [`RoliError::InvalidConfig`] naming the endpoint and its floor otherwise.
+///
+/// # Example
+/// ```
+/// use roli::{limits, Endpoint, RoliError};
+/// use std::time::Duration;
+///
+/// assert!(limits::check_poll_interval(Endpoint::AllItemDetails, Duration::from_secs(60)).is_ok());
+/// assert!(matches!(
+///     limits::check_poll_interval(Endpoint::AllItemDetails, Duration::from_secs(10)),
+///     Err(RoliError::InvalidConfig(_))
+/// ));
+/// ```
+pub fn check_poll_interval(endpoint: Endpoint, interval: Duration) -> Result<(), RoliError> {
+    let floor = min_poll_interval(endpoint);
+
+    if interval < floor {
+        return Err(RoliError::InvalidConfig(format!(
+            "{} can not be polled more than once every {} seconds, but the interval is {:?}",
+            endpoint,
+            floor.as_secs(),
+            interval
+        )));
+    }
+
+    Ok(())
+}
@@ -1,11 +1,15 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
-use crate::{Client, Code, RoliError};
+use crate::items::ItemDetails;
+use crate::{Client, Code, Endpoint, RoliError};
 use reqwest::header;
 
 const MARKET_ACTIVITY_URL: &str = "https://www.rolimons.com/api/activity";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct RecentSalesResponse {
     success: bool,
     activities: Vec<Vec<Code>>,
@@ -27,9 +31,136 @@ pub struct Sale {
     pub sale_id: u64,
     /// The unix timestamp of the sale.
     /// This is likely when the sale was detected by Rolimons.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
     pub timestamp: u64,
 }
 
+/// A [`Sale`] paired with the Rolimons details of the item that was sold.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct EnrichedSale {
+    /// The sale itself.
+    pub sale: Sale,
+    /// The details of the item that was sold.
+    pub item: ItemDetails,
+    /// When the item's value last changed, if known. Stale values can make a sale
+    /// look like a better deal than it is.
+    ///
+    /// Rolimons does not include this in its api, so it is `None` unless set from
+    /// [`items::value_updated_at`](crate::items::value_updated_at).
+    #[cfg_attr(
+        feature = "rfc3339",
+        serde(with = "crate::serde_helpers::rfc3339_option")
+    )]
+    pub value_updated_at: Option<u64>,
+}
+
+/// The sales of one item in one bucket of time, as returned by [`aggregate`].
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct SaleBucket {
+    /// The unix timestamp the bucket starts at, inclusive.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
+    pub start: u64,
+    /// The id of the item.
+    pub item_id: u64,
+    /// The amount of sales of the item in the bucket.
+    pub count: u64,
+    /// The sum of the sale prices of the item in the bucket.
+    pub volume: u64,
+    /// The average sale price of the item in the bucket.
+    pub average_price: f64,
+    /// The lowest sale price of the item in the bucket.
+    pub min_price: u64,
+    /// The highest sale price of the item in the bucket.
+    pub max_price: u64,
+}
+
+impl EnrichedSale {
+    /// Pairs a sale with the details of the item that was sold.
+    pub fn new(sale: Sale, item: ItemDetails) -> Self {
+        Self {
+            sale,
+            item,
+            value_updated_at: None,
+        }
+    }
+
+    /// The percentage below the item's value (or rap if unvalued) the item was sold at.
+    ///
+    /// Negative if the item sold above its value. Returns 0 if the item has no value or rap.
+    pub fn discount_percent(&self) -> f64 {
+        discount_percent(self.sale.sale_price, self.item.value_or_rap())
+    }
+}
+
+/// Groups `sales` into buckets of `bucket` length (such as an hour or a day) per item,
+/// so that charts can be drawn from pre-binned data.
+///
+/// Buckets are aligned to the unix epoch, so hourly buckets start on the hour and daily
+/// buckets start at midnight UTC. Only buckets with at least one sale are returned,
+/// sorted by start and then by item id. A `bucket` shorter than a second is treated
+/// as one second.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::market_activity;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let sales = client.recent_sales().await?;
+///
+/// for bucket in market_activity::aggregate(&sales, Duration::from_secs(3600)) {
+///     println!(
+///         "{} {}: {} sales averaging {:.0}",
+///         bucket.start, bucket.item_id, bucket.count, bucket.average_price
+///     );
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn aggregate(sales: &[Sale], bucket: Duration) -> Vec<SaleBucket> {
+    let bucket_seconds = bucket.as_secs().max(1);
+    let mut buckets = BTreeMap::<(u64, u64), SaleBucket>::new();
+
+    for sale in sales {
+        let start = sale.timestamp - sale.timestamp % bucket_seconds;
+
+        let bucket = buckets.entry((start, sale.item_id)).or_insert(SaleBucket {
+            start,
+            item_id: sale.item_id,
+            min_price: sale.sale_price,
+            max_price: sale.sale_price,
+            ..Default::default()
+        });
+
+        bucket.count += 1;
+        bucket.volume += sale.sale_price;
+        bucket.min_price = bucket.min_price.min(sale.sale_price);
+        bucket.max_price = bucket.max_price.max(sale.sale_price);
+    }
+
+    buckets
+        .into_values()
+        .map(|mut bucket| {
+            bucket.average_price = bucket.volume as f64 / bucket.count as f64;
+            bucket
+        })
+        .collect()
+}
+
+/// Returns the percentage `price` is below `value`. Negative if `price` is above `value`,
+/// and 0 if `value` is 0.
+pub(crate) fn discount_percent(price: u64, value: u64) -> f64 {
+    if value == 0 {
+        return 0.0;
+    }
+
+    (value as f64 - price as f64) / value as f64 * 100.0
+}
+
 impl Sale {
     fn from_raw(codes: Vec<Code>) -> Result<Self, RoliError> {
         // Follows form of
@@ -79,6 +210,10 @@ impl Client {
     ///
     /// On the Rolimons deals page, this api is polled roughly every 3 seconds.
     ///
+    /// The api takes no count or paging parameters and only returns the latest sales
+    /// Rolimons has recorded, so sales can be missed if it is polled too slowly. The
+    /// sales are sorted newest first (by timestamp, then by sale id).
+    ///
     /// Does not require authentication.
     ///
     /// # Example
@@ -94,48 +229,48 @@ impl Client {
     /// # }
     /// ```
     pub async fn recent_sales(&self) -> Result<Vec<Sale>, RoliError> {
-        let request_result = self
+        let request = self
             .reqwest_client
             .get(MARKET_ACTIVITY_URL)
-            .header(header::USER_AGENT, crate::USER_AGENT)
-            .send()
-            .await;
-
-        match request_result {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                match status_code {
-                    200 => {
-                        let raw = match response.json::<RecentSalesResponse>().await {
-                            Ok(raw) => raw,
-                            Err(_) => return Err(RoliError::MalformedResponse),
-                        };
-
-                        if !raw.success {
-                            return Err(RoliError::RequestReturnedUnsuccessful);
-                        }
-
-                        let mut sales = Vec::new();
-
-                        for activity in raw.activities {
-                            let sale = Sale::from_raw(activity)?;
-                            sales.push(sale);
-                        }
-
-                        Ok(sales)
-                    }
-                    429 => Err(RoliError::TooManyRequests),
-                    500 => Err(RoliError::InternalServerError),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
-            Err(e) => Err(RoliError::ReqwestError(e)),
+            .header(header::USER_AGENT, self.user_agent());
+
+        let raw = self
+            .request_json::<RecentSalesResponse>(Endpoint::RecentSales, request)
+            .await?;
+
+        if !raw.success {
+            return Err(RoliError::RequestReturnedUnsuccessful);
         }
+
+        let mut sales = Vec::new();
+
+        for activity in raw.activities {
+            let sale = Sale::from_raw(activity)?;
+            sales.push(sale);
+        }
+
+        sort_newest_first(&mut sales);
+
+        Ok(sales)
+    }
+
+    /// The same as [`Client::recent_sales`], but only returns the newest `limit` sales.
+    ///
+    /// The api has no count parameter, so the full response is still fetched and is
+    /// cut down locally.
+    pub async fn recent_sales_with_limit(&self, limit: usize) -> Result<Vec<Sale>, RoliError> {
+        let mut sales = self.recent_sales().await?;
+        sales.truncate(limit);
+
+        Ok(sales)
     }
 }
 
-fn calculate_sale_price(old_rap: u64, new_rap: u64) -> u64 {
+fn sort_newest_first(sales: &mut [Sale]) {
+    sales.sort_by_key(|x| std::cmp::Reverse((x.timestamp, x.sale_id)));
+}
+
+pub(crate) fn calculate_sale_price(old_rap: u64, new_rap: u64) -> u64 {
     // Formula from https://devforum.roblox.com/t/rap-change-calculator/1971776
     // I can do basic algebra!
 
@@ -154,6 +289,71 @@ fn calculate_sale_price(old_rap: u64, new_rap: u64) -> u64 {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_enriched_sale_discount_percent() {
+        let sale = Sale {
+            sale_price: 750,
+            ..Default::default()
+        };
+
+        let item = ItemDetails {
+            rap: 900,
+            valued: true,
+            value: 1000,
+            ..Default::default()
+        };
+
+        let enriched = EnrichedSale::new(sale, item);
+        assert_eq!(enriched.discount_percent(), 25.0);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let sale = |item_id, sale_price, timestamp| Sale {
+            item_id,
+            sale_price,
+            timestamp,
+            ..Default::default()
+        };
+
+        let sales = vec![
+            sale(2, 100, 3700),
+            sale(1, 300, 3599),
+            sale(1, 100, 3600),
+            sale(1, 200, 7199),
+        ];
+
+        let buckets = aggregate(&sales, Duration::from_secs(3600));
+
+        assert_eq!(
+            buckets
+                .iter()
+                .map(|x| (x.start, x.item_id, x.count))
+                .collect::<Vec<_>>(),
+            vec![(0, 1, 1), (3600, 1, 2), (3600, 2, 1)]
+        );
+        assert_eq!(buckets[1].volume, 300);
+        assert_eq!(buckets[1].average_price, 150.0);
+        assert_eq!((buckets[1].min_price, buckets[1].max_price), (100, 200));
+    }
+
+    #[test]
+    fn test_sort_newest_first() {
+        let sale = |sale_id, timestamp| Sale {
+            sale_id,
+            timestamp,
+            ..Default::default()
+        };
+
+        let mut sales = vec![sale(1, 100), sale(3, 200), sale(2, 200)];
+        sort_newest_first(&mut sales);
+
+        assert_eq!(
+            sales.iter().map(|x| x.sale_id).collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+    }
+
     #[test]
     fn test_calculate_sale_price() {
         let old_rap = 4272;
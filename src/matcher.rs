@@ -0,0 +1,180 @@
+use crate::items::ItemInfoProvider;
+use serde::{Deserialize, Serialize};
+
+/// The totals of one side of a trade, as computed by [`score_trade`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SideTotals {
+    /// The sum of [`ItemDetails::value_or_rap`](crate::items::ItemDetails::value_or_rap)
+    /// for every known item.
+    pub value: u64,
+    /// The sum of the rap of every known item.
+    pub rap: u64,
+    /// The sum of every known item's value (or rap if unvalued) multiplied by
+    /// [`Demand::multiplier`](crate::items::Demand::multiplier).
+    pub demand_weighted_value: f64,
+    /// The ids of items on this side that are marked as projected.
+    pub projected_items: Vec<u64>,
+    /// The ids of items on this side that were not found in the table. These are
+    /// not counted in any of the totals.
+    pub unknown_items: Vec<u64>,
+}
+
+/// A structured report of how fair a trade is, produced by [`score_trade`].
+///
+/// All deltas are `offer - request`, so a positive delta means the offer side
+/// is worth more than the request side.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FairnessReport {
+    /// The totals of the offered items.
+    pub offer: SideTotals,
+    /// The totals of the requested items.
+    pub request: SideTotals,
+    /// The difference in value (or rap for unvalued items) between the two sides.
+    pub value_delta: i64,
+    /// The difference in rap between the two sides.
+    pub rap_delta: i64,
+    /// The difference in demand weighted value between the two sides.
+    pub demand_weighted_delta: f64,
+}
+
+impl FairnessReport {
+    /// Returns whether either side of the trade contains a projected item.
+    pub fn has_projected_risk(&self) -> bool {
+        !self.offer.projected_items.is_empty() || !self.request.projected_items.is_empty()
+    }
+
+    /// Returns whether either side of the trade contains an item that was not in the table.
+    ///
+    /// Reports with unknown items are incomplete and usually should not be acted on.
+    pub fn has_unknown_items(&self) -> bool {
+        !self.offer.unknown_items.is_empty() || !self.request.unknown_items.is_empty()
+    }
+
+    /// Returns the value delta as a percentage of the request side's value.
+    ///
+    /// Returns `None` if the request side has no value.
+    pub fn value_delta_percent(&self) -> Option<f64> {
+        if self.request.value == 0 {
+            return None;
+        }
+
+        Some(self.value_delta as f64 / self.request.value as f64 * 100.0)
+    }
+}
+
+/// Scores how fair a trade of `offer_items` for `request_items` is, using the
+/// item details in `table`.
+///
+/// Item ids may repeat on a side (e.g. when trading two copies of an item), and
+/// are counted once per occurrence.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::ItemDetailsTable;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+///
+/// let report = roli::matcher::score_trade(&[1365767, 1365767], &[1028606], &table);
+///
+/// if !report.has_projected_risk() && report.value_delta > 0 {
+///     println!("Accepting, we gain {} value", report.value_delta);
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn score_trade(
+    offer_items: &[u64],
+    request_items: &[u64],
+    table: &impl ItemInfoProvider,
+) -> FairnessReport {
+    let offer = side_totals(offer_items, table);
+    let request = side_totals(request_items, table);
+
+    FairnessReport {
+        value_delta: offer.value as i64 - request.value as i64,
+        rap_delta: offer.rap as i64 - request.rap as i64,
+        demand_weighted_delta: offer.demand_weighted_value - request.demand_weighted_value,
+        offer,
+        request,
+    }
+}
+
+fn side_totals(item_ids: &[u64], table: &impl ItemInfoProvider) -> SideTotals {
+    let mut totals = SideTotals::default();
+
+    for item_id in item_ids.iter().copied() {
+        let item = match table.item_details(item_id) {
+            Some(x) => x,
+            None => {
+                totals.unknown_items.push(item_id);
+                continue;
+            }
+        };
+
+        totals.value += item.value_or_rap();
+        totals.rap += item.rap;
+        totals.demand_weighted_value += item.value_or_rap() as f64 * item.demand.multiplier();
+
+        if item.projected {
+            totals.projected_items.push(item_id);
+        }
+    }
+
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::{Demand, ItemDetails, ItemDetailsTable};
+
+    fn table() -> ItemDetailsTable {
+        ItemDetailsTable::new(vec![
+            ItemDetails {
+                item_id: 1,
+                rap: 900,
+                valued: true,
+                value: 1000,
+                demand: Demand::High,
+                ..Default::default()
+            },
+            ItemDetails {
+                item_id: 2,
+                rap: 1500,
+                demand: Demand::Low,
+                projected: true,
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn test_score_trade() {
+        let report = score_trade(&[1, 1], &[2], &table());
+
+        assert_eq!(report.offer.value, 2000);
+        assert_eq!(report.offer.rap, 1800);
+        assert_eq!(report.request.value, 1500);
+        assert_eq!(report.value_delta, 500);
+        assert_eq!(report.rap_delta, 300);
+        assert!((report.demand_weighted_delta - (2400.0 - 1200.0)).abs() < 1e-9);
+        assert_eq!(report.request.projected_items, vec![2]);
+        assert!(report.has_projected_risk());
+        assert!(!report.has_unknown_items());
+    }
+
+    #[test]
+    fn test_score_trade_unknown_items() {
+        let report = score_trade(&[1, 3], &[], &table());
+
+        assert_eq!(report.offer.value, 1000);
+        assert_eq!(report.offer.unknown_items, vec![3]);
+        assert!(report.has_unknown_items());
+        assert_eq!(report.value_delta_percent(), None);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{Endpoint, RoliError};
+use std::time::Duration;
+
+/// A counter of requests made, labeled by `endpoint`.
+pub const REQUESTS_TOTAL: &str = "roli_requests_total";
+/// A counter of requests that returned an error, labeled by `endpoint` and `error`.
+pub const REQUEST_ERRORS_TOTAL: &str = "roli_request_errors_total";
+/// A histogram of request latencies in seconds, labeled by `endpoint`.
+pub const REQUEST_DURATION_SECONDS: &str = "roli_request_duration_seconds";
+/// A counter of items emitted by streams, labeled by `stream`.
+pub const STREAM_EVENTS_TOTAL: &str = "roli_stream_events_total";
+/// A counter of items dropped by [`streams::buffered`](crate::streams::buffered) because
+/// the buffer was full.
+pub const STREAM_DROPPED_TOTAL: &str = "roli_stream_dropped_total";
+/// A counter of feeds restarted by a [`Supervisor`](crate::streams::supervisor::Supervisor),
+/// labeled by `feed`.
+pub const STREAM_RESTARTS_TOTAL: &str = "roli_stream_restarts_total";
+
+/// Installs a global Prometheus recorder and returns a handle to it.
+///
+/// Call [`PrometheusHandle::render`](metrics_exporter_prometheus::PrometheusHandle::render)
+/// from your own http server (e.g. on `/metrics`) to expose the metrics.
+///
+/// Returns `None` if a global recorder was already installed.
+///
+/// # Example
+/// ```no_run
+/// let handle = roli::metrics::install_prometheus_recorder().unwrap();
+///
+/// // ...make some requests...
+///
+/// println!("{}", handle.render());
+/// ```
+#[cfg(feature = "prometheus")]
+pub fn install_prometheus_recorder() -> Option<metrics_exporter_prometheus::PrometheusHandle> {
+    metrics_exporter_prometheus::PrometheusBuilder::new()
+        .install_recorder()
+        .ok()
+}
+
+/// Records a finished request. Does nothing if the `metrics` feature is disabled.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_request(endpoint: Endpoint, duration: Duration, error: Option<&RoliError>) {
+    #[cfg(feature = "metrics")]
+    {
+        let endpoint = endpoint.name();
+
+        ::metrics::counter!(REQUESTS_TOTAL, "endpoint" => endpoint).increment(1);
+        ::metrics::histogram!(REQUEST_DURATION_SECONDS, "endpoint" => endpoint)
+            .record(duration.as_secs_f64());
+
+        if let Some(error) = error {
+            ::metrics::counter!(
+                REQUEST_ERRORS_TOTAL,
+                "endpoint" => endpoint,
+                "error" => error_label(error)
+            )
+            .increment(1);
+        }
+    }
+}
+
+/// Records an item emitted by a stream. Does nothing if the `metrics` feature is disabled.
+#[cfg(feature = "async-rt")]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_stream_event(stream: &'static str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(STREAM_EVENTS_TOTAL, "stream" => stream).increment(1);
+}
+
+/// Records a feed restarted by a supervisor. Does nothing if the `metrics` feature is
+/// disabled.
+#[cfg(feature = "async-rt")]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_stream_restart(feed: &str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(STREAM_RESTARTS_TOTAL, "feed" => feed.to_string()).increment(1);
+}
+
+/// Records an item dropped by a full buffer. Does nothing if the `metrics` feature is
+/// disabled.
+#[cfg(feature = "async-rt")]
+pub(crate) fn record_stream_dropped() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(STREAM_DROPPED_TOTAL).increment(1);
+}
+
+#[cfg(feature = "metrics")]
+fn error_label(error: &RoliError) -> &'static str {
+    match error {
+        RoliError::RequestReturnedUnsuccessful => "request_returned_unsuccessful",
+        RoliError::TooManyRequests => "too_many_requests",
+        RoliError::InternalServerError => "internal_server_error",
+        RoliError::MalformedResponse => "malformed_response",
+        RoliError::UnexpectedHtmlResponse => "unexpected_html_response",
+        RoliError::RoliVerificationContainsInvalidCharacters => {
+            "roli_verification_contains_invalid_characters"
+        }
+        RoliError::RoliVerificationInvalidOrExpired => "roli_verification_invalid_or_expired",
+        RoliError::RoliVerificationNotSet => "roli_verification_not_set",
+        RoliError::CooldownNotExpired => "cooldown_not_expired",
+        RoliError::TradeAdViolation(_) => "trade_ad_violation",
+        RoliError::UnidentifiedStatusCode(_) => "unidentified_status_code",
+        #[cfg(feature = "async-rt")]
+        RoliError::DeadlineExceeded => "deadline_exceeded",
+        RoliError::ReqwestError(_) => "reqwest_error",
+        RoliError::CoalescedRequestFailed(_) => "coalesced_request_failed",
+        _ => "other",
+    }
+}
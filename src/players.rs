@@ -1,12 +1,31 @@
-use crate::{Client, Code, RoliError};
+use crate::cdn::{self, HeadshotSize};
+use crate::items::ItemInfoProvider;
+use crate::{Client, Code, Endpoint, RoliError};
 use reqwest::header;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Contains metrics computed from a player's inventory, such as how concentrated its value is.
+pub mod analytics;
 
 const PLAYER_SEARCH_API: &str = "https://www.rolimons.com/api/playersearch";
 const PLAYER_API: &str = "https://www.rolimons.com/api/playerassets/";
 
+pub use crate::limits::MIN_PLAYER_PROFILE_INTERVAL;
+
+/// The default time a profile is cached for by [`Client::player_profile_cached`].
+pub const DEFAULT_PLAYER_PROFILE_TTL: Duration = crate::limits::PLAYER_PROFILE_POLL_INTERVAL;
+
+/// The default amount of profiles cached by [`Client::player_profile_cached`].
+pub const DEFAULT_PLAYER_PROFILE_CACHE_CAPACITY: usize = 1000;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct PlayerSearchResponse {
     success: bool,
     result_count: i64,
@@ -25,6 +44,7 @@ pub struct PlayerSearchResult {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct PlayerProfileResponse {
     success: bool,
     #[serde(rename = "playerTerminated")]
@@ -73,14 +93,24 @@ pub struct PlayerProfile {
     /// Whether the player is currently online.
     pub is_online: bool,
     /// The unix timestamp of the player's last online status.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
     pub last_online: u64,
     /// Whether the player has premium
     pub premium: bool,
+    /// Whether the player has verified their account on Rolimons.
+    pub verified: bool,
     /// The type of presence the player has (e.g. Unavailable, Website, InGame).
     pub presence_type: PresenceType,
+    /// The game the player is in, if they are [`PresenceType::InGame`] and Rolimons
+    /// could see which game it is (the player's location tracking has to be on).
+    pub join_context: Option<GameLocation>,
     /// The player's badges and the unix timestamp of when they were earned.
+    ///
+    /// Sorted by when they were earned, oldest first.
     pub badges: Vec<Badge>,
     /// The player's inventory. Each player asset includes item ids, as well as uaids owned.
+    ///
+    /// Sorted by item id, with at most one asset per item.
     pub inventory: Vec<PlayerAsset>,
 }
 
@@ -90,9 +120,42 @@ pub struct Badge {
     /// The name of the badge.
     pub name: String,
     /// The unix timestamp of when the badge was earned.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
     pub timestamp_earned: u64,
 }
 
+/// The Rolimons badges this crate knows about, with a description and category for each.
+///
+/// Badges are matched against [`Badge::name`] with [`RoliBadge::from_name`]. Badges
+/// Rolimons adds later are still kept in [`PlayerProfile::badges`], but have no
+/// [`RoliBadge`] until one is added here.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RoliBadge {
+    RoliVerified,
+    RoliDonor,
+    RoliAwardWinner,
+    Value1M,
+    Value5M,
+    Value10M,
+    CreateTenTradeAds,
+    CreateHundredTradeAds,
+    CreateThousandTradeAds,
+}
+
+/// The kind of achievement a [`RoliBadge`] is awarded for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum BadgeCategory {
+    /// Awarded for proving ownership of the account on Rolimons.
+    Trust,
+    /// Awarded for supporting or being recognized by Rolimons.
+    Community,
+    /// Awarded for reaching an inventory value.
+    Value,
+    /// Awarded for using the trade ads page.
+    Trading,
+}
+
 /// The type of presence the player has on Roblox (e.g. InGame, Website).
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Copy)]
 pub enum PresenceType {
@@ -106,15 +169,357 @@ pub enum PresenceType {
     InStudio,
 }
 
+/// The game a player is in, as shown on their Rolimons profile.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct GameLocation {
+    /// The place id of the game.
+    pub place_id: u64,
+    /// The name of the game.
+    pub name: String,
+    /// The url of the game's icon, if Rolimons has one.
+    pub icon_url: Option<String>,
+    /// Whether the game is tracked on the Rolimons games page.
+    pub tracked: bool,
+}
+
 /// Contains the item id and the uaids (unique asset ids) of all the copies the user owns.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
 pub struct PlayerAsset {
     /// The item id of the asset.
     pub item_id: u64,
     /// The unique asset ids of all the copies the user owns.
+    ///
+    /// Sorted ascending and deduplicated.
     pub uaids: Vec<u64>,
 }
 
+impl PlayerSearchResult {
+    /// Returns the Roblox thumbnails api url for the player's avatar headshot (see
+    /// [`cdn::avatar_headshot_url`]).
+    pub fn avatar_url(&self, size: HeadshotSize) -> String {
+        cdn::avatar_headshot_url(self.user_id, size)
+    }
+}
+
+impl PlayerProfile {
+    /// Returns the Roblox thumbnails api url for the player's avatar headshot (see
+    /// [`cdn::avatar_headshot_url`]).
+    pub fn avatar_url(&self, size: HeadshotSize) -> String {
+        cdn::avatar_headshot_url(self.user_id, size)
+    }
+
+    /// Returns the total amount of copies the player owns.
+    pub fn uaid_count(&self) -> usize {
+        self.inventory.iter().map(|asset| asset.uaids.len()).sum()
+    }
+
+    /// Returns the amount of distinct items the player owns at least one copy of.
+    pub fn unique_item_count(&self) -> usize {
+        self.inventory.len()
+    }
+
+    /// Returns the total value of the player's inventory, counting each copy at the
+    /// item's [`value_or_rap`](crate::items::ItemDetails::value_or_rap).
+    ///
+    /// Items that are not in `table` are not counted.
+    pub fn inventory_value(&self, table: &impl ItemInfoProvider) -> u64 {
+        self.inventory
+            .iter()
+            .filter_map(|asset| {
+                let item = table.item_details(asset.item_id)?;
+                Some(item.value_or_rap() * asset.uaids.len() as u64)
+            })
+            .sum()
+    }
+
+    /// Returns the player's copies of an item, if they own any.
+    ///
+    /// This is a binary search, relying on [`PlayerProfile::inventory`] being sorted
+    /// by item id.
+    pub fn asset(&self, item_id: u64) -> Option<&PlayerAsset> {
+        self.inventory
+            .binary_search_by_key(&item_id, |asset| asset.item_id)
+            .ok()
+            .map(|i| &self.inventory[i])
+    }
+
+    /// Returns whether the player has earned `badge`.
+    pub fn has_badge(&self, badge: RoliBadge) -> bool {
+        self.badges.iter().any(|x| x.name == badge.name())
+    }
+
+    /// Returns the least amount of trade ads the player has created, going by their
+    /// trade ad badges (see [`RoliBadge::trade_ads_created`]).
+    ///
+    /// The profile has no exact count, so this is 0 for players with fewer than 10
+    /// ads, and stays at 1,000 once a player passes it.
+    pub fn min_trade_ads_created(&self) -> u64 {
+        self.badges
+            .iter()
+            .filter_map(|badge| badge.kind()?.trade_ads_created())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the player's inventory keyed by item id.
+    ///
+    /// The map is built on every call, so build it once and reuse it when looking up
+    /// many items. For a handful of lookups, [`PlayerProfile::asset`] avoids the allocation.
+    pub fn inventory_map(&self) -> HashMap<u64, &PlayerAsset> {
+        self.inventory
+            .iter()
+            .map(|asset| (asset.item_id, asset))
+            .collect()
+    }
+}
+
+impl RoliBadge {
+    /// Every known badge.
+    pub const ALL: [RoliBadge; 9] = [
+        Self::RoliVerified,
+        Self::RoliDonor,
+        Self::RoliAwardWinner,
+        Self::Value1M,
+        Self::Value5M,
+        Self::Value10M,
+        Self::CreateTenTradeAds,
+        Self::CreateHundredTradeAds,
+        Self::CreateThousandTradeAds,
+    ];
+
+    /// Returns the badge with the name Rolimons uses for it, if it is known.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|badge| badge.name() == name)
+    }
+
+    /// Returns the name Rolimons uses for the badge, as found in [`Badge::name`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RoliVerified => "roli_verified",
+            Self::RoliDonor => "roli_donor",
+            Self::RoliAwardWinner => "roli_award_winner",
+            Self::Value1M => "value_1m",
+            Self::Value5M => "value_5m",
+            Self::Value10M => "value_10m",
+            Self::CreateTenTradeAds => "create_10_trade_ads",
+            Self::CreateHundredTradeAds => "create_100_trade_ads",
+            Self::CreateThousandTradeAds => "create_1000_trade_ads",
+        }
+    }
+
+    /// Returns a short description of what the badge is awarded for.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::RoliVerified => "Verified ownership of their Roblox account on Rolimons",
+            Self::RoliDonor => "Donated to Rolimons",
+            Self::RoliAwardWinner => "Won a Rolimons award",
+            Self::Value1M => "Reached an inventory value of 1,000,000",
+            Self::Value5M => "Reached an inventory value of 5,000,000",
+            Self::Value10M => "Reached an inventory value of 10,000,000",
+            Self::CreateTenTradeAds => "Created 10 trade ads",
+            Self::CreateHundredTradeAds => "Created 100 trade ads",
+            Self::CreateThousandTradeAds => "Created 1,000 trade ads",
+        }
+    }
+
+    /// Returns the kind of achievement the badge is awarded for.
+    pub fn category(&self) -> BadgeCategory {
+        match self {
+            Self::RoliVerified => BadgeCategory::Trust,
+            Self::RoliDonor | Self::RoliAwardWinner => BadgeCategory::Community,
+            Self::Value1M | Self::Value5M | Self::Value10M => BadgeCategory::Value,
+            Self::CreateTenTradeAds
+            | Self::CreateHundredTradeAds
+            | Self::CreateThousandTradeAds => BadgeCategory::Trading,
+        }
+    }
+
+    /// Returns the amount of trade ads the badge is awarded for creating, or `None`
+    /// if it is not a trade ad badge.
+    pub fn trade_ads_created(&self) -> Option<u64> {
+        match self {
+            Self::CreateTenTradeAds => Some(10),
+            Self::CreateHundredTradeAds => Some(100),
+            Self::CreateThousandTradeAds => Some(1000),
+            _ => None,
+        }
+    }
+}
+
+impl Badge {
+    /// Returns the known badge this is, or `None` if this crate does not know it.
+    pub fn kind(&self) -> Option<RoliBadge> {
+        RoliBadge::from_name(&self.name)
+    }
+}
+
+/// Sorts an inventory by item id and the uaids of each asset, merging assets with
+/// the same item id and removing duplicate uaids.
+pub(crate) fn normalize_inventory(inventory: &mut Vec<PlayerAsset>) {
+    inventory.sort_by_key(|asset| asset.item_id);
+
+    inventory.dedup_by(|duplicate, asset| {
+        if duplicate.item_id != asset.item_id {
+            return false;
+        }
+
+        asset.uaids.append(&mut duplicate.uaids);
+        true
+    });
+
+    for asset in inventory.iter_mut() {
+        asset.uaids.sort_unstable();
+        asset.uaids.dedup();
+    }
+}
+
+/// The items and copies two players have in common, as returned by [`overlap`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct OwnershipOverlap {
+    /// The ids of items both players own at least one copy of, sorted ascending.
+    pub common_item_ids: Vec<u64>,
+    /// The uaids that show up in both players' inventories, sorted ascending.
+    ///
+    /// A copy can only have one owner at a time, so when the profiles were taken at
+    /// different times, these are copies that moved between the two players.
+    pub shared_uaids: Vec<u64>,
+}
+
+/// A least recently used cache of player profiles, used by [`Client::player_profile_cached`].
+#[derive(Debug)]
+pub(crate) struct PlayerProfileCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+    /// A lock per player with a fetch in flight, so concurrent misses fetch once.
+    in_flight: Mutex<HashMap<u64, Arc<futures_util::lock::Mutex<()>>>>,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    profiles: HashMap<u64, CacheEntry>,
+    /// Incremented on every access, used to find the least recently used entry.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    profile: PlayerProfile,
+    fetched_at: Instant,
+    last_used: u64,
+}
+
+impl PlayerProfileCache {
+    /// Creates a cache. `ttl` is raised to [`MIN_PLAYER_PROFILE_INTERVAL`] and
+    /// `capacity` to 1 if they are lower.
+    pub(crate) fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl: ttl.max(MIN_PLAYER_PROFILE_INTERVAL),
+            capacity: capacity.max(1),
+            entries: Mutex::new(CacheEntries::default()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached profile of `user_id`, or fetches it with `fetch` and caches it.
+    ///
+    /// Concurrent misses for the same player wait for the first fetch instead of
+    /// starting their own.
+    pub(crate) async fn get_or_fetch<F, Fut>(
+        &self,
+        user_id: u64,
+        fetch: F,
+    ) -> Result<PlayerProfile, RoliError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<PlayerProfile, RoliError>>,
+    {
+        if let Some(profile) = self.get(user_id) {
+            return Ok(profile);
+        }
+
+        let lock = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(user_id)
+            .or_default()
+            .clone();
+
+        let result = {
+            let _guard = lock.lock().await;
+
+            match self.get(user_id) {
+                Some(profile) => Ok(profile),
+                None => fetch().await.inspect(|profile| {
+                    self.insert(user_id, profile.clone());
+                }),
+            }
+        };
+
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Only the map and this call hold the lock, so nobody else is waiting on it.
+        if Arc::strong_count(&lock) == 2 {
+            in_flight.remove(&user_id);
+        }
+
+        result
+    }
+
+    fn get(&self, user_id: u64) -> Option<PlayerProfile> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        let entry = entries.profiles.get_mut(&user_id)?;
+
+        if entry.fetched_at.elapsed() >= self.ttl {
+            return None;
+        }
+
+        entry.last_used = clock;
+
+        Some(entry.profile.clone())
+    }
+
+    fn insert(&self, user_id: u64, profile: PlayerProfile) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        if !entries.profiles.contains_key(&user_id) && entries.profiles.len() >= self.capacity {
+            let least_recently_used = entries
+                .profiles
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(user_id, _)| *user_id);
+
+            if let Some(least_recently_used) = least_recently_used {
+                entries.profiles.remove(&least_recently_used);
+            }
+        }
+
+        entries.profiles.insert(
+            user_id,
+            CacheEntry {
+                profile,
+                fetched_at: Instant::now(),
+                last_used: clock,
+            },
+        );
+    }
+}
+
+impl Default for PlayerProfileCache {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_PLAYER_PROFILE_TTL,
+            DEFAULT_PLAYER_PROFILE_CACHE_CAPACITY,
+        )
+    }
+}
+
 impl PlayerSearchResult {
     /// Converts a vector of [`Code`] into a [`PlayerSearchResult`].
     ///
@@ -131,14 +536,161 @@ impl PlayerSearchResult {
     }
 }
 
+/// Compares two players' inventories, returning the items and copies they have in common.
+///
+/// This is a building block for alt detection and trust tools. To catch copies
+/// that moved between the players over time, use [`overlap_history`] with older
+/// profiles of each player.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// #
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let a = client.player_profile(2207291).await?;
+/// let b = client.player_profile(1).await?;
+///
+/// let overlap = roli::players::overlap(&a, &b);
+/// println!("{} items in common", overlap.common_item_ids.len());
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn overlap(a: &PlayerProfile, b: &PlayerProfile) -> OwnershipOverlap {
+    overlap_history(std::slice::from_ref(a), std::slice::from_ref(b))
+}
+
+/// The same as [`overlap`], but compares every profile of one player against every
+/// profile of the other (e.g. profiles taken at different times).
+///
+/// Any uaid that appears in a profile of each player is reported in
+/// [`OwnershipOverlap::shared_uaids`], even if neither player owns it anymore.
+/// Common items are taken from the latest profile of each player only, which is
+/// the last profile in each slice.
+pub fn overlap_history(a: &[PlayerProfile], b: &[PlayerProfile]) -> OwnershipOverlap {
+    let item_ids = |profile: Option<&PlayerProfile>| {
+        profile
+            .into_iter()
+            .flat_map(|profile| profile.inventory.iter().map(|asset| asset.item_id))
+            .collect::<BTreeSet<_>>()
+    };
+
+    let uaids = |profiles: &[PlayerProfile]| {
+        profiles
+            .iter()
+            .flat_map(|profile| profile.inventory.iter())
+            .flat_map(|asset| asset.uaids.iter().copied())
+            .collect::<BTreeSet<_>>()
+    };
+
+    OwnershipOverlap {
+        common_item_ids: item_ids(a.last())
+            .intersection(&item_ids(b.last()))
+            .copied()
+            .collect(),
+        shared_uaids: uaids(a).intersection(&uaids(b)).copied().collect(),
+    }
+}
+
+/// Writes a player's inventory to `writer` as CSV, one row per copy, with the columns
+/// `Item ID`, `Item Name`, `Value`, `RAP`, and `UAID`.
+///
+/// Value is the item's [`value_or_rap`](crate::items::ItemDetails::value_or_rap).
+/// Items that are not in `table` are still written, with an empty name, value, and rap,
+/// so that every copy is accounted for.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::ItemDetailsTable;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+/// let profile = client.player_profile(2207291).await?;
+///
+/// let file = std::fs::File::create("inventory.csv")?;
+/// roli::players::export_inventory_csv(&profile, &table, file)?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn export_inventory_csv(
+    profile: &PlayerProfile,
+    table: &impl ItemInfoProvider,
+    mut writer: impl Write,
+) -> Result<(), RoliError> {
+    writeln!(writer, "Item ID,Item Name,Value,RAP,UAID").map_err(RoliError::IoError)?;
+
+    for asset in &profile.inventory {
+        let item = table.item_details(asset.item_id);
+
+        let (item_name, value, rap) = match &item {
+            Some(item) => (
+                csv_field(&item.item_name),
+                item.value_or_rap().to_string(),
+                item.rap.to_string(),
+            ),
+            None => Default::default(),
+        };
+
+        for uaid in &asset.uaids {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                asset.item_id, item_name, value, rap, uaid
+            )
+            .map_err(RoliError::IoError)?;
+        }
+    }
+
+    writer.flush().map_err(RoliError::IoError)
+}
+
+/// Quotes a CSV field if it contains a comma, quote, or line break.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+impl GameLocation {
+    /// Returns a url that opens the game on Roblox, which can be used to deep link
+    /// to where the player is.
+    pub fn url(&self) -> String {
+        format!("https://www.roblox.com/games/{}", self.place_id)
+    }
+}
+
+impl PlayerProfileResponse {
+    /// Returns the game the player is in, if they are in one Rolimons can see.
+    fn join_context(&self, presence_type: PresenceType) -> Option<GameLocation> {
+        if presence_type != PresenceType::InGame {
+            return None;
+        }
+
+        Some(GameLocation {
+            place_id: self.last_place_id?,
+            name: self.last_location.clone(),
+            icon_url: self.location_game_icon_url.clone(),
+            tracked: self.location_game_is_tracked,
+        })
+    }
+}
+
 impl PresenceType {
-    fn from_u8(value: u8) -> Self {
+    fn from_u8(value: u8) -> Result<Self, RoliError> {
         match value {
-            0 => Self::Unavailable,
-            1 => Self::Website,
-            2 => Self::InGame,
-            3 => Self::InStudio,
-            _ => Self::Unavailable,
+            0 => Ok(Self::Unavailable),
+            1 => Ok(Self::Website),
+            2 => Ok(Self::InGame),
+            3 => Ok(Self::InStudio),
+            _ => crate::unknown_code(Self::Unavailable),
         }
     }
 }
@@ -171,43 +723,26 @@ impl Client {
     ) -> Result<Vec<PlayerSearchResult>, RoliError> {
         let formatted_url = format!("{}?searchstring={}", PLAYER_SEARCH_API, username);
 
-        let request_result = self
+        let request = self
             .reqwest_client
             .get(formatted_url)
-            .header(header::USER_AGENT, crate::USER_AGENT)
-            .send()
-            .await;
-
-        match request_result {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                match status_code {
-                    200 => {
-                        let raw = match response.json::<PlayerSearchResponse>().await {
-                            Ok(x) => x,
-                            Err(_) => return Err(RoliError::MalformedResponse),
-                        };
-
-                        if !raw.success {
-                            return Err(RoliError::RequestReturnedUnsuccessful);
-                        }
-
-                        let mut search_outputs = Vec::new();
-
-                        for player in raw.players {
-                            search_outputs.push(PlayerSearchResult::from_raw(player)?);
-                        }
-
-                        Ok(search_outputs)
-                    }
-                    429 => Err(RoliError::TooManyRequests),
-                    500 => Err(RoliError::InternalServerError),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
-            Err(e) => Err(RoliError::ReqwestError(e)),
+            .header(header::USER_AGENT, self.user_agent());
+
+        let raw = self
+            .request_json::<PlayerSearchResponse>(Endpoint::PlayerSearch, request)
+            .await?;
+
+        if !raw.success {
+            return Err(RoliError::RequestReturnedUnsuccessful);
         }
+
+        let mut search_outputs = Vec::new();
+
+        for player in raw.players {
+            search_outputs.push(PlayerSearchResult::from_raw(player)?);
+        }
+
+        Ok(search_outputs)
     }
 
     /// Gets a player's Rolimons profile. Contains their Roblox inventory, Rolimons badges, Roblox online status,
@@ -236,69 +771,315 @@ impl Client {
     pub async fn player_profile(&self, user_id: u64) -> Result<PlayerProfile, RoliError> {
         let formatted_url = format!("{}{}", PLAYER_API, user_id);
 
-        let request_result = self
+        let request = self
             .reqwest_client
             .get(formatted_url)
-            .header(header::USER_AGENT, crate::USER_AGENT)
-            .send()
-            .await;
-
-        match request_result {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                match status_code {
-                    200 => {
-                        let raw = match response.json::<PlayerProfileResponse>().await {
-                            Ok(x) => x,
-                            Err(_) => return Err(RoliError::MalformedResponse),
-                        };
-
-                        if !raw.success {
-                            return Err(RoliError::RequestReturnedUnsuccessful);
-                        }
-
-                        let mut badges = Vec::new();
-
-                        for (name, timestamp) in raw.badges {
-                            badges.push(Badge {
-                                name,
-                                timestamp_earned: timestamp,
-                            });
-                        }
-
-                        let mut inventory = Vec::new();
-
-                        for (item_id, uaids) in raw.player_assets {
-                            let item_id_u64 = match item_id.parse::<u64>() {
-                                Ok(x) => x,
-                                Err(_) => return Err(RoliError::MalformedResponse),
-                            };
-
-                            inventory.push(PlayerAsset {
-                                item_id: item_id_u64,
-                                uaids,
-                            });
-                        }
-
-                        Ok(PlayerProfile {
-                            user_id: raw.player_id,
-                            terminated: raw.player_terminated,
-                            privated: raw.player_privacy_enabled,
-                            inventory,
-                            is_online: raw.is_online,
-                            presence_type: PresenceType::from_u8(raw.presence_type),
-                            last_online: raw.last_online,
-                            premium: raw.premium,
-                            badges,
-                        })
-                    }
-                    429 => Err(RoliError::TooManyRequests),
-                    500 => Err(RoliError::InternalServerError),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
-            Err(e) => Err(RoliError::ReqwestError(e)),
+            .header(header::USER_AGENT, self.user_agent());
+
+        let raw = self
+            .request_json::<PlayerProfileResponse>(Endpoint::PlayerProfile, request)
+            .await?;
+
+        if !raw.success {
+            return Err(RoliError::RequestReturnedUnsuccessful);
+        }
+
+        let presence_type = PresenceType::from_u8(raw.presence_type)?;
+        let join_context = raw.join_context(presence_type);
+
+        let mut badges = Vec::new();
+
+        for (name, timestamp) in raw.badges {
+            badges.push(Badge {
+                name,
+                timestamp_earned: timestamp,
+            });
+        }
+
+        badges.sort_by(|a, b| {
+            a.timestamp_earned
+                .cmp(&b.timestamp_earned)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let mut inventory = Vec::new();
+
+        for (item_id, uaids) in raw.player_assets {
+            let item_id_u64 = match item_id.parse::<u64>() {
+                Ok(x) => x,
+                Err(_) => return Err(RoliError::MalformedResponse),
+            };
+
+            inventory.push(PlayerAsset {
+                item_id: item_id_u64,
+                uaids,
+            });
+        }
+
+        normalize_inventory(&mut inventory);
+
+        Ok(PlayerProfile {
+            user_id: raw.player_id,
+            terminated: raw.player_terminated,
+            privated: raw.player_privacy_enabled,
+            inventory,
+            is_online: raw.is_online,
+            presence_type,
+            join_context,
+            last_online: raw.last_online,
+            premium: raw.premium,
+            verified: raw.player_verified,
+            badges,
+        })
+    }
+
+    /// The same as [`Client::player_profile`], but returns a cached profile if the
+    /// player was fetched recently.
+    ///
+    /// Profiles are cached per player for the ttl set with
+    /// [`ClientBuilder::set_player_profile_cache`](crate::ClientBuilder::set_player_profile_cache)
+    /// ([`DEFAULT_PLAYER_PROFILE_TTL`] by default). The ttl is never lower than
+    /// [`MIN_PLAYER_PROFILE_INTERVAL`], so a player is not fetched more often than that
+    /// through this method while their profile stays cached. Concurrent calls for the
+    /// same player share one fetch. Once the cache is full, the least recently used
+    /// profile is evicted and fetched again on its next call. Errors are not cached.
+    ///
+    /// The cache is shared between clones of the client.
+    ///
+    /// Does not require authentication.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let player = client.player_profile_cached(2207291).await?;
+    /// // Does not make another request.
+    /// let player = client.player_profile_cached(2207291).await?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn player_profile_cached(&self, user_id: u64) -> Result<PlayerProfile, RoliError> {
+        self.player_profile_cache
+            .get_or_fetch(user_id, || self.player_profile(user_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Faker;
+
+    fn profile(user_id: u64) -> PlayerProfile {
+        PlayerProfile {
+            user_id,
+            ..Faker::new(user_id).player_profile()
+        }
+    }
+
+    fn profile_with_inventory(user_id: u64, inventory: &[(u64, &[u64])]) -> PlayerProfile {
+        PlayerProfile {
+            inventory: inventory
+                .iter()
+                .map(|(item_id, uaids)| PlayerAsset {
+                    item_id: *item_id,
+                    uaids: uaids.to_vec(),
+                })
+                .collect(),
+            ..profile(user_id)
         }
     }
+
+    #[test]
+    fn test_export_inventory_csv() {
+        use crate::items::{ItemDetails, ItemDetailsTable};
+
+        let table = ItemDetailsTable::new(vec![ItemDetails {
+            item_id: 1,
+            item_name: "Bighead, the \"Great\"".to_string(),
+            rap: 800,
+            valued: true,
+            value: 1000,
+            ..Default::default()
+        }]);
+
+        let profile = profile_with_inventory(5, &[(1, &[10, 11]), (2, &[12])]);
+
+        let mut csv = Vec::new();
+        export_inventory_csv(&profile, &table, &mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "Item ID,Item Name,Value,RAP,UAID\n\
+             1,\"Bighead, the \"\"Great\"\"\",1000,800,10\n\
+             1,\"Bighead, the \"\"Great\"\"\",1000,800,11\n\
+             2,,,,12\n"
+        );
+    }
+
+    #[test]
+    fn test_badges() {
+        let player = PlayerProfile {
+            badges: vec![
+                Badge {
+                    name: "roli_verified".to_string(),
+                    timestamp_earned: 1,
+                },
+                Badge {
+                    name: "unknown_badge".to_string(),
+                    timestamp_earned: 2,
+                },
+            ],
+            ..profile(1)
+        };
+
+        assert!(player.has_badge(RoliBadge::RoliVerified));
+        assert!(!player.has_badge(RoliBadge::RoliDonor));
+        assert_eq!(player.badges[0].kind(), Some(RoliBadge::RoliVerified));
+        assert_eq!(player.badges[1].kind(), None);
+
+        for badge in RoliBadge::ALL {
+            assert_eq!(RoliBadge::from_name(badge.name()), Some(badge));
+        }
+    }
+
+    #[test]
+    fn test_join_context() {
+        let mut raw = serde_json::from_value::<PlayerProfileResponse>(serde_json::json!({
+            "success": true,
+            "playerTerminated": false,
+            "playerPrivacyEnabled": false,
+            "playerVerified": true,
+            "playerId": 1,
+            "chartNominalScanTime": 0,
+            "playerAssets": {},
+            "isOnline": true,
+            "presenceType": 2,
+            "lastOnline": 1700000000,
+            "lastLocation": "Test Game",
+            "lastPlaceId": 920587237,
+            "locationGameIsTracked": true,
+            "locationGameIconUrl": "https://example.com/icon.png",
+            "premium": false,
+            "badges": {}
+        }))
+        .unwrap();
+
+        let location = raw.join_context(PresenceType::InGame).unwrap();
+        assert_eq!(location.place_id, 920587237);
+        assert_eq!(location.name, "Test Game");
+        assert!(location.tracked);
+        assert_eq!(location.url(), "https://www.roblox.com/games/920587237");
+
+        assert!(raw.join_context(PresenceType::Website).is_none());
+
+        raw.last_place_id = None;
+        assert!(raw.join_context(PresenceType::InGame).is_none());
+    }
+
+    #[test]
+    fn test_normalize_inventory() {
+        let mut player = profile_with_inventory(1, &[(20, &[3, 1, 3]), (10, &[5]), (20, &[2])]);
+        normalize_inventory(&mut player.inventory);
+
+        assert_eq!(
+            player.inventory,
+            vec![
+                PlayerAsset {
+                    item_id: 10,
+                    uaids: vec![5]
+                },
+                PlayerAsset {
+                    item_id: 20,
+                    uaids: vec![1, 2, 3]
+                },
+            ]
+        );
+        assert_eq!(player.uaid_count(), 4);
+        assert_eq!(player.unique_item_count(), 2);
+        assert_eq!(player.asset(20).unwrap().uaids, vec![1, 2, 3]);
+        assert!(player.asset(15).is_none());
+        assert_eq!(player.inventory_map()[&10].uaids, vec![5]);
+    }
+
+    #[test]
+    fn test_overlap() {
+        let a = profile_with_inventory(1, &[(10, &[100, 101]), (20, &[200]), (30, &[300])]);
+        let b = profile_with_inventory(2, &[(10, &[102]), (30, &[301]), (40, &[400])]);
+
+        let overlap = overlap(&a, &b);
+
+        assert_eq!(overlap.common_item_ids, vec![10, 30]);
+        assert!(overlap.shared_uaids.is_empty());
+    }
+
+    #[test]
+    fn test_overlap_history() {
+        let a_before = profile_with_inventory(1, &[(10, &[100, 101]), (20, &[200])]);
+        let a_after = profile_with_inventory(1, &[(10, &[100])]);
+        let b_after = profile_with_inventory(2, &[(10, &[101]), (20, &[200])]);
+
+        let overlap = overlap_history(&[a_before, a_after], &[b_after]);
+
+        assert_eq!(overlap.common_item_ids, vec![10]);
+        assert_eq!(overlap.shared_uaids, vec![101, 200]);
+    }
+
+    #[test]
+    fn test_player_profile_cache_evicts_least_recently_used() {
+        let cache = PlayerProfileCache::new(Duration::ZERO, 2);
+
+        cache.insert(1, profile(1));
+        cache.insert(2, profile(2));
+        assert!(cache.get(1).is_some());
+
+        cache.insert(3, profile(3));
+
+        assert!(cache.get(1).is_some());
+        assert!(cache.get(2).is_none());
+        assert!(cache.get(3).is_some());
+    }
+
+    #[test]
+    fn test_player_profile_cache_ttl_floor() {
+        let cache = PlayerProfileCache::new(Duration::ZERO, 10);
+        assert_eq!(cache.ttl, MIN_PLAYER_PROFILE_INTERVAL);
+
+        cache.insert(1, profile(1));
+        assert_eq!(cache.get(1).unwrap().user_id, 1);
+    }
+
+    #[test]
+    fn test_player_profile_cache_zero_capacity() {
+        let cache = PlayerProfileCache::new(Duration::ZERO, 0);
+        assert_eq!(cache.capacity, 1);
+
+        cache.insert(1, profile(1));
+        assert_eq!(cache.get(1).unwrap().user_id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_player_profile_cache_joins_in_flight_fetches() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = PlayerProfileCache::new(Duration::ZERO, 10);
+        let fetches = AtomicUsize::new(0);
+
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Ok(profile(1))
+        };
+
+        let (a, b) = tokio::join!(cache.get_or_fetch(1, fetch), cache.get_or_fetch(1, fetch));
+
+        assert_eq!(a.unwrap().user_id, 1);
+        assert_eq!(b.unwrap().user_id, 1);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(cache.in_flight.lock().unwrap().is_empty());
+    }
 }
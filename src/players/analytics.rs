@@ -0,0 +1,263 @@
+use crate::items::ItemInfoProvider;
+use crate::players::PlayerProfile;
+use crate::trade_ads::TradeAd;
+use serde::{Deserialize, Serialize};
+
+/// How concentrated a player's inventory value is, as returned by [`concentration`].
+///
+/// Every share is a fraction of [`Concentration::total_value`] between 0 and 1, and is
+/// 0 if the total value is 0.
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Concentration {
+    /// The total value of the inventory, from [`PlayerProfile::inventory_value`].
+    pub total_value: u64,
+    /// The Gini coefficient of the value held in each item, from 0 (every item is worth
+    /// the same) to nearly 1 (one item holds all of the value).
+    pub gini: f64,
+    /// The share of the value held in the player's most valuable item, counting every copy.
+    pub top_item_share: f64,
+    /// The share of the value held in rare items.
+    pub rares_share: f64,
+    /// The share of the value held in projected items.
+    pub projecteds_share: f64,
+}
+
+/// Computes how concentrated `profile`'s inventory value is, using the values in `table`.
+///
+/// Each item is counted at its [`value_or_rap`](crate::items::ItemDetails::value_or_rap)
+/// times the amount of copies owned. Items that are not in `table` are not counted.
+///
+/// A counterparty whose value is mostly in one item, or in projecteds, is riskier to
+/// trade with than one with the same value spread over many items.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::ItemDetailsTable;
+/// use roli::players::analytics;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+/// let profile = client.player_profile(2207291).await?;
+///
+/// let concentration = analytics::concentration(&profile, &table);
+///
+/// if concentration.projecteds_share > 0.5 {
+///     println!("Most of this player's value is in projecteds");
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn concentration(profile: &PlayerProfile, table: &impl ItemInfoProvider) -> Concentration {
+    let mut holdings = Vec::with_capacity(profile.inventory.len());
+    let mut rares_value = 0;
+    let mut projecteds_value = 0;
+
+    for asset in &profile.inventory {
+        let item = match table.item_details(asset.item_id) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        let value = item.value_or_rap() * asset.uaids.len() as u64;
+
+        if item.rare {
+            rares_value += value;
+        }
+
+        if item.projected {
+            projecteds_value += value;
+        }
+
+        holdings.push(value);
+    }
+
+    let total_value = holdings.iter().sum::<u64>();
+
+    if total_value == 0 {
+        return Concentration::default();
+    }
+
+    holdings.sort_unstable();
+
+    let share = |value: u64| value as f64 / total_value as f64;
+
+    Concentration {
+        total_value,
+        gini: gini(&holdings, total_value),
+        top_item_share: share(holdings.last().copied().unwrap_or(0)),
+        rares_share: share(rares_value),
+        projecteds_share: share(projecteds_value),
+    }
+}
+
+/// How actively a player uses the trade ads page, as returned by [`trade_activity`].
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct TradeActivity {
+    /// The user id of the player.
+    pub user_id: u64,
+    /// The least amount of trade ads the player has ever created, from
+    /// [`PlayerProfile::min_trade_ads_created`].
+    pub min_trade_ads_created: u64,
+    /// The amount of the given trade ads that were posted by the player.
+    pub recent_trade_ads: u64,
+    /// The unix timestamp of the player's newest trade ad among the given ones.
+    #[cfg_attr(
+        feature = "rfc3339",
+        serde(with = "crate::serde_helpers::rfc3339_option")
+    )]
+    pub last_trade_ad_at: Option<u64>,
+}
+
+/// Measures how actively `profile`'s player trades, using their trade ad badges and
+/// their ads among `trade_ads` (such as the ads collected from
+/// [`Client::trade_ad_stream`](crate::Client::trade_ad_stream) over a day).
+///
+/// Rolimons does not expose exact trade or trade ad counts, so these are the best
+/// available signals of how responsive a trade partner is likely to be.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::players::analytics;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let trade_ads = client.recent_trade_ads().await?;
+/// let profile = client.player_profile(2207291).await?;
+///
+/// let activity = analytics::trade_activity(&profile, &trade_ads);
+/// println!(
+///     "{}+ ads created, {} in the last few minutes",
+///     activity.min_trade_ads_created, activity.recent_trade_ads
+/// );
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn trade_activity(profile: &PlayerProfile, trade_ads: &[TradeAd]) -> TradeActivity {
+    let own_ads = trade_ads
+        .iter()
+        .filter(|trade_ad| trade_ad.user_id == profile.user_id);
+
+    TradeActivity {
+        user_id: profile.user_id,
+        min_trade_ads_created: profile.min_trade_ads_created(),
+        recent_trade_ads: own_ads.clone().count() as u64,
+        last_trade_ad_at: own_ads.map(|trade_ad| trade_ad.timestamp).max(),
+    }
+}
+
+/// Computes the Gini coefficient of `sorted` (ascending), which sums to `total`.
+fn gini(sorted: &[u64], total: u64) -> f64 {
+    let n = sorted.len() as f64;
+
+    let weighted_sum = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (i + 1) as f64 * *x as f64)
+        .sum::<f64>();
+
+    (2.0 * weighted_sum) / (n * total as f64) - (n + 1.0) / n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::{ItemDetails, ItemDetailsTable};
+    use crate::players::{Badge, PlayerAsset};
+    use crate::testing::Faker;
+
+    fn item(item_id: u64, value: u64, rare: bool, projected: bool) -> ItemDetails {
+        ItemDetails {
+            item_id,
+            value,
+            valued: true,
+            rare,
+            projected,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_concentration() {
+        let table = ItemDetailsTable::new(vec![
+            item(1, 100, false, false),
+            item(2, 300, true, false),
+            item(3, 600, false, true),
+        ]);
+
+        let profile = PlayerProfile {
+            inventory: vec![
+                PlayerAsset {
+                    item_id: 1,
+                    uaids: vec![1],
+                },
+                PlayerAsset {
+                    item_id: 2,
+                    uaids: vec![2, 3],
+                },
+                PlayerAsset {
+                    item_id: 4,
+                    uaids: vec![4],
+                },
+            ],
+            ..Faker::new(1).player_profile()
+        };
+
+        let concentration = concentration(&profile, &table);
+
+        assert_eq!(concentration.total_value, 700);
+        assert!((concentration.top_item_share - 600.0 / 700.0).abs() < 1e-9);
+        assert!((concentration.rares_share - 600.0 / 700.0).abs() < 1e-9);
+        assert_eq!(concentration.projecteds_share, 0.0);
+        // (2 * (1 * 100 + 2 * 600)) / (2 * 700) - 3 / 2
+        assert!((concentration.gini - (2600.0 / 1400.0 - 1.5)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_trade_activity() {
+        let profile = PlayerProfile {
+            user_id: 1,
+            badges: vec![
+                Badge {
+                    name: "create_100_trade_ads".to_string(),
+                    timestamp_earned: 2,
+                },
+                Badge {
+                    name: "create_10_trade_ads".to_string(),
+                    timestamp_earned: 1,
+                },
+            ],
+            ..Faker::new(1).player_profile()
+        };
+
+        let trade_ad = |user_id, timestamp| TradeAd {
+            user_id,
+            timestamp,
+            ..Default::default()
+        };
+
+        let activity = trade_activity(
+            &profile,
+            &[trade_ad(1, 10), trade_ad(2, 30), trade_ad(1, 20)],
+        );
+
+        assert_eq!(activity.min_trade_ads_created, 100);
+        assert_eq!(activity.recent_trade_ads, 2);
+        assert_eq!(activity.last_trade_ad_at, Some(20));
+    }
+
+    #[test]
+    fn test_concentration_empty() {
+        let profile = Faker::new(1).player_profile();
+        let concentration = concentration(&profile, &ItemDetailsTable::default());
+        assert_eq!(concentration, Concentration::default());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::deals::Activity;
+use crate::games::Game;
+use crate::groups::GroupSearchResult;
+use crate::items::ItemDetails;
+use crate::market_activity::Sale;
+use crate::players::{PlayerProfile, PlayerSearchResult};
+use crate::trade_ads::TradeAd;
+use crate::{Client, RoliError};
+use serde_json::Value;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+impl Client {
+    /// The same as [`Client::all_item_details`], but also returns the raw json of the
+    /// response.
+    ///
+    /// The raw json is useful for diagnosing differences between what this crate
+    /// parses and what the website shows. Only available with the `raw` feature.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let (items, raw) = client.all_item_details_with_raw().await?;
+    ///
+    /// println!("Parsed {} items from {}", items.len(), raw["item_count"]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn all_item_details_with_raw(&self) -> Result<(Vec<ItemDetails>, Value), RoliError> {
+        self.with_raw(|client| async move { client.all_item_details().await })
+            .await
+    }
+
+    /// The same as [`Client::deals_activity`], but also returns the raw json of the
+    /// response. Only available with the `raw` feature.
+    pub async fn deals_activity_with_raw(&self) -> Result<(Vec<Activity>, Value), RoliError> {
+        self.with_raw(|client| async move { client.deals_activity().await })
+            .await
+    }
+
+    /// The same as [`Client::recent_trade_ads`], but also returns the raw json of the
+    /// response. Only available with the `raw` feature.
+    pub async fn recent_trade_ads_with_raw(&self) -> Result<(Vec<TradeAd>, Value), RoliError> {
+        self.with_raw(|client| async move { client.recent_trade_ads().await })
+            .await
+    }
+
+    /// The same as [`Client::player_search`], but also returns the raw json of the
+    /// response. Only available with the `raw` feature.
+    pub async fn player_search_with_raw(
+        &self,
+        username: &str,
+    ) -> Result<(Vec<PlayerSearchResult>, Value), RoliError> {
+        self.with_raw(|client| async move { client.player_search(username).await })
+            .await
+    }
+
+    /// The same as [`Client::player_profile`], but also returns the raw json of the
+    /// response. Only available with the `raw` feature.
+    pub async fn player_profile_with_raw(
+        &self,
+        user_id: u64,
+    ) -> Result<(PlayerProfile, Value), RoliError> {
+        self.with_raw(|client| async move { client.player_profile(user_id).await })
+            .await
+    }
+
+    /// The same as [`Client::games_list`], but also returns the raw json of the
+    /// response. Only available with the `raw` feature.
+    pub async fn games_list_with_raw(&self) -> Result<(Vec<Game>, Value), RoliError> {
+        self.with_raw(|client| async move { client.games_list().await })
+            .await
+    }
+
+    /// The same as [`Client::group_search`], but also returns the raw json of the
+    /// response. Only available with the `raw` feature.
+    pub async fn group_search_with_raw(
+        &self,
+        group_name: &str,
+    ) -> Result<(Vec<GroupSearchResult>, Value), RoliError> {
+        self.with_raw(|client| async move { client.group_search(group_name).await })
+            .await
+    }
+
+    /// The same as [`Client::recent_sales`], but also returns the raw json of the
+    /// response. Only available with the `raw` feature.
+    pub async fn recent_sales_with_raw(&self) -> Result<(Vec<Sale>, Value), RoliError> {
+        self.with_raw(|client| async move { client.recent_sales().await })
+            .await
+    }
+
+    /// Runs `call` with a copy of the client that keeps the body of its json response,
+    /// and returns the result of `call` along with that body.
+    async fn with_raw<T, F, Fut>(&self, call: F) -> Result<(T, Value), RoliError>
+    where
+        F: FnOnce(Client) -> Fut,
+        Fut: Future<Output = Result<T, RoliError>>,
+    {
+        let raw_body = Arc::new(Mutex::new(None));
+
+        let client = Client {
+            raw_body: Some(raw_body.clone()),
+            ..self.clone()
+        };
+
+        let typed = call(client).await?;
+
+        let body = raw_body
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .ok_or(RoliError::MalformedResponse)?;
+
+        Ok((typed, crate::request::parse_json(&body)?))
+    }
+}
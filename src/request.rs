@@ -0,0 +1,618 @@
+use crate::status::ResponseMeta;
+use crate::{Client, Endpoint, RoliError};
+use bytes::Bytes;
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The status code and body of a response, shared between coalesced requests, or why
+/// the request failed before a response was received.
+type SharedResponse = Shared<BoxFuture<'static, Result<(u16, Bytes), String>>>;
+
+/// Counts the requests made over a sliding window, so that a client stays under a
+/// limit it was configured with.
+#[derive(Debug)]
+pub(crate) struct RequestBudget {
+    limit: usize,
+    window: Duration,
+    made: Mutex<VecDeque<Instant>>,
+}
+
+impl RequestBudget {
+    pub(crate) fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit: limit as usize,
+            window,
+            made: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Records a request, or returns `false` without recording it if `limit` requests
+    /// were already made within the window.
+    pub(crate) fn try_acquire(&self) -> bool {
+        let now = Instant::now();
+        let mut made = self.made.lock().unwrap_or_else(|e| e.into_inner());
+
+        while made
+            .front()
+            .is_some_and(|made_at| now.duration_since(*made_at) >= self.window)
+        {
+            made.pop_front();
+        }
+
+        if made.len() >= self.limit {
+            return false;
+        }
+
+        made.push_back(now);
+        true
+    }
+}
+
+/// Takes a slot from `request_budget` before a request is sent, returning
+/// [`RoliError::TooManyRequests`] if there is none left.
+fn acquire(request_budget: Option<&RequestBudget>) -> Result<(), RoliError> {
+    match request_budget {
+        Some(request_budget) if !request_budget.try_acquire() => Err(RoliError::TooManyRequests),
+        _ => Ok(()),
+    }
+}
+
+/// Tracks in-flight requests so identical ones can share a single api call.
+#[derive(Default)]
+pub(crate) struct Coalescer {
+    uncoalesced_endpoints: HashSet<Endpoint>,
+    in_flight: Mutex<HashMap<String, SharedResponse>>,
+}
+
+impl Coalescer {
+    pub(crate) fn new(uncoalesced_endpoints: HashSet<Endpoint>) -> Self {
+        Self {
+            uncoalesced_endpoints,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_enabled(&self, endpoint: Endpoint) -> bool {
+        endpoint != Endpoint::CreateTradeAd && !self.uncoalesced_endpoints.contains(&endpoint)
+    }
+
+    /// Returns the in-flight response for `key`, starting `request` if there is none,
+    /// and whether it was started by this call.
+    fn join(
+        &self,
+        key: &str,
+        request: impl FnOnce() -> BoxFuture<'static, Result<(u16, Bytes), String>>,
+    ) -> (SharedResponse, bool) {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(response) = in_flight.get(key) {
+            return (response.clone(), false);
+        }
+
+        let response = request().shared();
+        in_flight.insert(key.to_string(), response.clone());
+
+        (response, true)
+    }
+
+    /// Joins or starts the request for `key` and waits for its response, also returning
+    /// whether it was started by this call.
+    ///
+    /// The request stops being shared once it finishes, or once a caller stops waiting
+    /// for it (such as when its deadline passes), so later calls never join a request
+    /// that may be stale or hung.
+    async fn run(
+        &self,
+        key: &str,
+        request: impl FnOnce() -> BoxFuture<'static, Result<(u16, Bytes), String>>,
+    ) -> (Result<(u16, Bytes), String>, bool) {
+        let (response, started) = self.join(key, request);
+
+        let _in_flight = InFlight {
+            coalescer: self,
+            key,
+            response: response.clone(),
+        };
+
+        (response.await, started)
+    }
+
+    /// Stops sharing `response` for `key`, so later requests make a new api call.
+    fn finish(&self, key: &str, response: &SharedResponse) {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+
+        if in_flight
+            .get(key)
+            .is_some_and(|x| Shared::ptr_eq(x, response))
+        {
+            in_flight.remove(key);
+        }
+    }
+}
+
+/// Calls [`Coalescer::finish`] when dropped, including when the caller waiting for
+/// the response is cancelled.
+struct InFlight<'a> {
+    coalescer: &'a Coalescer,
+    key: &'a str,
+    response: SharedResponse,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.coalescer.finish(self.key, &self.response);
+    }
+}
+
+/// Connection tuning applied to the reqwest client a [`ClientBuilder`](crate::ClientBuilder)
+/// creates. Unset options keep reqwest's defaults.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ConnectionOptions {
+    /// `Some(None)` disables the idle timeout.
+    pub(crate) pool_idle_timeout: Option<Option<Duration>>,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    pub(crate) http2: Option<bool>,
+}
+
+impl ConnectionOptions {
+    pub(crate) fn is_set(&self) -> bool {
+        *self != Self::default()
+    }
+
+    pub(crate) fn reqwest_client(&self) -> Result<reqwest::Client, RoliError> {
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(pool_idle_timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(pool_idle_timeout);
+        }
+
+        if let Some(pool_max_idle_per_host) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+
+        if let Some(tcp_keepalive) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(tcp_keepalive);
+        }
+
+        builder = match self.http2 {
+            Some(true) => builder.http2_prior_knowledge(),
+            Some(false) => builder.http1_only(),
+            None => builder,
+        };
+
+        builder.build().map_err(RoliError::ReqwestError)
+    }
+}
+
+impl fmt::Debug for Coalescer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Coalescer")
+            .field("uncoalesced_endpoints", &self.uncoalesced_endpoints)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Sends `request` and parses a 200 response as json.
+    ///
+    /// A 200 response with an html body (such as a maintenance page) is returned as
+    /// [`RoliError::UnexpectedHtmlResponse`]. Status codes 429 and 500 are mapped to their [`RoliError`] variants, and any
+    /// other status code is returned as [`RoliError::UnidentifiedStatusCode`].
+    ///
+    /// If coalescing is enabled for `endpoint`, identical requests that are already
+    /// in flight are joined instead of being sent again. If the shared request fails
+    /// before a response is received, the caller that started it sends its own request
+    /// to get its own [`RoliError::ReqwestError`], and the callers that joined it get
+    /// [`RoliError::CoalescedRequestFailed`].
+    pub(crate) async fn request_json<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        request: RequestBuilder,
+    ) -> Result<T, RoliError> {
+        self.instrument(endpoint, async {
+            let start = Instant::now();
+
+            let (status_code, body) = match self.send(endpoint, request).await {
+                Ok(x) => x,
+                Err(e) => return (Err(e), None),
+            };
+
+            let http_time = start.elapsed();
+
+            #[cfg(feature = "raw")]
+            if status_code == 200 {
+                if let Some(raw_body) = &self.raw_body {
+                    *raw_body.lock().unwrap_or_else(|e| e.into_inner()) = Some(body.clone());
+                }
+            }
+
+            let result = match status_code {
+                200 => {
+                    let start = Instant::now();
+                    let result = parse_json(&body);
+
+                    self.status.record_response(
+                        endpoint,
+                        ResponseMeta {
+                            bytes: body.len() as u64,
+                            http_time,
+                            parse_time: start.elapsed(),
+                        },
+                    );
+
+                    result
+                }
+                _ => Err(status_error(status_code)),
+            };
+
+            (result, Some(status_code))
+        })
+        .await
+    }
+
+    /// Sends `request`, returning the status code and body of the response.
+    ///
+    /// Identical requests are coalesced if coalescing is enabled for `endpoint`. With
+    /// the `audit` feature, the request is recorded in the audit log, or answered from
+    /// the replayed log instead of being sent.
+    ///
+    /// Returns [`RoliError::TooManyRequests`] without sending the request if it would go
+    /// over the limit set with [`ClientBuilder::set_requests_per_minute`](crate::ClientBuilder::set_requests_per_minute).
+    /// Joining a coalesced request does not count towards the limit.
+    pub(crate) async fn send(
+        &self,
+        endpoint: Endpoint,
+        request: RequestBuilder,
+    ) -> Result<(u16, Bytes), RoliError> {
+        #[cfg(feature = "audit")]
+        let target = crate::audit::target(&request);
+
+        #[cfg(feature = "audit")]
+        if let (Some(replay), Some(target)) = (&self.replay, &target) {
+            return replay.next(target);
+        }
+
+        #[cfg(feature = "audit")]
+        let (timestamp, start) = (crate::unix_timestamp(), Instant::now());
+
+        let result = if self.coalescer.is_enabled(endpoint) {
+            match self.coalesced_response(request.try_clone()).await {
+                Some(x) => x,
+                None => self.response(request).await,
+            }
+        } else {
+            self.response(request).await
+        };
+
+        #[cfg(feature = "audit")]
+        if let (Some(audit_log), Some(target)) = (&self.audit_log, target) {
+            // A 429 response is returned as a status code, so this error only comes from
+            // the request budget, and nothing was sent.
+            if !matches!(result, Err(RoliError::TooManyRequests)) {
+                audit_log.record(endpoint, target, timestamp, start.elapsed(), &result);
+            }
+        }
+
+        result
+    }
+
+    /// Runs a request future, recording how long it took and whether it failed.
+    ///
+    /// Every endpoint goes through here so that cross-cutting concerns (like metrics,
+    /// status reports, and deadlines) only need to be written once. `future` returns
+    /// the result along with the status code of the response, if one was received.
+    pub(crate) async fn instrument<T, F>(
+        &self,
+        endpoint: Endpoint,
+        future: F,
+    ) -> Result<T, RoliError>
+    where
+        F: Future<Output = (Result<T, RoliError>, Option<u16>)>,
+    {
+        let start = Instant::now();
+
+        #[cfg(feature = "async-rt")]
+        let result = match self.deadline {
+            Some(deadline) => match tokio::time::timeout(deadline, future).await {
+                Ok(x) => x,
+                Err(_) => (Err(RoliError::DeadlineExceeded), None),
+            },
+            None => future.await,
+        };
+
+        #[cfg(not(feature = "async-rt"))]
+        let result = future.await;
+
+        let (result, status_code) = result;
+
+        crate::metrics::record_request(endpoint, start.elapsed(), result.as_ref().err());
+        self.status
+            .record(endpoint, start.elapsed(), status_code, &result);
+
+        result
+    }
+
+    /// Sends `request` on its own, taking a slot from the request budget first.
+    async fn response(&self, request: RequestBuilder) -> Result<(u16, Bytes), RoliError> {
+        acquire(self.request_budget.as_deref())?;
+
+        let response = request.send().await.map_err(RoliError::ReqwestError)?;
+        let status_code = response.status().as_u16();
+        let body = response.bytes().await.map_err(RoliError::ReqwestError)?;
+
+        Ok((status_code, body))
+    }
+
+    /// Joins or starts a shared request.
+    ///
+    /// If the shared request fails before a response is received, the callers that
+    /// joined it get [`RoliError::CoalescedRequestFailed`]. Returns `None` if the request
+    /// can't be shared, or if this call started the request that failed, so that only
+    /// that caller sends it again to get its own [`RoliError::ReqwestError`].
+    ///
+    /// Only the call that starts the request takes a slot from the request budget, and
+    /// sending it again takes another one.
+    async fn coalesced_response(
+        &self,
+        request: Option<RequestBuilder>,
+    ) -> Option<Result<(u16, Bytes), RoliError>> {
+        let request = request?.build().ok()?;
+        let key = format!("{} {}", request.method(), request.url());
+        let reqwest_client = self.reqwest_client.clone();
+        let request_budget = self.request_budget.clone();
+
+        let (result, started) = self
+            .coalescer
+            .run(&key, || {
+                async move {
+                    acquire(request_budget.as_deref()).map_err(|e| e.to_string())?;
+
+                    let response = reqwest_client
+                        .execute(request)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    let status_code = response.status().as_u16();
+                    let body = response.bytes().await.map_err(|e| e.to_string())?;
+
+                    Ok((status_code, body))
+                }
+                .boxed()
+            })
+            .await;
+
+        match result {
+            Ok(x) => Some(Ok(x)),
+            Err(_) if started => None,
+            Err(e) => Some(Err(RoliError::CoalescedRequestFailed(e))),
+        }
+    }
+}
+
+/// Returns the error for a response with a status code other than 200.
+pub(crate) fn status_error(status_code: u16) -> RoliError {
+    match status_code {
+        429 => RoliError::TooManyRequests,
+        500 => RoliError::InternalServerError,
+        _ => RoliError::UnidentifiedStatusCode(status_code),
+    }
+}
+
+/// Parses a json body, telling html pages apart from malformed json.
+pub(crate) fn parse_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, RoliError> {
+    match serde_json::from_slice::<T>(body) {
+        Ok(x) => Ok(x),
+        Err(_) if body.trim_ascii_start().starts_with(b"<") => {
+            Err(RoliError::UnexpectedHtmlResponse)
+        }
+        Err(_) => Err(RoliError::MalformedResponse),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_coalescer_shares_in_flight_requests() {
+        let coalescer = Coalescer::default();
+        let calls = AtomicUsize::new(0);
+
+        let request = || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Ok((200, Bytes::from_static(b"[]"))) }.boxed()
+        };
+
+        let (first, started) = coalescer.join("GET a", request);
+        assert!(started);
+        let (second, started) = coalescer.join("GET a", request);
+        assert!(!started);
+        let (other, _) = coalescer.join("GET b", request);
+
+        assert!(Shared::ptr_eq(&first, &second));
+        assert!(!Shared::ptr_eq(&first, &other));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        assert_eq!(second.clone().await, first.clone().await);
+        coalescer.finish("GET a", &first);
+        coalescer.finish("GET a", &second);
+
+        let (third, _) = coalescer.join("GET a", request);
+        assert!(!Shared::ptr_eq(&first, &third));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_failure_is_shared() {
+        let coalescer = Coalescer::default();
+        let calls = AtomicUsize::new(0);
+
+        let request = || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async {
+                // Stays in flight until the second caller has joined.
+                tokio::task::yield_now().await;
+                Err("connection refused".to_string())
+            }
+            .boxed()
+        };
+
+        let (first, second) = tokio::join!(
+            coalescer.run("GET a", request),
+            coalescer.run("GET a", request)
+        );
+
+        // Only one request was sent, and only its starter is told to send it again.
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(first, (Err("connection refused".to_string()), true));
+        assert_eq!(second, (Err("connection refused".to_string()), false));
+    }
+
+    #[test]
+    fn test_cancelled_request_stops_being_shared() {
+        let coalescer = Coalescer::default();
+
+        // Polled once and then dropped, like a caller whose deadline passed.
+        let cancelled = coalescer
+            .run("GET a", || futures_util::future::pending().boxed())
+            .now_or_never();
+        assert!(cancelled.is_none());
+
+        assert!(coalescer.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "async-rt")]
+    #[tokio::test]
+    async fn test_deadline_exceeded() {
+        let client = crate::ClientBuilder::new()
+            .build()
+            .with_deadline(std::time::Duration::from_millis(10));
+
+        let result = client
+            .instrument(Endpoint::RecentSales, async {
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                (Ok(()), Some(200))
+            })
+            .await;
+
+        assert!(matches!(result, Err(RoliError::DeadlineExceeded)));
+
+        let result = client
+            .instrument(Endpoint::RecentSales, async { (Ok(()), Some(200)) })
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_request_budget() {
+        let budget = RequestBudget::new(2, Duration::from_secs(60));
+        assert!(budget.try_acquire());
+        assert!(budget.try_acquire());
+        assert!(!budget.try_acquire());
+
+        let budget = RequestBudget::new(1, Duration::ZERO);
+        assert!(budget.try_acquire());
+        assert!(budget.try_acquire());
+    }
+
+    /// Serves every connection a `[]` body after a short delay, counting the connections.
+    async fn slow_server() -> (String, Arc<AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                tokio::spawn(async move {
+                    let _ = stream.read(&mut [0; 1024]).await;
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    let _ = stream
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]",
+                        )
+                        .await;
+                });
+            }
+        });
+
+        (url, connections)
+    }
+
+    #[tokio::test]
+    async fn test_joined_request_does_not_use_budget() {
+        let (url, connections) = slow_server().await;
+        let client = crate::ClientBuilder::new()
+            .set_requests_per_minute(Some(1))
+            .build();
+
+        let (first, second) = tokio::join!(
+            client.send(Endpoint::RecentSales, client.reqwest_client.get(&url)),
+            client.send(Endpoint::RecentSales, client.reqwest_client.get(&url))
+        );
+
+        assert_eq!(first.unwrap().0, 200);
+        assert_eq!(second.unwrap().0, 200);
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+        let third = client
+            .send(Endpoint::RecentSales, client.reqwest_client.get(&url))
+            .await;
+        assert!(matches!(third, Err(RoliError::TooManyRequests)));
+    }
+
+    #[tokio::test]
+    async fn test_resent_request_uses_budget() {
+        // Nothing listens on the port once the listener is dropped.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+
+        let client = crate::ClientBuilder::new()
+            .set_requests_per_minute(Some(2))
+            .build();
+
+        // The shared request fails, so it is sent again with its own slot.
+        let result = client
+            .send(Endpoint::RecentSales, client.reqwest_client.get(&url))
+            .await;
+        assert!(matches!(result, Err(RoliError::ReqwestError(_))));
+        assert!(!client.request_budget.as_ref().unwrap().try_acquire());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        assert_eq!(parse_json::<Vec<u64>>(b"[1, 2]").unwrap(), vec![1, 2]);
+
+        assert!(matches!(
+            parse_json::<Vec<u64>>(b"\n  <!DOCTYPE html><title>Maintenance</title>"),
+            Err(RoliError::UnexpectedHtmlResponse)
+        ));
+
+        assert!(matches!(
+            parse_json::<Vec<u64>>(b"{\"success\": false"),
+            Err(RoliError::MalformedResponse)
+        ));
+    }
+
+    #[test]
+    fn test_coalescing_toggle() {
+        let coalescer = Coalescer::new(HashSet::from([Endpoint::PlayerProfile]));
+
+        assert!(coalescer.is_enabled(Endpoint::AllItemDetails));
+        assert!(!coalescer.is_enabled(Endpoint::PlayerProfile));
+        assert!(!coalescer.is_enabled(Endpoint::CreateTradeAd));
+    }
+}
@@ -0,0 +1,310 @@
+//! These are the same conversions this crate uses internally, exposed for use with
+//! `#[serde(deserialize_with = "...")]` when decoding raw Rolimons payloads yourself.
+//!
+//! # Example
+//! ```
+//! use roli::serde_helpers;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct RawItem(
+//!     String,
+//!     #[serde(deserialize_with = "serde_helpers::string_or_u64")] u64,
+//!     #[serde(deserialize_with = "serde_helpers::neg_one_as_none")] Option<u64>,
+//!     #[serde(deserialize_with = "serde_helpers::bool_from_pm_one")] bool,
+//! );
+//!
+//! let item: RawItem = serde_json::from_str(r#"["Dominus Empyreus", "4500000", -1, 1]"#).unwrap();
+//!
+//! assert_eq!(item.1, 4_500_000);
+//! assert_eq!(item.2, None);
+//! assert!(item.3);
+//! ```
+
+use crate::{Code, RoliError};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes a non-negative number that may be encoded as either a json number
+/// or a string (e.g. `123` or `"123"`).
+///
+/// Unless the `strict-parsing` feature is enabled, floats and strings with thousands
+/// separators (e.g. `99.6` or `"1,234"`) are also accepted and rounded. This applies
+/// to every helper in this module that reads a number.
+pub fn string_or_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Code::deserialize(deserializer)?
+        .to_u64()
+        .map_err(|e| to_de_error::<D>(e, "a non-negative integer or integer string"))
+}
+
+/// Deserializes a number where -1 means missing, such as the value of an unvalued
+/// item. Like [`string_or_u64`], the number may be encoded as a string.
+pub fn neg_one_as_none<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error> {
+    Code::deserialize(deserializer)?
+        .to_optional_u64()
+        .map_err(|e| to_de_error::<D>(e, "-1 or a non-negative integer"))
+}
+
+/// Deserializes a flag encoded as 1 (`true`) or -1 (`false`), such as whether an
+/// item is projected.
+///
+/// Other numbers deserialize as `false`, or fail if the `strict-parsing` feature
+/// is enabled.
+pub fn bool_from_pm_one<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Code::deserialize(deserializer)?
+        .to_bool()
+        .map_err(|e| to_de_error::<D>(e, "1 or -1"))
+}
+
+/// Formats a unix timestamp as an RFC 3339 string in UTC, such as
+/// `2023-03-16T04:06:40Z`.
+pub fn format_rfc3339(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Parses an RFC 3339 string (such as `2023-03-16T04:06:40Z` or
+/// `2023-03-16T06:06:40.5+02:00`) into a unix timestamp.
+///
+/// Fractions of a second are dropped. Returns `None` if the string is not valid
+/// RFC 3339 or is before 1970.
+pub fn parse_rfc3339(rfc3339: &str) -> Option<u64> {
+    let bytes = rfc3339.as_bytes();
+
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = rfc3339.get(range)?;
+
+        match digits.bytes().all(|x| x.is_ascii_digit()) {
+            true => digits.parse().ok(),
+            false => None,
+        }
+    };
+
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    // Leap seconds are allowed by RFC 3339, and are folded into the next second.
+    if second > 60 {
+        return None;
+    }
+
+    let mut rest = &rfc3339[19..];
+
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(|x| x.is_ascii_digit()).count();
+
+        if digits == 0 {
+            return None;
+        }
+
+        rest = &fraction[digits..];
+    }
+
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let hours = number(rfc3339.len() - 5..rfc3339.len() - 3)?;
+            let minutes = number(rfc3339.len() - 2..rfc3339.len())?;
+
+            match rest.as_bytes()[0] {
+                b'+' => hours * 3600 + minutes * 60,
+                b'-' => -(hours * 3600 + minutes * 60),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    let timestamp =
+        days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset;
+
+    u64::try_from(timestamp).ok()
+}
+
+/// Serializes a unix timestamp as an RFC 3339 string, for use with
+/// `#[serde(with = "roli::serde_helpers::rfc3339")]`.
+///
+/// Deserializing accepts an RFC 3339 string, or a unix timestamp encoded as a number
+/// or a string, so data saved before switching formats can still be read.
+///
+/// With the `rfc3339` feature, this crate's own structs serialize their timestamps
+/// this way.
+pub mod rfc3339 {
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes a unix timestamp as an RFC 3339 string.
+    pub fn serialize<S: Serializer>(timestamp: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::format_rfc3339(*timestamp))
+    }
+
+    /// Deserializes an RFC 3339 string or a unix timestamp.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        super::timestamp_from_code::<D>(serde::Deserialize::deserialize(deserializer)?)
+    }
+}
+
+/// The same as [`rfc3339`], but for optional timestamps.
+pub mod rfc3339_option {
+    use crate::Code;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serializes a unix timestamp as an RFC 3339 string, or `None` as null.
+    pub fn serialize<S: Serializer>(
+        timestamp: &Option<u64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match timestamp {
+            Some(x) => super::rfc3339::serialize(x, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes an RFC 3339 string, a unix timestamp, or null.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        Option::<Code>::deserialize(deserializer)?
+            .map(super::timestamp_from_code::<D>)
+            .transpose()
+    }
+}
+
+fn timestamp_from_code<'de, D: Deserializer<'de>>(code: Code) -> Result<u64, D::Error> {
+    if let Code::String(x) = &code {
+        if let Some(timestamp) = parse_rfc3339(x) {
+            return Ok(timestamp);
+        }
+    }
+
+    code.to_u64()
+        .map_err(|e| to_de_error::<D>(e, "an RFC 3339 string or a unix timestamp"))
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// Converts a (year, month, day) date into days since 1970-01-01.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+fn to_de_error<'de, D: Deserializer<'de>>(error: RoliError, expected: &str) -> D::Error {
+    D::Error::custom(format!("{}, expected {}", error, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Deserialize)]
+    struct Row(
+        #[serde(deserialize_with = "string_or_u64")] u64,
+        #[serde(deserialize_with = "neg_one_as_none")] Option<u64>,
+        #[serde(deserialize_with = "bool_from_pm_one")] bool,
+    );
+
+    #[test]
+    fn test_helpers() {
+        let row = serde_json::from_str::<Row>(r#"["42", "7", -1]"#).unwrap();
+        assert_eq!((row.0, row.1, row.2), (42, Some(7), false));
+
+        let row = serde_json::from_str::<Row>(r#"[42, -1, 1]"#).unwrap();
+        assert_eq!((row.0, row.1, row.2), (42, None, true));
+
+        assert!(serde_json::from_str::<Row>(r#"[-5, -1, 1]"#).is_err());
+        assert!(serde_json::from_str::<Row>(r#"["abc", -1, 1]"#).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        #[serde(with = "rfc3339")]
+        timestamp: u64,
+        #[serde(with = "rfc3339_option")]
+        updated_at: Option<u64>,
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(1678939600), "2023-03-16T04:06:40Z");
+        assert_eq!(format_rfc3339(951782400), "2000-02-29T00:00:00Z");
+
+        assert_eq!(parse_rfc3339("2023-03-16T04:06:40Z"), Some(1678939600));
+        assert_eq!(
+            parse_rfc3339("2023-03-16T06:06:40.123+02:00"),
+            Some(1678939600)
+        );
+        assert_eq!(parse_rfc3339("2023-03-15T23:06:40-05:00"), Some(1678939600));
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z"), None);
+        assert_eq!(parse_rfc3339("2023-13-16T04:06:40Z"), None);
+        assert_eq!(parse_rfc3339("2023-03-16T04:06:40"), None);
+
+        let event = Event {
+            timestamp: 1678939600,
+            updated_at: None,
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            json,
+            r#"{"timestamp":"2023-03-16T04:06:40Z","updated_at":null}"#
+        );
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+
+        let event =
+            serde_json::from_str::<Event>(r#"{"timestamp":1678939600,"updated_at":"5"}"#).unwrap();
+        assert_eq!(event.timestamp, 1678939600);
+        assert_eq!(event.updated_at, Some(5));
+    }
+}
@@ -0,0 +1,382 @@
+use crate::items::Snapshot;
+use crate::{compression, RoliError};
+use futures_util::future::{self, BoxFuture};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Contains [`RedisState`](redis::RedisState), which shares state between instances through Redis.
+#[cfg(feature = "redis")]
+pub mod redis;
+
+/// The key [`SnapshotStore`] keeps the snapshot under.
+const SNAPSHOT_KEY: &str = "snapshot";
+
+/// A key-value store for the state kept by the cache and streams, such as the
+/// latest item details snapshot and the last seen sale and trade ad ids.
+///
+/// Implement this to keep state in your own backend (SQLite, S3, etc.). Every
+/// [`StateStore`] is also a [`SnapshotStore`] and a [`CursorStore`], so it can be
+/// passed to [`ItemCacheBuilder::set_state`](crate::cache::ItemCacheBuilder::set_state)
+/// and to [`streams::sale_stream`](crate::streams::sale_stream) and
+/// [`streams::trade_ad_stream`](crate::streams::trade_ad_stream) directly.
+///
+/// In-crate implementations are [`MemoryState`], [`FileState`], and (with the `redis`
+/// feature) [`RedisState`](redis::RedisState).
+pub trait StateStore: Send + Sync {
+    /// Returns the value of `key`, or `None` if it is not set.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, RoliError>>;
+
+    /// Sets `key` to `value`.
+    fn set<'a>(&'a self, key: &'a str, value: &'a [u8]) -> BoxFuture<'a, Result<(), RoliError>>;
+
+    /// Sets `key` to `new` only if its current value is `expected` (`None` meaning
+    /// that it is not set), returning whether it was set.
+    ///
+    /// This must be atomic for every user of the store, as it is what keeps
+    /// instances sharing a store from overwriting each other.
+    fn compare_and_swap<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<&'a [u8]>,
+        new: &'a [u8],
+    ) -> BoxFuture<'a, Result<bool, RoliError>>;
+}
+
+/// Somewhere to keep the latest item details [`Snapshot`], so that it can be shared
+/// between instances of a bot.
+///
+/// Implemented for every [`StateStore`], which stores the snapshot as json. With the
+/// `zstd` feature, the json is compressed with zstd. Snapshots saved without the feature
+/// can still be loaded with it.
+pub trait SnapshotStore: Send + Sync {
+    /// Returns the stored snapshot, or `None` if nothing has been saved yet.
+    fn load_snapshot(&self) -> BoxFuture<'_, Result<Option<Snapshot>, RoliError>>;
+
+    /// Replaces the stored snapshot.
+    fn save_snapshot<'a>(&'a self, snapshot: &'a Snapshot) -> BoxFuture<'a, Result<(), RoliError>>;
+}
+
+/// Somewhere to keep the id of the last item a stream emitted (such as the last
+/// seen sale or trade ad id), so that streams running in different instances of a bot do not
+/// emit the same items.
+///
+/// Implemented for every [`StateStore`], which stores each cursor as a decimal
+/// string under `cursor:{key}`.
+pub trait CursorStore: Send + Sync {
+    /// Returns the last id seen under `key`, or `None` if nothing has been seen yet.
+    fn last_seen<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<u64>, RoliError>>;
+
+    /// Records `id` as seen under `key`. Ids lower than the current one are ignored,
+    /// so cursors only ever move forward.
+    fn set_last_seen<'a>(&'a self, key: &'a str, id: u64) -> BoxFuture<'a, Result<(), RoliError>>;
+
+    /// Sets the last id seen under `key` to `new` only if it is still `expected` (`None`
+    /// meaning that nothing has been seen yet), returning whether it was set.
+    ///
+    /// Streams use this to claim the ids between `expected` and `new`, so that only one
+    /// of the instances sharing the store emits them.
+    fn compare_and_swap_last_seen<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<u64>,
+        new: u64,
+    ) -> BoxFuture<'a, Result<bool, RoliError>>;
+}
+
+impl<T: StateStore> SnapshotStore for T {
+    fn load_snapshot(&self) -> BoxFuture<'_, Result<Option<Snapshot>, RoliError>> {
+        Box::pin(async move {
+            match self.get(SNAPSHOT_KEY).await? {
+                Some(bytes) => match serde_json::from_slice(&compression::decompress(&bytes)?) {
+                    Ok(x) => Ok(Some(x)),
+                    Err(_) => Err(RoliError::MalformedResponse),
+                },
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn save_snapshot<'a>(&'a self, snapshot: &'a Snapshot) -> BoxFuture<'a, Result<(), RoliError>> {
+        Box::pin(async move {
+            let json = match serde_json::to_vec(snapshot) {
+                Ok(x) => x,
+                Err(_) => return Err(RoliError::MalformedResponse),
+            };
+
+            self.set(SNAPSHOT_KEY, &compression::compress(&json)?).await
+        })
+    }
+}
+
+impl<T: StateStore> CursorStore for T {
+    fn last_seen<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<u64>, RoliError>> {
+        Box::pin(async move {
+            let value = self.get(&cursor_key(key)).await?;
+            value.as_deref().map(parse_cursor).transpose()
+        })
+    }
+
+    fn set_last_seen<'a>(&'a self, key: &'a str, id: u64) -> BoxFuture<'a, Result<(), RoliError>> {
+        Box::pin(async move {
+            let key = cursor_key(key);
+            let new = id.to_string();
+
+            loop {
+                let current = self.get(&key).await?;
+
+                if let Some(current) = current.as_deref() {
+                    if parse_cursor(current)? >= id {
+                        return Ok(());
+                    }
+                }
+
+                if self
+                    .compare_and_swap(&key, current.as_deref(), new.as_bytes())
+                    .await?
+                {
+                    return Ok(());
+                }
+            }
+        })
+    }
+
+    fn compare_and_swap_last_seen<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<u64>,
+        new: u64,
+    ) -> BoxFuture<'a, Result<bool, RoliError>> {
+        Box::pin(async move {
+            let key = cursor_key(key);
+            let current = self.get(&key).await?;
+
+            if current.as_deref().map(parse_cursor).transpose()? != expected {
+                return Ok(false);
+            }
+
+            self.compare_and_swap(&key, current.as_deref(), new.to_string().as_bytes())
+                .await
+        })
+    }
+}
+
+fn cursor_key(key: &str) -> String {
+    format!("cursor:{}", key)
+}
+
+fn parse_cursor(value: &[u8]) -> Result<u64, RoliError> {
+    std::str::from_utf8(value)
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .ok_or(RoliError::MalformedResponse)
+}
+
+/// A [`StateStore`] kept in memory.
+///
+/// This is what is used when no other state is provided. Clones share the same state,
+/// but nothing is shared with other processes or kept across restarts.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryState {
+    values: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+impl MemoryState {
+    /// Creates an empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn values(&self) -> std::sync::MutexGuard<'_, HashMap<String, Vec<u8>>> {
+        self.values.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl StateStore for MemoryState {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, RoliError>> {
+        Box::pin(future::ready(Ok(self.values().get(key).cloned())))
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: &'a [u8]) -> BoxFuture<'a, Result<(), RoliError>> {
+        self.values().insert(key.to_string(), value.to_vec());
+        Box::pin(future::ready(Ok(())))
+    }
+
+    fn compare_and_swap<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<&'a [u8]>,
+        new: &'a [u8],
+    ) -> BoxFuture<'a, Result<bool, RoliError>> {
+        let mut values = self.values();
+
+        let swapped = values.get(key).map(Vec::as_slice) == expected;
+
+        if swapped {
+            values.insert(key.to_string(), new.to_vec());
+        }
+
+        Box::pin(future::ready(Ok(swapped)))
+    }
+}
+
+/// A [`StateStore`] that keeps each key in a file inside of a directory, so that
+/// state survives restarts.
+///
+/// [`StateStore::compare_and_swap`] is only atomic between users of the same
+/// `FileState` (and its clones), so a directory should not be shared between
+/// processes. Files are written to a temporary file and renamed into place, so a
+/// crash never leaves a half written value behind.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::state::FileState;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let state = FileState::open("roli-state")?;
+///
+/// // Picks up where the last run left off instead of emitting old sales again.
+/// let client = roli::ClientBuilder::new().build();
+/// let sales = roli::streams::sale_stream(client, Duration::from_secs(60), state);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FileState {
+    directory: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl FileState {
+    /// Opens a state in `directory`, creating the directory if it does not exist.
+    pub fn open(directory: impl AsRef<Path>) -> Result<Self, RoliError> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory).map_err(RoliError::IoError)?;
+
+        Ok(Self {
+            directory,
+            lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Returns the path of the file `key` is kept in. Characters that are not safe
+    /// in file names are percent encoded.
+    fn path(&self, key: &str) -> PathBuf {
+        let mut file_name = String::with_capacity(key.len());
+
+        for byte in key.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+                file_name.push(byte as char);
+            } else {
+                file_name.push_str(&format!("%{:02X}", byte));
+            }
+        }
+
+        self.directory.join(file_name)
+    }
+
+    fn read(&self, key: &str) -> Result<Option<Vec<u8>>, RoliError> {
+        match fs::read(self.path(key)) {
+            Ok(x) => Ok(Some(x)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(RoliError::IoError(e)),
+        }
+    }
+
+    fn write(&self, key: &str, value: &[u8]) -> Result<(), RoliError> {
+        let path = self.path(key);
+        let temporary_path = path.with_extension("tmp");
+
+        fs::write(&temporary_path, value).map_err(RoliError::IoError)?;
+        fs::rename(&temporary_path, &path).map_err(RoliError::IoError)
+    }
+}
+
+impl StateStore for FileState {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, RoliError>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        Box::pin(future::ready(self.read(key)))
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: &'a [u8]) -> BoxFuture<'a, Result<(), RoliError>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        Box::pin(future::ready(self.write(key, value)))
+    }
+
+    fn compare_and_swap<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<&'a [u8]>,
+        new: &'a [u8],
+    ) -> BoxFuture<'a, Result<bool, RoliError>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let result = self.read(key).and_then(|current| {
+            if current.as_deref() != expected {
+                return Ok(false);
+            }
+
+            self.write(key, new).map(|_| true)
+        });
+
+        Box::pin(future::ready(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn check_state_store(state: impl StateStore) {
+        assert_eq!(state.load_snapshot().await.unwrap(), None);
+        let snapshot = Snapshot::new(100, vec![]);
+        state.save_snapshot(&snapshot).await.unwrap();
+        assert_eq!(state.load_snapshot().await.unwrap(), Some(snapshot));
+
+        assert_eq!(state.last_seen("sales").await.unwrap(), None);
+        state.set_last_seen("sales", 10).await.unwrap();
+        state.set_last_seen("sales", 5).await.unwrap();
+        assert_eq!(state.last_seen("sales").await.unwrap(), Some(10));
+        assert_eq!(state.last_seen("trade_ads").await.unwrap(), None);
+
+        assert!(!state
+            .compare_and_swap_last_seen("trade_ads", Some(5), 20)
+            .await
+            .unwrap());
+        assert!(state
+            .compare_and_swap_last_seen("trade_ads", None, 20)
+            .await
+            .unwrap());
+        assert_eq!(state.last_seen("trade_ads").await.unwrap(), Some(20));
+
+        assert!(!state.compare_and_swap("a", Some(b"x"), b"y").await.unwrap());
+        assert!(state.compare_and_swap("a", None, b"y").await.unwrap());
+        assert!(state.compare_and_swap("a", Some(b"y"), b"z").await.unwrap());
+        assert_eq!(state.get("a").await.unwrap(), Some(b"z".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_memory_state() {
+        check_state_store(MemoryState::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_state() {
+        let directory = std::env::temp_dir().join(format!("roli-state-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        check_state_store(FileState::open(&directory).unwrap()).await;
+
+        // Values survive reopening the directory.
+        let reopened = FileState::open(&directory).unwrap();
+        assert_eq!(reopened.last_seen("sales").await.unwrap(), Some(10));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+use crate::state::StateStore;
+use crate::RoliError;
+use futures_util::future::BoxFuture;
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Script};
+
+/// Sets `KEYS[1]` to `ARGV[3]` if it is unset and `ARGV[1]` is 0, or if it is equal
+/// to `ARGV[2]` and `ARGV[1]` is 1.
+const COMPARE_AND_SWAP_SCRIPT: &str = "
+local current = redis.call('GET', KEYS[1])
+local matches
+if ARGV[1] == '1' then
+    matches = current == ARGV[2]
+else
+    matches = not current
+end
+if matches then
+    redis.call('SET', KEYS[1], ARGV[3])
+    return 1
+end
+return 0
+";
+
+/// A [`StateStore`] backed by Redis, so that horizontally scaled bots share one item
+/// details snapshot and one set of stream cursors.
+///
+/// Every key is prefixed with `{prefix}:`, using the prefix given to [`RedisState::new`].
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::cache::ItemCacheBuilder;
+/// use roli::state::redis::RedisState;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let state = RedisState::connect("redis://127.0.0.1/", "roli").await?;
+///
+/// let client = roli::ClientBuilder::new().build();
+/// let cache = ItemCacheBuilder::new(client).set_state(state).build();
+///
+/// // Every instance with the same prefix shares this snapshot.
+/// let snapshot = cache.get().await?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RedisState {
+    connection: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisState {
+    /// Creates a state from an existing connection, with every key prefixed by `prefix`.
+    pub fn new(connection: ConnectionManager, prefix: &str) -> Self {
+        Self {
+            connection,
+            prefix: prefix.to_string(),
+        }
+    }
+
+    /// Connects to the Redis server at `url`, with every key prefixed by `prefix`.
+    pub async fn connect(url: &str, prefix: &str) -> Result<Self, RoliError> {
+        let client = redis::Client::open(url).map_err(RoliError::RedisError)?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(RoliError::RedisError)?;
+
+        Ok(Self::new(connection, prefix))
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}:{}", self.prefix, key)
+    }
+}
+
+impl std::fmt::Debug for RedisState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisState")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StateStore for RedisState {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>, RoliError>> {
+        Box::pin(async move {
+            let mut connection = self.connection.clone();
+
+            connection
+                .get(self.key(key))
+                .await
+                .map_err(RoliError::RedisError)
+        })
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: &'a [u8]) -> BoxFuture<'a, Result<(), RoliError>> {
+        Box::pin(async move {
+            let mut connection = self.connection.clone();
+
+            connection
+                .set::<_, _, ()>(self.key(key), value)
+                .await
+                .map_err(RoliError::RedisError)
+        })
+    }
+
+    fn compare_and_swap<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<&'a [u8]>,
+        new: &'a [u8],
+    ) -> BoxFuture<'a, Result<bool, RoliError>> {
+        Box::pin(async move {
+            let mut connection = self.connection.clone();
+
+            Script::new(COMPARE_AND_SWAP_SCRIPT)
+                .key(self.key(key))
+                .arg(expected.is_some() as u8)
+                .arg(expected.unwrap_or_default())
+                .arg(new)
+                .invoke_async::<bool>(&mut connection)
+                .await
+                .map_err(RoliError::RedisError)
+        })
+    }
+}
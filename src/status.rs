@@ -0,0 +1,409 @@
+use crate::{Client, Endpoint, RoliError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The health of every endpoint a [`Client`] has called, as returned by
+/// [`Client::status_report`].
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct StatusReport {
+    /// The unix timestamp the report was made at.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
+    pub generated_at: u64,
+    /// The status of each endpoint that has been called, in the order of [`Endpoint`].
+    pub endpoints: Vec<EndpointStatus>,
+}
+
+/// What a [`Client`] has observed of a single endpoint since it was built.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EndpointStatus {
+    /// The endpoint.
+    pub endpoint: Endpoint,
+    /// The amount of requests made to the endpoint.
+    pub requests: u64,
+    /// The amount of requests that returned an error.
+    pub errors: u64,
+    /// The amount of requests that were rate limited (status code 429).
+    pub rate_limited: u64,
+    /// The status code of the last response, or `None` if the last request failed
+    /// before a status code was known.
+    pub last_status: Option<u16>,
+    /// How long the last request took, in milliseconds.
+    pub last_latency_ms: u64,
+    /// The unix timestamp the last request finished at.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
+    pub last_request_at: u64,
+    /// The error returned by the last request, if it failed.
+    pub last_error: Option<String>,
+    /// The unix timestamp of the first request in the current run of maintenance
+    /// responses (see [`RoliError::is_maintenance`]), or `None` if the last request
+    /// was not one.
+    #[serde(default)]
+    pub maintenance_since: Option<u64>,
+    /// The size and timings of the last successful response, or `None` if there has
+    /// not been one.
+    #[serde(default)]
+    pub last_response: Option<ResponseMeta>,
+    /// The position of the last request among the requests to every endpoint, counting
+    /// from 1, which orders requests made within the same second.
+    #[serde(default)]
+    pub last_sequence: u64,
+}
+
+/// The size of a response and where the time fetching it went, as recorded in
+/// [`EndpointStatus::last_response`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct ResponseMeta {
+    /// The size of the response body in bytes.
+    pub bytes: u64,
+    /// How long it took to send the request and receive the whole body. This is
+    /// close to zero for requests coalesced into one already in flight.
+    pub http_time: Duration,
+    /// How long it took to parse the body as json, not counting the conversion into
+    /// this crate's types afterwards.
+    pub parse_time: Duration,
+}
+
+/// The overall state of Rolimons as seen by a [`Client`], returned by
+/// [`Client::service_status`].
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum ServiceStatus {
+    /// No requests have been made yet.
+    #[default]
+    Unknown,
+    /// The last request to every endpoint succeeded.
+    Operational,
+    /// The last request to some endpoint failed, but not because of maintenance.
+    Degraded,
+    /// The most recent request returned a maintenance response.
+    Maintenance {
+        /// The unix timestamp maintenance responses started at.
+        since: u64,
+    },
+}
+
+/// Keeps the status of each endpoint, shared between clones of a [`Client`].
+#[derive(Debug, Default)]
+pub(crate) struct StatusTracker {
+    endpoints: Mutex<BTreeMap<Endpoint, EndpointStatus>>,
+    sequence: AtomicU64,
+}
+
+impl StatusReport {
+    /// Returns the status of `endpoint`, or `None` if it has not been called.
+    pub fn get(&self, endpoint: Endpoint) -> Option<&EndpointStatus> {
+        self.endpoints
+            .iter()
+            .find(|status| status.endpoint == endpoint)
+    }
+
+    /// Returns whether the last request to every endpoint succeeded.
+    pub fn is_healthy(&self) -> bool {
+        self.endpoints
+            .iter()
+            .all(|status| status.last_error.is_none())
+    }
+
+    /// Returns the overall [`ServiceStatus`], judged by the most recent request.
+    pub fn service_status(&self) -> ServiceStatus {
+        let Some(latest) = self
+            .endpoints
+            .iter()
+            .max_by_key(|status| status.last_sequence)
+        else {
+            return ServiceStatus::Unknown;
+        };
+
+        match latest.maintenance_since {
+            Some(since) => ServiceStatus::Maintenance { since },
+            None if self.is_healthy() => ServiceStatus::Operational,
+            None => ServiceStatus::Degraded,
+        }
+    }
+}
+
+impl EndpointStatus {
+    /// Returns the percentage of requests that were rate limited.
+    pub fn rate_limited_percent(&self) -> f64 {
+        match self.requests {
+            0 => 0.0,
+            requests => self.rate_limited as f64 / requests as f64 * 100.0,
+        }
+    }
+
+    /// Returns the percentage of requests that returned an error.
+    pub fn error_percent(&self) -> f64 {
+        match self.requests {
+            0 => 0.0,
+            requests => self.errors as f64 / requests as f64 * 100.0,
+        }
+    }
+}
+
+impl StatusTracker {
+    /// Records a finished request, with the status code of its response if one was
+    /// received.
+    pub(crate) fn record<T>(
+        &self,
+        endpoint: Endpoint,
+        duration: Duration,
+        status_code: Option<u16>,
+        result: &Result<T, RoliError>,
+    ) {
+        let mut endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let status = entry(&mut endpoints, endpoint);
+
+        status.requests += 1;
+        status.last_latency_ms = duration.as_millis() as u64;
+        status.last_request_at = crate::unix_timestamp();
+        status.last_sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        status.last_status = status_code.or_else(|| error_status_code(result));
+        status.last_error = result.as_ref().err().map(|e| e.to_string());
+
+        status.maintenance_since = match result {
+            Err(e) if e.is_maintenance() => {
+                status.maintenance_since.or(Some(status.last_request_at))
+            }
+            _ => None,
+        };
+
+        if result.is_err() {
+            status.errors += 1;
+        }
+
+        if status.last_status == Some(429) {
+            status.rate_limited += 1;
+        }
+    }
+
+    pub(crate) fn record_response(&self, endpoint: Endpoint, response: ResponseMeta) {
+        let mut endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        entry(&mut endpoints, endpoint).last_response = Some(response);
+    }
+
+    pub(crate) fn report(&self) -> StatusReport {
+        StatusReport {
+            generated_at: crate::unix_timestamp(),
+            endpoints: self
+                .endpoints
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .values()
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl Client {
+    /// Returns the status, latency, and rate limit rate observed for each endpoint
+    /// this client (or any of its clones) has called.
+    ///
+    /// The report is serializable, so it can be exposed as is on a health endpoint.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let _ = client.recent_sales().await;
+    ///
+    /// let report = client.status_report();
+    /// println!("{}", serde_json::to_string_pretty(&report)?);
+    ///
+    /// for status in &report.endpoints {
+    ///     println!("{}: {:.1}% rate limited", status.endpoint, status.rate_limited_percent());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn status_report(&self) -> StatusReport {
+        self.status.report()
+    }
+
+    /// Returns whether Rolimons looks operational, degraded, or down for maintenance,
+    /// judging by the requests this client (or any of its clones) has made.
+    ///
+    /// Rolimons has no status endpoint, so this does not make a request.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use roli::status::ServiceStatus;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let _ = client.recent_sales().await;
+    ///
+    /// if let ServiceStatus::Maintenance { since } = client.service_status() {
+    ///     println!("Rolimons has been down for maintenance since {}", since);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn service_status(&self) -> ServiceStatus {
+        self.status.report().service_status()
+    }
+}
+
+fn entry(
+    endpoints: &mut BTreeMap<Endpoint, EndpointStatus>,
+    endpoint: Endpoint,
+) -> &mut EndpointStatus {
+    endpoints.entry(endpoint).or_insert_with(|| EndpointStatus {
+        endpoint,
+        requests: 0,
+        errors: 0,
+        rate_limited: 0,
+        last_status: None,
+        last_latency_ms: 0,
+        last_request_at: 0,
+        last_error: None,
+        maintenance_since: None,
+        last_response: None,
+        last_sequence: 0,
+    })
+}
+
+/// Returns the status code carried by an error, for requests whose response status
+/// was not passed to [`StatusTracker::record`].
+fn error_status_code<T>(result: &Result<T, RoliError>) -> Option<u16> {
+    match result {
+        Err(RoliError::ReqwestError(e)) => e.status().map(|x| x.as_u16()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_report() {
+        let tracker = StatusTracker::default();
+        let latency = Duration::from_millis(120);
+
+        tracker.record(Endpoint::RecentSales, latency, Some(200), &Ok(()));
+        tracker.record::<()>(
+            Endpoint::RecentSales,
+            latency,
+            Some(429),
+            &Err(RoliError::TooManyRequests),
+        );
+        tracker.record(Endpoint::AllItemDetails, latency, Some(200), &Ok(()));
+
+        let report = tracker.report();
+        assert_eq!(report.endpoints.len(), 2);
+        assert_eq!(report.endpoints[0].endpoint, Endpoint::AllItemDetails);
+        assert!(!report.is_healthy());
+
+        let sales = report.get(Endpoint::RecentSales).unwrap();
+        assert_eq!(sales.requests, 2);
+        assert_eq!(sales.last_status, Some(429));
+        assert_eq!(sales.last_latency_ms, 120);
+        assert_eq!(sales.rate_limited_percent(), 50.0);
+
+        // A 429 that surfaced as some other error still counts as rate limited.
+        tracker.record::<()>(
+            Endpoint::GamesList,
+            latency,
+            Some(429),
+            &Err(RoliError::UnidentifiedStatusCode(429)),
+        );
+        let report = tracker.report();
+        assert_eq!(report.get(Endpoint::GamesList).unwrap().rate_limited, 1);
+    }
+
+    #[test]
+    fn test_record_response() {
+        let tracker = StatusTracker::default();
+        let response = ResponseMeta {
+            bytes: 2048,
+            http_time: Duration::from_millis(80),
+            parse_time: Duration::from_millis(3),
+        };
+
+        tracker.record_response(Endpoint::AllItemDetails, response);
+        tracker.record(
+            Endpoint::AllItemDetails,
+            Duration::from_millis(90),
+            Some(200),
+            &Ok(()),
+        );
+
+        let status = tracker.report();
+        let status = status.get(Endpoint::AllItemDetails).unwrap();
+        assert_eq!(status.requests, 1);
+        assert_eq!(status.last_response, Some(response));
+    }
+
+    #[test]
+    fn test_service_status() {
+        let tracker = StatusTracker::default();
+        let latency = Duration::from_millis(120);
+        assert_eq!(tracker.report().service_status(), ServiceStatus::Unknown);
+
+        tracker.record(Endpoint::RecentSales, latency, Some(200), &Ok(()));
+        assert_eq!(
+            tracker.report().service_status(),
+            ServiceStatus::Operational
+        );
+
+        tracker.record::<()>(
+            Endpoint::RecentSales,
+            latency,
+            Some(200),
+            &Err(RoliError::UnexpectedHtmlResponse),
+        );
+        let report = tracker.report();
+        let sales = report.get(Endpoint::RecentSales).unwrap();
+        let since = sales.maintenance_since;
+        assert!(since.is_some());
+
+        // The maintenance page was still served with a 200.
+        assert_eq!(sales.last_status, Some(200));
+
+        tracker.record::<()>(
+            Endpoint::RecentSales,
+            latency,
+            Some(503),
+            &Err(RoliError::UnidentifiedStatusCode(503)),
+        );
+        assert_eq!(
+            tracker.report().service_status(),
+            ServiceStatus::Maintenance {
+                since: since.unwrap()
+            }
+        );
+
+        tracker.record::<()>(
+            Endpoint::RecentSales,
+            latency,
+            Some(500),
+            &Err(RoliError::InternalServerError),
+        );
+        assert_eq!(tracker.report().service_status(), ServiceStatus::Degraded);
+
+        // Judged by the most recent request even within the same second, regardless
+        // of the order of the endpoints.
+        tracker.record::<()>(
+            Endpoint::AllItemDetails,
+            latency,
+            Some(503),
+            &Err(RoliError::UnidentifiedStatusCode(503)),
+        );
+        assert!(matches!(
+            tracker.report().service_status(),
+            ServiceStatus::Maintenance { .. }
+        ));
+    }
+}
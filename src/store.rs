@@ -0,0 +1,758 @@
+use crate::items::{Demand, ItemDetails, ItemInfoProvider, Trend};
+use crate::market_activity::Sale;
+use crate::players::PlayerProfile;
+use crate::trade_ads::{Offer, Request, TradeAd};
+use crate::RoliError;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS item_snapshots (
+    timestamp INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    item_name TEXT NOT NULL,
+    acronym TEXT,
+    rap INTEGER NOT NULL,
+    valued INTEGER NOT NULL,
+    value INTEGER NOT NULL,
+    demand INTEGER NOT NULL,
+    trend INTEGER NOT NULL,
+    projected INTEGER NOT NULL,
+    hyped INTEGER NOT NULL,
+    rare INTEGER NOT NULL,
+    PRIMARY KEY (item_id, timestamp)
+);
+
+CREATE TABLE IF NOT EXISTS sales (
+    sale_id INTEGER PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    old_rap INTEGER NOT NULL,
+    new_rap INTEGER NOT NULL,
+    sale_price INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS sales_item_id_timestamp ON sales (item_id, timestamp);
+
+CREATE TABLE IF NOT EXISTS trade_ads (
+    trade_id INTEGER PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    username TEXT NOT NULL,
+    offer TEXT NOT NULL,
+    request TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS trade_ads_timestamp ON trade_ads (timestamp);
+
+CREATE TABLE IF NOT EXISTS player_snapshots (
+    timestamp INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    value INTEGER NOT NULL,
+    rap INTEGER NOT NULL,
+    PRIMARY KEY (user_id, timestamp)
+);
+";
+
+/// A historical store for item snapshots, sales, and trade ads, backed by SQLite.
+///
+/// Everything is stored with its unix timestamp so that it can be queried
+/// back later (e.g. "what was this item's value last week?").
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::store::SqliteStore;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let store = SqliteStore::open("roli.db")?;
+///
+/// let all_item_details = client.all_item_details().await?;
+/// store.insert_item_snapshot(1700000000, &all_item_details)?;
+///
+/// let value = store.value_at(1365767, 1700000100)?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SqliteStore {
+    connection: Connection,
+}
+
+/// The details of an item as stored in one snapshot, returned by
+/// [`SqliteStore::item_history`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemSnapshot {
+    /// When the snapshot was taken.
+    pub timestamp: u64,
+    /// The details of the item at the time.
+    pub item: ItemDetails,
+}
+
+/// The worth of a player's inventory at one point in time, returned by
+/// [`SqliteStore::player_value_history`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct PlayerValueSnapshot {
+    /// When the snapshot was taken.
+    pub timestamp: u64,
+    /// The id of the player.
+    pub user_id: u64,
+    /// The total value of the inventory (see [`PlayerProfile::inventory_value`]).
+    pub value: u64,
+    /// The total rap of the inventory.
+    pub rap: u64,
+}
+
+impl SqliteStore {
+    /// Opens (or creates) a store at the given path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RoliError> {
+        let connection = Connection::open(path).map_err(RoliError::SqliteError)?;
+        Self::from_connection(connection)
+    }
+
+    /// Opens a store that only lives in memory. Mostly useful for testing.
+    pub fn open_in_memory() -> Result<Self, RoliError> {
+        let connection = Connection::open_in_memory().map_err(RoliError::SqliteError)?;
+        Self::from_connection(connection)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, RoliError> {
+        connection
+            .execute_batch(SCHEMA)
+            .map_err(RoliError::SqliteError)?;
+
+        Ok(Self { connection })
+    }
+
+    /// Stores a snapshot of item details taken at the given unix timestamp.
+    ///
+    /// Storing the same item twice at the same timestamp replaces the older row.
+    pub fn insert_item_snapshot(
+        &self,
+        timestamp: u64,
+        items: &[ItemDetails],
+    ) -> Result<(), RoliError> {
+        let transaction = self
+            .connection
+            .unchecked_transaction()
+            .map_err(RoliError::SqliteError)?;
+
+        {
+            let mut statement = transaction
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO item_snapshots (timestamp, item_id, item_name, acronym, rap, valued, value, demand, trend, projected, hyped, rare)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                )
+                .map_err(RoliError::SqliteError)?;
+
+            for item in items {
+                statement
+                    .execute(params![
+                        timestamp as i64,
+                        item.item_id as i64,
+                        item.item_name,
+                        item.acronym,
+                        item.rap as i64,
+                        item.valued,
+                        item.value as i64,
+                        demand_to_code(item.demand),
+                        trend_to_code(item.trend),
+                        item.projected,
+                        item.hyped,
+                        item.rare,
+                    ])
+                    .map_err(RoliError::SqliteError)?;
+            }
+        }
+
+        transaction.commit().map_err(RoliError::SqliteError)
+    }
+
+    /// Stores sales. Sales that are already stored (by sale id) are ignored.
+    pub fn insert_sales(&self, sales: &[Sale]) -> Result<(), RoliError> {
+        let transaction = self
+            .connection
+            .unchecked_transaction()
+            .map_err(RoliError::SqliteError)?;
+
+        {
+            let mut statement = transaction
+                .prepare_cached(
+                    "INSERT OR IGNORE INTO sales (sale_id, timestamp, item_id, old_rap, new_rap, sale_price)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )
+                .map_err(RoliError::SqliteError)?;
+
+            for sale in sales {
+                statement
+                    .execute(params![
+                        sale.sale_id as i64,
+                        sale.timestamp as i64,
+                        sale.item_id as i64,
+                        sale.old_rap as i64,
+                        sale.new_rap as i64,
+                        sale.sale_price as i64,
+                    ])
+                    .map_err(RoliError::SqliteError)?;
+            }
+        }
+
+        transaction.commit().map_err(RoliError::SqliteError)
+    }
+
+    /// Stores trade ads. Trade ads that are already stored (by trade id) are ignored.
+    pub fn insert_trade_ads(&self, trade_ads: &[TradeAd]) -> Result<(), RoliError> {
+        let transaction = self
+            .connection
+            .unchecked_transaction()
+            .map_err(RoliError::SqliteError)?;
+
+        {
+            let mut statement = transaction
+                .prepare_cached(
+                    "INSERT OR IGNORE INTO trade_ads (trade_id, timestamp, user_id, username, offer, request)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )
+                .map_err(RoliError::SqliteError)?;
+
+            for trade_ad in trade_ads {
+                // These can only fail on non-string map keys, which these structs do not have.
+                let offer = serde_json::to_string(&trade_ad.offer)
+                    .map_err(|_| RoliError::MalformedResponse)?;
+                let request = serde_json::to_string(&trade_ad.request)
+                    .map_err(|_| RoliError::MalformedResponse)?;
+
+                statement
+                    .execute(params![
+                        trade_ad.trade_id as i64,
+                        trade_ad.timestamp as i64,
+                        trade_ad.user_id as i64,
+                        trade_ad.username,
+                        offer,
+                        request,
+                    ])
+                    .map_err(RoliError::SqliteError)?;
+            }
+        }
+
+        transaction.commit().map_err(RoliError::SqliteError)
+    }
+
+    /// Stores the value and rap of a player's inventory at the given unix timestamp,
+    /// looking up every item in `items`. Items `items` does not know are not counted.
+    ///
+    /// Storing the same player twice at the same timestamp replaces the older row.
+    pub fn insert_player_snapshot(
+        &self,
+        timestamp: u64,
+        profile: &PlayerProfile,
+        items: &impl ItemInfoProvider,
+    ) -> Result<(), RoliError> {
+        let rap = profile
+            .inventory
+            .iter()
+            .filter_map(|asset| {
+                let item = items.item_details(asset.item_id)?;
+                Some(item.rap * asset.uaids.len() as u64)
+            })
+            .sum::<u64>();
+
+        self.connection
+            .prepare_cached(
+                "INSERT OR REPLACE INTO player_snapshots (timestamp, user_id, value, rap)
+                VALUES (?1, ?2, ?3, ?4)",
+            )
+            .and_then(|mut statement| {
+                statement.execute(params![
+                    timestamp as i64,
+                    profile.user_id as i64,
+                    profile.inventory_value(items) as i64,
+                    rap as i64,
+                ])
+            })
+            .map(|_| ())
+            .map_err(RoliError::SqliteError)
+    }
+
+    /// Returns the details of an item from the latest snapshot taken at or before `timestamp`.
+    ///
+    /// Returns `None` if no snapshot of the item exists before `timestamp`.
+    pub fn item_at(&self, item_id: u64, timestamp: u64) -> Result<Option<ItemDetails>, RoliError> {
+        self.connection
+            .query_row(
+                "SELECT item_id, item_name, acronym, rap, valued, value, demand, trend, projected, hyped, rare
+                FROM item_snapshots
+                WHERE item_id = ?1 AND timestamp <= ?2
+                ORDER BY timestamp DESC
+                LIMIT 1",
+                params![item_id as i64, timestamp as i64],
+                item_details_from_row,
+            )
+            .optional()
+            .map_err(RoliError::SqliteError)
+    }
+
+    /// Returns the value of an item from the latest snapshot taken at or before `timestamp`.
+    ///
+    /// Returns `None` if no snapshot of the item exists before `timestamp`, or if
+    /// the item was not valued at the time.
+    pub fn value_at(&self, item_id: u64, timestamp: u64) -> Result<Option<u64>, RoliError> {
+        let item = self.item_at(item_id, timestamp)?;
+
+        Ok(item.filter(|item| item.valued).map(|item| item.value))
+    }
+
+    /// Returns the latest details of every item whose first snapshot was taken at or after
+    /// `timestamp`, sorted by item id. This is the store backed version of
+    /// [`items::added_since`](crate::items::added_since).
+    ///
+    /// Returns nothing if no snapshot was taken before `timestamp`, as every item would
+    /// otherwise look new.
+    pub fn items_added_since(&self, timestamp: u64) -> Result<Vec<ItemDetails>, RoliError> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT s.item_id, s.item_name, s.acronym, s.rap, s.valued, s.value, s.demand, s.trend, s.projected, s.hyped, s.rare
+                FROM item_snapshots s
+                JOIN (
+                    SELECT item_id, MAX(timestamp) AS latest
+                    FROM item_snapshots
+                    GROUP BY item_id
+                    HAVING MIN(timestamp) >= ?1
+                ) a ON s.item_id = a.item_id AND s.timestamp = a.latest
+                WHERE EXISTS (SELECT 1 FROM item_snapshots WHERE timestamp < ?1)
+                ORDER BY s.item_id",
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        let rows = statement
+            .query_map(params![timestamp as i64], item_details_from_row)
+            .map_err(RoliError::SqliteError)?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(RoliError::SqliteError)
+    }
+
+    /// Returns every stored snapshot of an item taken in `range`, oldest first.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use roli::store::SqliteStore;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let store = SqliteStore::open("roli.db")?;
+    ///
+    /// for snapshot in store.item_history(1365767, 1700000000..1700604800)? {
+    ///     println!("{}: {}", snapshot.timestamp, snapshot.item.value_or_rap());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn item_history(
+        &self,
+        item_id: u64,
+        range: Range<u64>,
+    ) -> Result<Vec<ItemSnapshot>, RoliError> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT item_id, item_name, acronym, rap, valued, value, demand, trend, projected, hyped, rare, timestamp
+                FROM item_snapshots
+                WHERE item_id = ?1 AND timestamp >= ?2 AND timestamp < ?3
+                ORDER BY timestamp",
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        let rows = statement
+            .query_map(
+                params![item_id as i64, range.start as i64, range.end as i64],
+                |row| {
+                    Ok(ItemSnapshot {
+                        timestamp: row.get::<_, i64>(11)? as u64,
+                        item: item_details_from_row(row)?,
+                    })
+                },
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(RoliError::SqliteError)
+    }
+
+    /// Returns every stored snapshot of a player's inventory worth taken in `range`,
+    /// oldest first. Snapshots are stored with [`SqliteStore::insert_player_snapshot`].
+    pub fn player_value_history(
+        &self,
+        user_id: u64,
+        range: Range<u64>,
+    ) -> Result<Vec<PlayerValueSnapshot>, RoliError> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT timestamp, user_id, value, rap
+                FROM player_snapshots
+                WHERE user_id = ?1 AND timestamp >= ?2 AND timestamp < ?3
+                ORDER BY timestamp",
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        let rows = statement
+            .query_map(
+                params![user_id as i64, range.start as i64, range.end as i64],
+                |row| {
+                    Ok(PlayerValueSnapshot {
+                        timestamp: row.get::<_, i64>(0)? as u64,
+                        user_id: row.get::<_, i64>(1)? as u64,
+                        value: row.get::<_, i64>(2)? as u64,
+                        rap: row.get::<_, i64>(3)? as u64,
+                    })
+                },
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(RoliError::SqliteError)
+    }
+
+    /// Returns all sales of an item with a timestamp in `start..end`, oldest first.
+    pub fn item_sales(&self, item_id: u64, start: u64, end: u64) -> Result<Vec<Sale>, RoliError> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT sale_id, timestamp, item_id, old_rap, new_rap, sale_price
+                FROM sales
+                WHERE item_id = ?1 AND timestamp >= ?2 AND timestamp < ?3
+                ORDER BY timestamp, sale_id",
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        let rows = statement
+            .query_map(
+                params![item_id as i64, start as i64, end as i64],
+                sale_from_row,
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(RoliError::SqliteError)
+    }
+
+    /// Returns all sales with a timestamp in `start..end`, oldest first.
+    pub fn sales_between(&self, start: u64, end: u64) -> Result<Vec<Sale>, RoliError> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT sale_id, timestamp, item_id, old_rap, new_rap, sale_price
+                FROM sales
+                WHERE timestamp >= ?1 AND timestamp < ?2
+                ORDER BY timestamp, sale_id",
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        let rows = statement
+            .query_map(params![start as i64, end as i64], sale_from_row)
+            .map_err(RoliError::SqliteError)?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(RoliError::SqliteError)
+    }
+
+    /// Returns all trade ads with a timestamp in `start..end`, oldest first.
+    pub fn trade_ads_between(&self, start: u64, end: u64) -> Result<Vec<TradeAd>, RoliError> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT trade_id, timestamp, user_id, username, offer, request
+                FROM trade_ads
+                WHERE timestamp >= ?1 AND timestamp < ?2
+                ORDER BY timestamp, trade_id",
+            )
+            .map_err(RoliError::SqliteError)?;
+
+        let rows = statement
+            .query_map(params![start as i64, end as i64], |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    row.get::<_, i64>(1)? as u64,
+                    row.get::<_, i64>(2)? as u64,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })
+            .map_err(RoliError::SqliteError)?;
+
+        let mut trade_ads = Vec::new();
+
+        for row in rows {
+            let (trade_id, timestamp, user_id, username, offer, request) =
+                row.map_err(RoliError::SqliteError)?;
+
+            let offer =
+                serde_json::from_str::<Offer>(&offer).map_err(|_| RoliError::MalformedResponse)?;
+            let request = serde_json::from_str::<Request>(&request)
+                .map_err(|_| RoliError::MalformedResponse)?;
+
+            trade_ads.push(TradeAd {
+                trade_id,
+                timestamp,
+                user_id,
+                username,
+                offer,
+                request,
+            });
+        }
+
+        Ok(trade_ads)
+    }
+}
+
+fn item_details_from_row(row: &Row) -> rusqlite::Result<ItemDetails> {
+    Ok(ItemDetails {
+        item_id: row.get::<_, i64>(0)? as u64,
+        item_name: row.get(1)?,
+        acronym: row.get(2)?,
+        rap: row.get::<_, i64>(3)? as u64,
+        valued: row.get(4)?,
+        value: row.get::<_, i64>(5)? as u64,
+        demand: demand_from_code(row.get(6)?),
+        trend: trend_from_code(row.get(7)?),
+        projected: row.get(8)?,
+        hyped: row.get(9)?,
+        rare: row.get(10)?,
+    })
+}
+
+fn sale_from_row(row: &Row) -> rusqlite::Result<Sale> {
+    Ok(Sale {
+        sale_id: row.get::<_, i64>(0)? as u64,
+        timestamp: row.get::<_, i64>(1)? as u64,
+        item_id: row.get::<_, i64>(2)? as u64,
+        old_rap: row.get::<_, i64>(3)? as u64,
+        new_rap: row.get::<_, i64>(4)? as u64,
+        sale_price: row.get::<_, i64>(5)? as u64,
+    })
+}
+
+// Demand and trend are stored using the same codes Rolimons uses in the item details api.
+
+fn demand_to_code(demand: Demand) -> i64 {
+    match demand {
+        Demand::Unassigned => -1,
+        Demand::Terrible => 0,
+        Demand::Low => 1,
+        Demand::Normal => 2,
+        Demand::High => 3,
+        Demand::Amazing => 4,
+    }
+}
+
+fn demand_from_code(code: i64) -> Demand {
+    match code {
+        0 => Demand::Terrible,
+        1 => Demand::Low,
+        2 => Demand::Normal,
+        3 => Demand::High,
+        4 => Demand::Amazing,
+        _ => Demand::Unassigned,
+    }
+}
+
+fn trend_to_code(trend: Trend) -> i64 {
+    match trend {
+        Trend::Unassigned => -1,
+        Trend::Lowering => 0,
+        Trend::Unstable => 1,
+        Trend::Stable => 2,
+        Trend::Raising => 3,
+        Trend::Fluctuating => 4,
+    }
+}
+
+fn trend_from_code(code: i64) -> Trend {
+    match code {
+        0 => Trend::Lowering,
+        1 => Trend::Unstable,
+        2 => Trend::Stable,
+        3 => Trend::Raising,
+        4 => Trend::Fluctuating,
+        _ => Trend::Unassigned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trade_ads::RequestTag;
+
+    fn item(value: u64) -> ItemDetails {
+        ItemDetails {
+            item_id: 1,
+            item_name: "Test item name".to_string(),
+            acronym: Some("TI".to_string()),
+            rap: 100,
+            valued: true,
+            value,
+            demand: Demand::High,
+            trend: Trend::Stable,
+            projected: false,
+            hyped: true,
+            rare: false,
+        }
+    }
+
+    #[test]
+    fn test_value_at() {
+        let store = SqliteStore::open_in_memory().unwrap();
+
+        store.insert_item_snapshot(100, &[item(1000)]).unwrap();
+        store.insert_item_snapshot(200, &[item(2000)]).unwrap();
+
+        assert_eq!(store.value_at(1, 50).unwrap(), None);
+        assert_eq!(store.value_at(1, 100).unwrap(), Some(1000));
+        assert_eq!(store.value_at(1, 199).unwrap(), Some(1000));
+        assert_eq!(store.value_at(1, 500).unwrap(), Some(2000));
+        assert_eq!(store.value_at(2, 500).unwrap(), None);
+        assert_eq!(store.item_at(1, 150).unwrap(), Some(item(1000)));
+    }
+
+    #[test]
+    fn test_item_history() {
+        let store = SqliteStore::open_in_memory().unwrap();
+
+        store.insert_item_snapshot(100, &[item(1000)]).unwrap();
+        store.insert_item_snapshot(200, &[item(2000)]).unwrap();
+        store.insert_item_snapshot(300, &[item(3000)]).unwrap();
+
+        let history = store.item_history(1, 100..300).unwrap();
+        assert_eq!(
+            history,
+            vec![
+                ItemSnapshot {
+                    timestamp: 100,
+                    item: item(1000)
+                },
+                ItemSnapshot {
+                    timestamp: 200,
+                    item: item(2000)
+                },
+            ]
+        );
+        assert!(store.item_history(2, 0..1000).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_player_value_history() {
+        use crate::items::ItemDetailsTable;
+        use crate::players::PlayerAsset;
+        use crate::testing::Faker;
+
+        let store = SqliteStore::open_in_memory().unwrap();
+        let table = ItemDetailsTable::new(vec![item(1000)]);
+
+        let mut profile = PlayerProfile {
+            user_id: 5,
+            inventory: vec![PlayerAsset {
+                item_id: 1,
+                uaids: vec![10, 11],
+            }],
+            ..Faker::new(1).player_profile()
+        };
+
+        store.insert_player_snapshot(100, &profile, &table).unwrap();
+        profile.inventory[0].uaids.pop();
+        store.insert_player_snapshot(200, &profile, &table).unwrap();
+
+        let history = store.player_value_history(5, 0..1000).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].value, history[0].rap), (2000, 200));
+        assert_eq!(history[1].value, 1000);
+        assert!(store.player_value_history(5, 150..200).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_items_added_since() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let new_item = ItemDetails {
+            item_id: 2,
+            ..item(500)
+        };
+
+        store.insert_item_snapshot(100, &[item(1000)]).unwrap();
+        store
+            .insert_item_snapshot(200, &[item(1000), new_item.clone()])
+            .unwrap();
+
+        assert_eq!(store.items_added_since(150).unwrap(), vec![new_item]);
+        assert!(store.items_added_since(100).unwrap().is_empty());
+        assert!(store.items_added_since(300).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sales() {
+        let store = SqliteStore::open_in_memory().unwrap();
+
+        let sales = vec![
+            Sale {
+                item_id: 1,
+                sale_id: 10,
+                timestamp: 100,
+                ..Default::default()
+            },
+            Sale {
+                item_id: 2,
+                sale_id: 11,
+                timestamp: 150,
+                ..Default::default()
+            },
+        ];
+
+        store.insert_sales(&sales).unwrap();
+        // Inserting the same sales again should not create duplicates.
+        store.insert_sales(&sales).unwrap();
+
+        assert_eq!(store.sales_between(0, 1000).unwrap(), sales);
+        assert_eq!(
+            store.sales_between(101, 1000).unwrap(),
+            vec![sales[1].clone()]
+        );
+        assert_eq!(
+            store.item_sales(1, 0, 1000).unwrap(),
+            vec![sales[0].clone()]
+        );
+    }
+
+    #[test]
+    fn test_trade_ads() {
+        let store = SqliteStore::open_in_memory().unwrap();
+
+        let trade_ad = TradeAd {
+            trade_id: 5,
+            timestamp: 100,
+            user_id: 2207291,
+            username: "Linkmon99".to_string(),
+            offer: Offer {
+                items: vec![1, 2],
+                robux: Some(500),
+            },
+            request: Request {
+                items: vec![3],
+                tags: vec![RequestTag::Any],
+            },
+        };
+
+        store
+            .insert_trade_ads(std::slice::from_ref(&trade_ad))
+            .unwrap();
+
+        assert_eq!(store.trade_ads_between(0, 1000).unwrap(), vec![trade_ad]);
+        assert!(store.trade_ads_between(101, 1000).unwrap().is_empty());
+    }
+}
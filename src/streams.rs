@@ -0,0 +1,1685 @@
+use crate::api::RoliApi;
+use crate::deals::Activity;
+use crate::groups::{self, GroupChange, GroupSearchResult};
+use crate::items::ItemDetails;
+use crate::leaderboard::{self, LeaderboardEntry, RankChange};
+use crate::limits::{self, MAINTENANCE_POLL_INTERVAL};
+use crate::market_activity::Sale;
+use crate::players::PresenceType;
+use crate::state::{CursorStore, MemoryState};
+use crate::trade_ads::TradeAd;
+use crate::{Client, Endpoint, RoliError};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+pub use crate::players::MIN_PLAYER_PROFILE_INTERVAL;
+
+/// Contains a supervisor that runs several streams as one and restarts failing ones.
+pub mod supervisor;
+
+/// The [`CursorStore`] key [`sale_stream`] keeps the last seen sale id under.
+pub const SALES_CURSOR: &str = "sales";
+
+/// The [`CursorStore`] key [`trade_ad_stream`] keeps the last seen trade ad id under.
+pub const TRADE_ADS_CURSOR: &str = "trade_ads";
+
+/// How long a polling stream waits between polls.
+///
+/// Every stream in this module takes its interval as `impl Into<Pacing>`, so a
+/// [`Duration`] can be passed for a fixed gap. [`Pacing::jittered`] picks a random
+/// gap within a range instead, which keeps many streams (or many instances of a bot)
+/// started at the same time from polling in lockstep.
+///
+/// # Example
+/// ```no_run
+/// use roli::streams::Pacing;
+/// use std::time::Duration;
+///
+/// let client = roli::ClientBuilder::new().build();
+/// let pacing = Pacing::jittered(Duration::from_secs(3), Duration::from_secs(5));
+/// let stream = client.trade_ad_stream(pacing);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pacing {
+    min: Duration,
+    max: Duration,
+}
+
+impl Pacing {
+    /// Waits exactly `interval` between polls.
+    pub fn fixed(interval: Duration) -> Self {
+        Self {
+            min: interval,
+            max: interval,
+        }
+    }
+
+    /// Waits a random gap between `min` and `max` (inclusive) between polls.
+    ///
+    /// The bounds are swapped if `min` is greater than `max`.
+    pub fn jittered(min: Duration, max: Duration) -> Self {
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+
+    /// Returns the shortest gap between polls.
+    pub fn min(&self) -> Duration {
+        self.min
+    }
+
+    /// Returns the longest gap between polls.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Raises both bounds to at least `floor`.
+    fn at_least(self, floor: Duration) -> Self {
+        Self {
+            min: self.min.max(floor),
+            max: self.max.max(floor),
+        }
+    }
+
+    /// Returns the gap to wait before the next poll.
+    pub(crate) fn next_gap(&self) -> Duration {
+        let span = (self.max - self.min).as_nanos() as u64;
+
+        if span == 0 {
+            return self.min;
+        }
+
+        // RandomState is randomly seeded, which is plenty for spreading out polls
+        // without pulling in a dependency.
+        let random = RandomState::new().build_hasher().finish();
+
+        self.min + Duration::from_nanos(random % (span + 1))
+    }
+}
+
+impl From<Duration> for Pacing {
+    fn from(interval: Duration) -> Self {
+        Self::fixed(interval)
+    }
+}
+
+/// What [`buffered`] does with a new item when its buffer is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum OverflowPolicy {
+    /// Waits for the consumer to make room, which pauses polling until it does.
+    #[default]
+    Block,
+    /// Drops the new item, keeping the oldest items.
+    DropNewest,
+    /// Drops the oldest item to make room, keeping the latest items.
+    KeepLatest,
+}
+
+/// The queue shared between the task driving a [`buffered`] stream and its consumer.
+struct Buffer<T> {
+    queue: Mutex<VecDeque<T>>,
+    capacity: usize,
+    finished: AtomicBool,
+    item_ready: Notify,
+    space_ready: Notify,
+}
+
+/// The consumer side of a [`buffered`] stream. Stops the driving task when dropped.
+struct BufferReceiver<T> {
+    buffer: Arc<Buffer<T>>,
+    task: JoinHandle<()>,
+}
+
+impl<T> Buffer<T> {
+    /// Adds `item` to the queue following `policy`, or returns it if the queue is full
+    /// and the policy is to wait.
+    fn push(&self, item: T, policy: OverflowPolicy) -> Option<T> {
+        let mut queue = self.queue();
+
+        if queue.len() < self.capacity {
+            queue.push_back(item);
+            return None;
+        }
+
+        match policy {
+            OverflowPolicy::Block => return Some(item),
+            OverflowPolicy::DropNewest => {}
+            OverflowPolicy::KeepLatest => {
+                queue.pop_front();
+                queue.push_back(item);
+            }
+        }
+
+        crate::metrics::record_stream_dropped();
+        None
+    }
+
+    fn queue(&self) -> MutexGuard<'_, VecDeque<T>> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T> Drop for BufferReceiver<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Drives `stream` on a spawned task into a buffer of at most `capacity` items, and
+/// returns a stream of the buffered items.
+///
+/// The streams in this module only poll when they are polled, so a slow consumer
+/// (such as a Discord sink waiting out a rate limit) delays polling and misses
+/// whatever falls out of the recent window in the meantime. Buffering keeps polling
+/// on schedule while the consumer catches up, and `policy` decides what happens once
+/// the consumer falls `capacity` items behind, so memory stays bounded in a
+/// long-running bot. A `capacity` of 0 is treated as 1.
+///
+/// Dropped items are counted in [`metrics::STREAM_DROPPED_TOTAL`](crate::metrics::STREAM_DROPPED_TOTAL)
+/// when the `metrics` feature is enabled. Dropping the returned stream stops the task.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use futures_util::StreamExt;
+/// use roli::streams::{self, OverflowPolicy};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let stream = streams::buffered(
+///     client.trade_ad_stream(Duration::from_secs(60)),
+///     100,
+///     OverflowPolicy::KeepLatest,
+/// );
+/// futures_util::pin_mut!(stream);
+///
+/// while let Some(trade_ad) = stream.next().await {
+///     let trade_ad = trade_ad?;
+///     println!("New trade ad: {}", trade_ad.trade_id);
+///     tokio::time::sleep(Duration::from_secs(1)).await;
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn buffered<S>(
+    stream: S,
+    capacity: usize,
+    policy: OverflowPolicy,
+) -> impl Stream<Item = S::Item> + Send
+where
+    S: Stream + Send + 'static,
+    S::Item: Send + 'static,
+{
+    let buffer = Arc::new(Buffer {
+        queue: Mutex::new(VecDeque::new()),
+        capacity: capacity.max(1),
+        finished: AtomicBool::new(false),
+        item_ready: Notify::new(),
+        space_ready: Notify::new(),
+    });
+
+    let task = tokio::spawn(fill_buffer(stream, buffer.clone(), policy));
+    let receiver = BufferReceiver { buffer, task };
+
+    stream::unfold(receiver, |receiver| async move {
+        loop {
+            let item = receiver.buffer.queue().pop_front();
+
+            if let Some(item) = item {
+                receiver.buffer.space_ready.notify_one();
+                return Some((item, receiver));
+            }
+
+            if receiver.buffer.finished.load(Ordering::Acquire) {
+                return None;
+            }
+
+            receiver.buffer.item_ready.notified().await;
+        }
+    })
+}
+
+/// Moves every item of `stream` into `buffer`, following `policy` when it is full.
+async fn fill_buffer<S: Stream>(stream: S, buffer: Arc<Buffer<S::Item>>, policy: OverflowPolicy) {
+    futures_util::pin_mut!(stream);
+
+    while let Some(item) = stream.next().await {
+        let mut pending = Some(item);
+
+        while let Some(item) = pending.take() {
+            pending = buffer.push(item, policy);
+
+            if pending.is_some() {
+                buffer.space_ready.notified().await;
+            }
+        }
+
+        buffer.item_ready.notify_one();
+    }
+
+    buffer.finished.store(true, Ordering::Release);
+    buffer.item_ready.notify_one();
+}
+
+/// A change in a player's presence, emitted by [`Client::player_presence_stream`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Copy)]
+pub struct PresenceChange {
+    /// The user id of the player.
+    pub user_id: u64,
+    /// The presence of the player before the change. This is `None` for the
+    /// first event of a stream.
+    pub previous: Option<PresenceType>,
+    /// The presence of the player after the change.
+    pub current: PresenceType,
+    /// Whether the player is currently online.
+    pub is_online: bool,
+    /// The unix timestamp of the player's last online status.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
+    pub last_online: u64,
+}
+
+/// An event from a polling stream, stamped with when this process received it.
+///
+/// The event's own timestamp is the one Rolimons recorded, which is when Rolimons
+/// saw the sale or trade ad. `received_at` is when the poll that returned it finished,
+/// so the gap between the two is the end to end latency of the feed.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct Received<T> {
+    /// The event itself.
+    pub event: T,
+    /// The local unix timestamp the event was received at.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
+    pub received_at: u64,
+}
+
+/// An event that carries the unix timestamp Rolimons recorded for it.
+pub trait Timestamped {
+    /// Returns the unix timestamp Rolimons recorded for the event.
+    fn timestamp(&self) -> u64;
+}
+
+impl Timestamped for Sale {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+impl Timestamped for TradeAd {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+impl Timestamped for Activity {
+    fn timestamp(&self) -> u64 {
+        match self {
+            Activity::PriceUpdate(x) => x.timestamp,
+            Activity::RapUpdate(x) => x.timestamp,
+            Activity::Other(x) => x.timestamp,
+        }
+    }
+}
+
+impl<T> Received<T> {
+    /// Stamps `event` with the current time.
+    pub fn now(event: T) -> Self {
+        Self {
+            event,
+            received_at: crate::unix_timestamp(),
+        }
+    }
+}
+
+impl<T: Timestamped> Received<T> {
+    /// Returns how long after Rolimons recorded the event it was received.
+    ///
+    /// This is 0 if the local clock is behind Rolimons'.
+    pub fn latency(&self) -> Duration {
+        Duration::from_secs(self.received_at.saturating_sub(self.event.timestamp()))
+    }
+}
+
+/// Tracks whether a polling stream is inside a maintenance window, so that it only
+/// yields the first maintenance error and polls less often until Rolimons is back.
+#[derive(Debug, Default)]
+struct Downtime {
+    ongoing: bool,
+}
+
+impl Downtime {
+    /// Returns whether `error` is a maintenance error inside a window that has already
+    /// been reported, marking the window as started otherwise.
+    fn is_ongoing(&mut self, error: &RoliError) -> bool {
+        if !error.is_maintenance() {
+            return false;
+        }
+
+        std::mem::replace(&mut self.ongoing, true)
+    }
+
+    /// Called after a successful poll.
+    fn end(&mut self) {
+        self.ongoing = false;
+    }
+
+    fn next_gap(&self, interval: &Pacing) -> Duration {
+        match self.ongoing {
+            true => interval.next_gap().max(MAINTENANCE_POLL_INTERVAL),
+            false => interval.next_gap(),
+        }
+    }
+}
+
+struct PresenceState<A> {
+    api: A,
+    user_id: u64,
+    interval: Pacing,
+    previous: Option<PresenceType>,
+    first_poll: bool,
+    downtime: Downtime,
+}
+
+struct TradeAdState<A, C> {
+    api: A,
+    cursors: C,
+    interval: Pacing,
+    pending: VecDeque<Received<TradeAd>>,
+    first_poll: bool,
+    downtime: Downtime,
+}
+
+struct SaleState<A, C> {
+    api: A,
+    cursors: C,
+    interval: Pacing,
+    pending: VecDeque<Received<Sale>>,
+    first_poll: bool,
+    downtime: Downtime,
+}
+
+struct GroupState<A> {
+    api: A,
+    group_name: String,
+    interval: Pacing,
+    known: HashMap<u64, GroupSearchResult>,
+    pending: VecDeque<GroupChange>,
+    first_poll: bool,
+    downtime: Downtime,
+}
+
+struct RankState<F> {
+    fetch_leaderboard: F,
+    interval: Pacing,
+    previous: Vec<LeaderboardEntry>,
+    pending: VecDeque<RankChange>,
+    first_poll: bool,
+    downtime: Downtime,
+}
+
+struct PageState<T, F> {
+    fetch_page: F,
+    next_page: Option<u64>,
+    pending: VecDeque<T>,
+}
+
+impl Client {
+    /// Polls [`Client::player_profile`] every `interval` and emits a [`PresenceChange`]
+    /// whenever the player's presence changes (e.g. Website -> InGame).
+    ///
+    /// The first item of the stream is always the player's current presence.
+    ///
+    /// Does not require authentication.
+    ///
+    /// # Warning
+    ///
+    /// This uses the player profile endpoint, which is heavy on Rolimons' servers. Because of
+    /// this, `interval` is raised to [`MIN_PLAYER_PROFILE_INTERVAL`] if it is lower.
+    ///
+    /// Errors are yielded as they happen and do not end the stream. While Rolimons is
+    /// down for maintenance (see [`RoliError::is_maintenance`]), only the first error is
+    /// yielded and polling slows to [`MAINTENANCE_POLL_INTERVAL`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use futures_util::StreamExt;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let stream = client.player_presence_stream(2207291, Duration::from_secs(60));
+    /// futures_util::pin_mut!(stream);
+    ///
+    /// while let Some(change) = stream.next().await {
+    ///     let change = change?;
+    ///     println!("{:?} -> {:?}", change.previous, change.current);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn player_presence_stream(
+        &self,
+        user_id: u64,
+        interval: impl Into<Pacing>,
+    ) -> impl Stream<Item = Result<PresenceChange, RoliError>> + Send {
+        player_presence_stream(self.clone(), user_id, interval)
+    }
+
+    /// The same as [`Client::player_presence_stream`], but returns
+    /// [`RoliError::InvalidConfig`] if `interval` is below [`MIN_PLAYER_PROFILE_INTERVAL`]
+    /// instead of raising it.
+    pub fn try_player_presence_stream(
+        &self,
+        user_id: u64,
+        interval: impl Into<Pacing>,
+    ) -> Result<impl Stream<Item = Result<PresenceChange, RoliError>> + Send, RoliError> {
+        try_player_presence_stream(self.clone(), user_id, interval)
+    }
+
+    /// Polls [`Client::recent_trade_ads`] every `interval` and emits every trade ad with
+    /// a higher trade id than the last one emitted, oldest first.
+    ///
+    /// The first poll emits every trade ad currently in the recent window. To share the
+    /// last seen trade id between instances of a bot, use [`trade_ad_stream`] with a
+    /// shared [`CursorStore`].
+    ///
+    /// Does not require authentication.
+    ///
+    /// Errors are yielded as they happen and do not end the stream. While Rolimons is
+    /// down for maintenance (see [`RoliError::is_maintenance`]), only the first error is
+    /// yielded and polling slows to [`MAINTENANCE_POLL_INTERVAL`].
+    ///
+    /// `interval` is raised to [`limits::MIN_POLL_INTERVAL`] if it is lower. Use
+    /// [`Client::try_trade_ad_stream`] to get an error instead.
+    pub fn trade_ad_stream(
+        &self,
+        interval: impl Into<Pacing>,
+    ) -> impl Stream<Item = Result<TradeAd, RoliError>> + Send {
+        trade_ad_stream(self.clone(), interval, MemoryState::new())
+    }
+
+    /// The same as [`Client::trade_ad_stream`], but returns [`RoliError::InvalidConfig`]
+    /// if `interval` is below [`limits::MIN_POLL_INTERVAL`] instead of raising it.
+    pub fn try_trade_ad_stream(
+        &self,
+        interval: impl Into<Pacing>,
+    ) -> Result<impl Stream<Item = Result<TradeAd, RoliError>> + Send, RoliError> {
+        try_trade_ad_stream(self.clone(), interval, MemoryState::new())
+    }
+
+    /// The same as [`Client::trade_ad_stream`], but each trade ad is stamped with when
+    /// it was received.
+    pub fn received_trade_ad_stream(
+        &self,
+        interval: impl Into<Pacing>,
+    ) -> impl Stream<Item = Result<Received<TradeAd>, RoliError>> + Send {
+        received_trade_ad_stream(self.clone(), interval, MemoryState::new())
+    }
+
+    /// Polls [`Client::recent_sales`] every `interval` and emits every sale with a
+    /// higher sale id than the last one emitted, oldest first.
+    ///
+    /// The first poll emits every sale currently in the recent window. To share the
+    /// last seen sale id between instances of a bot, use [`sale_stream`] with a
+    /// shared [`CursorStore`].
+    ///
+    /// Does not require authentication.
+    ///
+    /// Errors are yielded as they happen and do not end the stream. While Rolimons is
+    /// down for maintenance (see [`RoliError::is_maintenance`]), only the first error is
+    /// yielded and polling slows to [`MAINTENANCE_POLL_INTERVAL`].
+    ///
+    /// `interval` is raised to [`limits::MIN_POLL_INTERVAL`] if it is lower. Use
+    /// [`Client::try_sale_stream`] to get an error instead.
+    pub fn sale_stream(
+        &self,
+        interval: impl Into<Pacing>,
+    ) -> impl Stream<Item = Result<Sale, RoliError>> + Send {
+        sale_stream(self.clone(), interval, MemoryState::new())
+    }
+
+    /// The same as [`Client::sale_stream`], but returns [`RoliError::InvalidConfig`] if
+    /// `interval` is below [`limits::MIN_POLL_INTERVAL`] instead of raising it.
+    pub fn try_sale_stream(
+        &self,
+        interval: impl Into<Pacing>,
+    ) -> Result<impl Stream<Item = Result<Sale, RoliError>> + Send, RoliError> {
+        try_sale_stream(self.clone(), interval, MemoryState::new())
+    }
+
+    /// The same as [`Client::sale_stream`], but each sale is stamped with when it was
+    /// received.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use futures_util::StreamExt;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let stream = client.received_sale_stream(Duration::from_secs(3));
+    /// futures_util::pin_mut!(stream);
+    ///
+    /// while let Some(received) = stream.next().await {
+    ///     let received = received?;
+    ///     println!("{} seen {:?} after it sold", received.event.item_id, received.latency());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn received_sale_stream(
+        &self,
+        interval: impl Into<Pacing>,
+    ) -> impl Stream<Item = Result<Received<Sale>, RoliError>> + Send {
+        received_sale_stream(self.clone(), interval, MemoryState::new())
+    }
+
+    /// Emits every new trade ad whose request side contains `item`, either directly
+    /// or through a request tag the item satisfies.
+    ///
+    /// This is [`Client::trade_ad_stream`] filtered with [`TradeAd::requests_item`],
+    /// and is useful for "someone wants this item" alerts.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use futures_util::StreamExt;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let all_item_details = client.all_item_details().await?;
+    /// let item = all_item_details
+    ///     .into_iter()
+    ///     .find(|item| item.item_id == 1365767)
+    ///     .unwrap();
+    ///
+    /// let stream = client.requester_stream(item, Duration::from_secs(60));
+    /// futures_util::pin_mut!(stream);
+    ///
+    /// while let Some(trade_ad) = stream.next().await {
+    ///     println!("{} wants the item!", trade_ad?.username);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn requester_stream(
+        &self,
+        item: ItemDetails,
+        interval: impl Into<Pacing>,
+    ) -> impl Stream<Item = Result<TradeAd, RoliError>> + Send {
+        self.trade_ad_stream(interval).filter(move |result| {
+            let keep = match result {
+                Ok(trade_ad) => trade_ad.requests_item(&item),
+                Err(_) => true,
+            };
+
+            async move { keep }
+        })
+    }
+
+    /// The same as [`Client::requester_stream`], but returns
+    /// [`RoliError::InvalidConfig`] if `interval` is below [`limits::MIN_POLL_INTERVAL`]
+    /// instead of raising it.
+    pub fn try_requester_stream(
+        &self,
+        item: ItemDetails,
+        interval: impl Into<Pacing>,
+    ) -> Result<impl Stream<Item = Result<TradeAd, RoliError>> + Send, RoliError> {
+        let interval = checked(interval, Endpoint::RecentTradeAds)?;
+        Ok(self.requester_stream(item, interval))
+    }
+
+    /// Polls [`Client::group_search`] for `group_name` every `interval` and emits a
+    /// [`GroupChange`] whenever a group's member count changes.
+    ///
+    /// The first poll emits [`GroupChange::NewlyTracked`] for every group in the
+    /// results, as does any later poll that returns a group not seen before. Groups
+    /// that drop out of the results are remembered, so they are not reported as
+    /// newly tracked if they come back.
+    ///
+    /// Does not require authentication.
+    ///
+    /// Errors are yielded as they happen and do not end the stream. While Rolimons is
+    /// down for maintenance (see [`RoliError::is_maintenance`]), only the first error is
+    /// yielded and polling slows to [`MAINTENANCE_POLL_INTERVAL`].
+    ///
+    /// `interval` is raised to [`limits::MIN_POLL_INTERVAL`] if it is lower. Use
+    /// [`Client::try_group_change_stream`] to get an error instead.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// use futures_util::StreamExt;
+    /// use roli::groups::GroupChange;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let stream = client.group_change_stream("Tetra", Duration::from_secs(600));
+    /// futures_util::pin_mut!(stream);
+    ///
+    /// while let Some(change) = stream.next().await {
+    ///     if let GroupChange::MembersGained { group, .. } = change? {
+    ///         println!("{} now has {} members", group.name, group.member_count);
+    ///     }
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn group_change_stream(
+        &self,
+        group_name: &str,
+        interval: impl Into<Pacing>,
+    ) -> impl Stream<Item = Result<GroupChange, RoliError>> + Send {
+        group_change_stream(self.clone(), group_name, interval)
+    }
+
+    /// The same as [`Client::group_change_stream`], but returns
+    /// [`RoliError::InvalidConfig`] if `interval` is below [`limits::MIN_POLL_INTERVAL`]
+    /// instead of raising it.
+    pub fn try_group_change_stream(
+        &self,
+        group_name: &str,
+        interval: impl Into<Pacing>,
+    ) -> Result<impl Stream<Item = Result<GroupChange, RoliError>> + Send, RoliError> {
+        try_group_change_stream(self.clone(), group_name, interval)
+    }
+}
+
+/// Returns `interval` raised to the [`limits::min_poll_interval`] of `endpoint`.
+fn floored(interval: impl Into<Pacing>, endpoint: Endpoint) -> Pacing {
+    interval
+        .into()
+        .at_least(limits::min_poll_interval(endpoint))
+}
+
+/// Returns `interval`, or [`RoliError::InvalidConfig`] if it is below the
+/// [`limits::min_poll_interval`] of `endpoint`.
+fn checked(interval: impl Into<Pacing>, endpoint: Endpoint) -> Result<Pacing, RoliError> {
+    let interval = interval.into();
+    limits::check_poll_interval(endpoint, interval.min())?;
+
+    Ok(interval)
+}
+
+/// The same as [`Client::player_presence_stream`], but generic over any [`RoliApi`].
+///
+/// The returned stream is `Send`, so it can be moved into a spawned task.
+pub fn player_presence_stream<A: RoliApi + 'static>(
+    api: A,
+    user_id: u64,
+    interval: impl Into<Pacing>,
+) -> impl Stream<Item = Result<PresenceChange, RoliError>> + Send {
+    let state = PresenceState {
+        api,
+        user_id,
+        interval: floored(interval, Endpoint::PlayerProfile),
+        previous: None,
+        first_poll: true,
+        downtime: Downtime::default(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if !state.first_poll {
+                tokio::time::sleep(state.downtime.next_gap(&state.interval)).await;
+            }
+
+            state.first_poll = false;
+
+            let profile = match state.api.player_profile(state.user_id).await {
+                Ok(x) => {
+                    state.downtime.end();
+                    x
+                }
+                Err(e) if state.downtime.is_ongoing(&e) => continue,
+                Err(e) => return Some((Err(e), state)),
+            };
+
+            if state.previous == Some(profile.presence_type) {
+                continue;
+            }
+
+            let change = PresenceChange {
+                user_id: state.user_id,
+                previous: state.previous,
+                current: profile.presence_type,
+                is_online: profile.is_online,
+                last_online: profile.last_online,
+            };
+
+            state.previous = Some(profile.presence_type);
+
+            crate::metrics::record_stream_event("player_presence");
+
+            return Some((Ok(change), state));
+        }
+    })
+}
+
+/// The same as [`Client::try_player_presence_stream`], but generic over any [`RoliApi`].
+pub fn try_player_presence_stream<A: RoliApi + 'static>(
+    api: A,
+    user_id: u64,
+    interval: impl Into<Pacing>,
+) -> Result<impl Stream<Item = Result<PresenceChange, RoliError>> + Send, RoliError> {
+    let interval = checked(interval, Endpoint::PlayerProfile)?;
+    Ok(player_presence_stream(api, user_id, interval))
+}
+
+/// The same as [`Client::trade_ad_stream`], but generic over any [`RoliApi`], with the
+/// last seen trade id kept in `cursors` under [`TRADE_ADS_CURSOR`].
+///
+/// New trade ads are claimed through `cursors` the same way [`sale_stream`] claims
+/// sales, so streams in different instances that share `cursors` each emit a trade ad
+/// at most once between them, and a restarted stream does not emit trade ads again.
+///
+/// The returned stream is `Send`, so it can be moved into a spawned task.
+pub fn trade_ad_stream<A: RoliApi + 'static, C: CursorStore + 'static>(
+    api: A,
+    interval: impl Into<Pacing>,
+    cursors: C,
+) -> impl Stream<Item = Result<TradeAd, RoliError>> + Send {
+    received_trade_ad_stream(api, interval, cursors).map(|x| x.map(|received| received.event))
+}
+
+/// The same as [`trade_ad_stream`], but returns [`RoliError::InvalidConfig`] if
+/// `interval` is below [`limits::MIN_POLL_INTERVAL`] instead of raising it.
+pub fn try_trade_ad_stream<A: RoliApi + 'static, C: CursorStore + 'static>(
+    api: A,
+    interval: impl Into<Pacing>,
+    cursors: C,
+) -> Result<impl Stream<Item = Result<TradeAd, RoliError>> + Send, RoliError> {
+    let interval = checked(interval, Endpoint::RecentTradeAds)?;
+    Ok(trade_ad_stream(api, interval, cursors))
+}
+
+/// The same as [`received_trade_ad_stream`], but returns [`RoliError::InvalidConfig`]
+/// if `interval` is below [`limits::MIN_POLL_INTERVAL`] instead of raising it.
+pub fn try_received_trade_ad_stream<A: RoliApi + 'static, C: CursorStore + 'static>(
+    api: A,
+    interval: impl Into<Pacing>,
+    cursors: C,
+) -> Result<impl Stream<Item = Result<Received<TradeAd>, RoliError>> + Send, RoliError> {
+    let interval = checked(interval, Endpoint::RecentTradeAds)?;
+    Ok(received_trade_ad_stream(api, interval, cursors))
+}
+
+/// The same as [`trade_ad_stream`], but each trade ad is stamped with when it was
+/// received.
+///
+/// The returned stream is `Send`, so it can be moved into a spawned task.
+pub fn received_trade_ad_stream<A: RoliApi + 'static, C: CursorStore + 'static>(
+    api: A,
+    interval: impl Into<Pacing>,
+    cursors: C,
+) -> impl Stream<Item = Result<Received<TradeAd>, RoliError>> + Send {
+    let state = TradeAdState {
+        api,
+        cursors,
+        interval: floored(interval, Endpoint::RecentTradeAds),
+        pending: VecDeque::new(),
+        first_poll: true,
+        downtime: Downtime::default(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(trade_ad) = state.pending.pop_front() {
+                crate::metrics::record_stream_event("trade_ads");
+                return Some((Ok(trade_ad), state));
+            }
+
+            if !state.first_poll {
+                tokio::time::sleep(state.downtime.next_gap(&state.interval)).await;
+            }
+
+            state.first_poll = false;
+
+            let mut trade_ads = match state.api.recent_trade_ads().await {
+                Ok(x) => {
+                    state.downtime.end();
+                    x
+                }
+                Err(e) if state.downtime.is_ongoing(&e) => continue,
+                Err(e) => return Some((Err(e), state)),
+            };
+
+            let received_at = crate::unix_timestamp();
+
+            trade_ads.sort_by_key(|trade_ad| trade_ad.trade_id);
+
+            let trade_ads =
+                match claim_unseen(&state.cursors, TRADE_ADS_CURSOR, trade_ads, |trade_ad| {
+                    trade_ad.trade_id
+                })
+                .await
+                {
+                    Ok(x) => x,
+                    Err(e) => return Some((Err(e), state)),
+                };
+
+            state.pending.extend(
+                trade_ads
+                    .into_iter()
+                    .map(|event| Received { event, received_at }),
+            );
+        }
+    })
+}
+
+/// Returns the items in `sorted` (sorted by `id`) with a higher id than the one last
+/// seen under `key`, after claiming them by moving the cursor past them.
+///
+/// If another instance moves the cursor first, the cursor is re-read and only the items
+/// past it are claimed.
+async fn claim_unseen<C: CursorStore, T>(
+    cursors: &C,
+    key: &str,
+    mut sorted: Vec<T>,
+    id: impl Fn(&T) -> u64,
+) -> Result<Vec<T>, RoliError> {
+    loop {
+        let last_seen = cursors.last_seen(key).await?;
+        let start = match last_seen {
+            Some(last_seen) => sorted.partition_point(|item| id(item) <= last_seen),
+            None => 0,
+        };
+
+        let Some(newest) = sorted.last().map(&id) else {
+            return Ok(Vec::new());
+        };
+
+        if start == sorted.len() {
+            return Ok(Vec::new());
+        }
+
+        if cursors
+            .compare_and_swap_last_seen(key, last_seen, newest)
+            .await?
+        {
+            return Ok(sorted.split_off(start));
+        }
+    }
+}
+
+/// The same as [`Client::sale_stream`], but generic over any [`RoliApi`], with the
+/// last seen sale id kept in `cursors` under [`SALES_CURSOR`].
+///
+/// After each poll, the new sales are claimed by moving the cursor past them with
+/// [`CursorStore::compare_and_swap_last_seen`] before they are emitted, so streams in
+/// different instances that share `cursors` split the sales between them instead of
+/// each emitting all of them. Each sale is emitted by exactly one of the streams.
+///
+/// The returned stream is `Send`, so it can be moved into a spawned task.
+pub fn sale_stream<A: RoliApi + 'static, C: CursorStore + 'static>(
+    api: A,
+    interval: impl Into<Pacing>,
+    cursors: C,
+) -> impl Stream<Item = Result<Sale, RoliError>> + Send {
+    received_sale_stream(api, interval, cursors).map(|x| x.map(|received| received.event))
+}
+
+/// The same as [`sale_stream`], but returns [`RoliError::InvalidConfig`] if `interval`
+/// is below [`limits::MIN_POLL_INTERVAL`] instead of raising it.
+pub fn try_sale_stream<A: RoliApi + 'static, C: CursorStore + 'static>(
+    api: A,
+    interval: impl Into<Pacing>,
+    cursors: C,
+) -> Result<impl Stream<Item = Result<Sale, RoliError>> + Send, RoliError> {
+    let interval = checked(interval, Endpoint::RecentSales)?;
+    Ok(sale_stream(api, interval, cursors))
+}
+
+/// The same as [`received_sale_stream`], but returns [`RoliError::InvalidConfig`] if
+/// `interval` is below [`limits::MIN_POLL_INTERVAL`] instead of raising it.
+pub fn try_received_sale_stream<A: RoliApi + 'static, C: CursorStore + 'static>(
+    api: A,
+    interval: impl Into<Pacing>,
+    cursors: C,
+) -> Result<impl Stream<Item = Result<Received<Sale>, RoliError>> + Send, RoliError> {
+    let interval = checked(interval, Endpoint::RecentSales)?;
+    Ok(received_sale_stream(api, interval, cursors))
+}
+
+/// The same as [`sale_stream`], but each sale is stamped with when it was received.
+///
+/// The returned stream is `Send`, so it can be moved into a spawned task.
+pub fn received_sale_stream<A: RoliApi + 'static, C: CursorStore + 'static>(
+    api: A,
+    interval: impl Into<Pacing>,
+    cursors: C,
+) -> impl Stream<Item = Result<Received<Sale>, RoliError>> + Send {
+    let state = SaleState {
+        api,
+        cursors,
+        interval: floored(interval, Endpoint::RecentSales),
+        pending: VecDeque::new(),
+        first_poll: true,
+        downtime: Downtime::default(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(sale) = state.pending.pop_front() {
+                crate::metrics::record_stream_event("sales");
+                return Some((Ok(sale), state));
+            }
+
+            if !state.first_poll {
+                tokio::time::sleep(state.downtime.next_gap(&state.interval)).await;
+            }
+
+            state.first_poll = false;
+
+            let mut sales = match state.api.recent_sales().await {
+                Ok(x) => {
+                    state.downtime.end();
+                    x
+                }
+                Err(e) if state.downtime.is_ongoing(&e) => continue,
+                Err(e) => return Some((Err(e), state)),
+            };
+
+            let received_at = crate::unix_timestamp();
+
+            sales.sort_by_key(|sale| sale.sale_id);
+
+            let sales = match claim_unseen(&state.cursors, SALES_CURSOR, sales, |sale| sale.sale_id)
+                .await
+            {
+                Ok(x) => x,
+                Err(e) => return Some((Err(e), state)),
+            };
+
+            state.pending.extend(
+                sales
+                    .into_iter()
+                    .map(|event| Received { event, received_at }),
+            );
+        }
+    })
+}
+
+/// The same as [`Client::group_change_stream`], but generic over any [`RoliApi`].
+///
+/// The returned stream is `Send`, so it can be moved into a spawned task.
+pub fn group_change_stream<A: RoliApi + 'static>(
+    api: A,
+    group_name: &str,
+    interval: impl Into<Pacing>,
+) -> impl Stream<Item = Result<GroupChange, RoliError>> + Send {
+    let state = GroupState {
+        api,
+        group_name: group_name.to_string(),
+        interval: floored(interval, Endpoint::GroupSearch),
+        known: HashMap::new(),
+        pending: VecDeque::new(),
+        first_poll: true,
+        downtime: Downtime::default(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(change) = state.pending.pop_front() {
+                crate::metrics::record_stream_event("group_changes");
+                return Some((Ok(change), state));
+            }
+
+            if !state.first_poll {
+                tokio::time::sleep(state.downtime.next_gap(&state.interval)).await;
+            }
+
+            state.first_poll = false;
+
+            let groups = match state.api.group_search(&state.group_name).await {
+                Ok(x) => {
+                    state.downtime.end();
+                    x
+                }
+                Err(e) if state.downtime.is_ongoing(&e) => continue,
+                Err(e) => return Some((Err(e), state)),
+            };
+
+            let known = state.known.values().cloned().collect::<Vec<_>>();
+            state.pending.extend(groups::diff(&known, &groups));
+
+            for group in groups {
+                state.known.insert(group.id, group);
+            }
+        }
+    })
+}
+
+/// The same as [`group_change_stream`], but returns [`RoliError::InvalidConfig`] if
+/// `interval` is below [`limits::MIN_POLL_INTERVAL`] instead of raising it.
+pub fn try_group_change_stream<A: RoliApi + 'static>(
+    api: A,
+    group_name: &str,
+    interval: impl Into<Pacing>,
+) -> Result<impl Stream<Item = Result<GroupChange, RoliError>> + Send, RoliError> {
+    let interval = checked(interval, Endpoint::GroupSearch)?;
+    Ok(group_change_stream(api, group_name, interval))
+}
+
+/// Calls `fetch_leaderboard` every `interval` and emits a [`RankChange`] for every
+/// player whose rank changed since the previous call, as computed by [`leaderboard::diff`].
+///
+/// `fetch_leaderboard` should return the same pages of the leaderboard each time.
+/// Every player in the first response is emitted as [`RankChange::Entered`].
+///
+/// Errors are yielded as they happen and do not end the stream. While Rolimons is
+/// down for maintenance (see [`RoliError::is_maintenance`]), only the first error is
+/// yielded and polling slows to [`MAINTENANCE_POLL_INTERVAL`].
+///
+/// `interval` is raised to [`limits::MIN_POLL_INTERVAL`] if it is lower. Use
+/// [`try_rank_change_stream`] to get an error instead.
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use roli::leaderboard::LeaderboardEntry;
+/// use roli::RoliError;
+/// use std::time::Duration;
+///
+/// # async fn fetch_top_players() -> Result<Vec<LeaderboardEntry>, RoliError> { todo!() }
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let stream = roli::streams::rank_change_stream(fetch_top_players, Duration::from_secs(600));
+/// futures_util::pin_mut!(stream);
+///
+/// while let Some(change) = stream.next().await {
+///     let change = change?;
+///     println!("{} moved {:+} places", change.entry().username, change.rank_delta());
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn rank_change_stream<F, Fut>(
+    fetch_leaderboard: F,
+    interval: impl Into<Pacing>,
+) -> impl Stream<Item = Result<RankChange, RoliError>> + Send
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<LeaderboardEntry>, RoliError>> + Send,
+{
+    let state = RankState {
+        fetch_leaderboard,
+        interval: interval.into().at_least(limits::MIN_POLL_INTERVAL),
+        previous: Vec::new(),
+        pending: VecDeque::new(),
+        first_poll: true,
+        downtime: Downtime::default(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(change) = state.pending.pop_front() {
+                crate::metrics::record_stream_event("rank_changes");
+                return Some((Ok(change), state));
+            }
+
+            if !state.first_poll {
+                tokio::time::sleep(state.downtime.next_gap(&state.interval)).await;
+            }
+
+            state.first_poll = false;
+
+            let entries = match (state.fetch_leaderboard)().await {
+                Ok(x) => {
+                    state.downtime.end();
+                    x
+                }
+                Err(e) if state.downtime.is_ongoing(&e) => continue,
+                Err(e) => return Some((Err(e), state)),
+            };
+
+            state
+                .pending
+                .extend(leaderboard::diff(&state.previous, &entries));
+            state.previous = entries;
+        }
+    })
+}
+
+/// The same as [`rank_change_stream`], but returns [`RoliError::InvalidConfig`] if
+/// `interval` is below [`limits::MIN_POLL_INTERVAL`] instead of raising it.
+pub fn try_rank_change_stream<F, Fut>(
+    fetch_leaderboard: F,
+    interval: impl Into<Pacing>,
+) -> Result<impl Stream<Item = Result<RankChange, RoliError>> + Send, RoliError>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<LeaderboardEntry>, RoliError>> + Send,
+{
+    let interval = interval.into();
+
+    if interval.min() < limits::MIN_POLL_INTERVAL {
+        return Err(RoliError::InvalidConfig(format!(
+            "the leaderboard can not be polled more than once every {} seconds, but the interval is {:?}",
+            limits::MIN_POLL_INTERVAL.as_secs(),
+            interval.min()
+        )));
+    }
+
+    Ok(rank_change_stream(fetch_leaderboard, interval))
+}
+
+/// Turns a paged endpoint into a stream of items, starting at `first_page`.
+///
+/// `fetch_page` is called with consecutive page numbers until it returns an empty
+/// page. Items are yielded in order. If a page fails, its error is yielded and the
+/// stream ends, as retrying the same page forever would never finish.
+///
+/// Rolimons does not currently expose any paged endpoints (recent trade ads are
+/// only available for the last few minutes), so this is provided for wrapping
+/// paged endpoints as they become available.
+///
+/// # Example
+/// ```
+/// use futures_util::StreamExt;
+/// use roli::RoliError;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let pages = vec![vec![1, 2], vec![3]];
+///
+/// let stream = roli::streams::paginate(0, |page| {
+///     let page = pages.get(page as usize).cloned().unwrap_or_default();
+///     async move { Ok::<_, RoliError>(page) }
+/// });
+///
+/// let items = stream.map(Result::unwrap).collect::<Vec<_>>().await;
+/// assert_eq!(items, vec![1, 2, 3]);
+/// # }
+/// ```
+pub fn paginate<T, F, Fut>(
+    first_page: u64,
+    fetch_page: F,
+) -> impl Stream<Item = Result<T, RoliError>>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, RoliError>>,
+{
+    let state = PageState {
+        fetch_page,
+        next_page: Some(first_page),
+        pending: VecDeque::new(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.pending.pop_front() {
+                return Some((Ok(item), state));
+            }
+
+            let page = state.next_page?;
+
+            match (state.fetch_page)(page).await {
+                Ok(items) if items.is_empty() => return None,
+                Ok(items) => {
+                    state.pending.extend(items);
+                    state.next_page = page.checked_add(1);
+                }
+                Err(e) => {
+                    state.next_page = None;
+                    return Some((Err(e), state));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Faker, MockClient};
+    use crate::Endpoint;
+
+    #[test]
+    fn test_pacing() {
+        let fixed = Pacing::from(Duration::from_secs(3));
+        assert_eq!(fixed.next_gap(), Duration::from_secs(3));
+
+        let jittered = Pacing::jittered(Duration::from_secs(5), Duration::from_secs(3));
+        assert_eq!(jittered.min(), Duration::from_secs(3));
+
+        for _ in 0..100 {
+            let gap = jittered.next_gap();
+            assert!(gap >= Duration::from_secs(3) && gap <= Duration::from_secs(5));
+        }
+
+        let floored = jittered.at_least(MIN_PLAYER_PROFILE_INTERVAL);
+        assert_eq!(floored, Pacing::fixed(MIN_PLAYER_PROFILE_INTERVAL));
+    }
+
+    #[test]
+    fn test_downtime() {
+        let interval = Pacing::fixed(Duration::from_secs(3));
+        let mut downtime = Downtime::default();
+
+        assert!(!downtime.is_ongoing(&RoliError::TooManyRequests));
+        assert!(!downtime.is_ongoing(&RoliError::UnexpectedHtmlResponse));
+        assert!(downtime.is_ongoing(&RoliError::UnidentifiedStatusCode(503)));
+        assert_eq!(downtime.next_gap(&interval), MAINTENANCE_POLL_INTERVAL);
+
+        downtime.end();
+        assert_eq!(downtime.next_gap(&interval), Duration::from_secs(3));
+        assert!(!downtime.is_ongoing(&RoliError::UnexpectedHtmlResponse));
+    }
+
+    #[tokio::test]
+    async fn test_buffered_policies() {
+        let collect = |policy| async move {
+            let exhausted = Arc::new(Notify::new());
+            let signal = exhausted.clone();
+
+            // Signals once the task has pushed (or dropped) every item.
+            let source = stream::iter(1..=5).chain(stream::poll_fn(move |_| {
+                signal.notify_one();
+                std::task::Poll::Ready(None)
+            }));
+
+            let stream = buffered(source, 2, policy);
+            futures_util::pin_mut!(stream);
+
+            // A blocked task never gets to the end until the buffer is consumed.
+            if policy != OverflowPolicy::Block {
+                exhausted.notified().await;
+            }
+
+            stream.collect::<Vec<_>>().await
+        };
+
+        assert_eq!(collect(OverflowPolicy::Block).await, vec![1, 2, 3, 4, 5]);
+        assert_eq!(collect(OverflowPolicy::DropNewest).await, vec![1, 2]);
+        assert_eq!(collect(OverflowPolicy::KeepLatest).await, vec![4, 5]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_trade_ad_stream_dedup_and_errors() {
+        let mut faker = Faker::new(7);
+        let trade_ads = (1..=3)
+            .map(|trade_id| TradeAd {
+                trade_id,
+                ..faker.trade_ad()
+            })
+            .collect::<Vec<_>>();
+
+        let mock = MockClient::new();
+        mock.set_recent_trade_ads(trade_ads[..2].to_vec());
+
+        let stream = trade_ad_stream(
+            mock.clone(),
+            limits::RECENT_TRADE_ADS_POLL_INTERVAL,
+            MemoryState::new(),
+        );
+        futures_util::pin_mut!(stream);
+
+        assert_eq!(stream.next().await.unwrap().unwrap().trade_id, 1);
+        assert_eq!(stream.next().await.unwrap().unwrap().trade_id, 2);
+
+        mock.push_error(Endpoint::RecentTradeAds, RoliError::TooManyRequests);
+        mock.set_recent_trade_ads(trade_ads[1..].to_vec());
+
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(RoliError::TooManyRequests)
+        ));
+        assert_eq!(stream.next().await.unwrap().unwrap().trade_id, 3);
+        assert_eq!(mock.call_count(Endpoint::RecentTradeAds), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_trade_ad_stream_shared_cursor() {
+        let mut faker = Faker::new(7);
+        let trade_ads = (1..=3)
+            .map(|trade_id| TradeAd {
+                trade_id,
+                ..faker.trade_ad()
+            })
+            .collect::<Vec<_>>();
+
+        let mock = MockClient::new();
+        mock.set_recent_trade_ads(trade_ads[..2].to_vec());
+
+        let cursors = MemoryState::new();
+
+        let first = trade_ad_stream(
+            mock.clone(),
+            limits::RECENT_TRADE_ADS_POLL_INTERVAL,
+            cursors.clone(),
+        );
+        futures_util::pin_mut!(first);
+
+        assert_eq!(first.next().await.unwrap().unwrap().trade_id, 1);
+        assert_eq!(first.next().await.unwrap().unwrap().trade_id, 2);
+
+        mock.set_recent_trade_ads(trade_ads[1..].to_vec());
+
+        // A restarted stream skips the trade ads the first one already emitted.
+        let second = trade_ad_stream(mock, limits::RECENT_TRADE_ADS_POLL_INTERVAL, cursors);
+        futures_util::pin_mut!(second);
+
+        assert_eq!(second.next().await.unwrap().unwrap().trade_id, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_player_presence_stream_first_event() {
+        let profile = Faker::new(3).player_profile();
+        let user_id = profile.user_id;
+        let presence_type = profile.presence_type;
+
+        let mock = MockClient::new();
+        mock.insert_player_profile(profile);
+
+        let stream = player_presence_stream(mock, user_id, limits::PLAYER_PROFILE_POLL_INTERVAL);
+        futures_util::pin_mut!(stream);
+
+        let change = stream.next().await.unwrap().unwrap();
+        assert_eq!(change.previous, None);
+        assert_eq!(change.current, presence_type);
+    }
+
+    #[test]
+    fn test_try_player_presence_stream_floor() {
+        let mock = MockClient::new();
+
+        assert!(try_player_presence_stream(mock.clone(), 1, MIN_PLAYER_PROFILE_INTERVAL).is_ok());
+        assert!(matches!(
+            try_player_presence_stream(
+                mock,
+                1,
+                Pacing::jittered(Duration::from_secs(5), Duration::from_secs(60))
+            ),
+            Err(RoliError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_stream_zero_interval() {
+        let mock = MockClient::new();
+
+        assert!(try_sale_stream(mock.clone(), Duration::ZERO, MemoryState::new()).is_err());
+        assert!(try_trade_ad_stream(mock.clone(), Duration::ZERO, MemoryState::new()).is_err());
+        assert!(try_group_change_stream(mock.clone(), "Tetra", Duration::ZERO).is_err());
+        assert!(try_rank_change_stream(|| async { Ok(Vec::new()) }, Duration::ZERO).is_err());
+        assert!(try_sale_stream(mock, limits::MIN_POLL_INTERVAL, MemoryState::new()).is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_zero_interval_is_floored() {
+        let sale = |sale_id| Sale {
+            sale_id,
+            ..Default::default()
+        };
+
+        let mock = MockClient::new();
+        mock.set_recent_sales(vec![sale(1)]);
+
+        let stream = sale_stream(mock.clone(), Duration::ZERO, MemoryState::new());
+        futures_util::pin_mut!(stream);
+
+        assert_eq!(stream.next().await.unwrap().unwrap().sale_id, 1);
+
+        let start = tokio::time::Instant::now();
+        mock.set_recent_sales(vec![sale(2), sale(1)]);
+
+        assert_eq!(stream.next().await.unwrap().unwrap().sale_id, 2);
+        assert!(start.elapsed() >= limits::MIN_POLL_INTERVAL);
+        assert_eq!(mock.call_count(Endpoint::RecentSales), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_group_change_stream() {
+        let group = |id, member_count| GroupSearchResult {
+            id,
+            member_count,
+            ..Default::default()
+        };
+
+        let mock = MockClient::new();
+        mock.set_group_search_results(vec![group(1, 100), group(2, 200)]);
+
+        let stream = group_change_stream(mock.clone(), "Tetra", Duration::from_secs(600));
+        futures_util::pin_mut!(stream);
+
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            GroupChange::NewlyTracked(group(1, 100))
+        );
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            GroupChange::NewlyTracked(group(2, 200))
+        );
+
+        mock.set_group_search_results(vec![group(1, 100), group(2, 250)]);
+
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            GroupChange::MembersGained {
+                group: group(2, 250),
+                previous_member_count: 200
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paginate_stops_on_error() {
+        let stream = paginate(1, |page| async move {
+            match page {
+                1 => Ok(vec![page]),
+                _ => Err(RoliError::TooManyRequests),
+            }
+        });
+        futures_util::pin_mut!(stream);
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), 1);
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_received_sale_stream() {
+        let mock = MockClient::new();
+        mock.set_recent_sales(vec![Sale {
+            sale_id: 1,
+            timestamp: 1000,
+            ..Default::default()
+        }]);
+
+        let before = crate::unix_timestamp();
+        let stream =
+            received_sale_stream(mock, limits::RECENT_SALES_POLL_INTERVAL, MemoryState::new());
+        futures_util::pin_mut!(stream);
+
+        let received = stream.next().await.unwrap().unwrap();
+        assert_eq!(received.event.sale_id, 1);
+        assert!(received.received_at >= before);
+        assert_eq!(
+            received.latency(),
+            Duration::from_secs(received.received_at - 1000)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sale_stream_shared_cursor() {
+        let sale = |sale_id| Sale {
+            sale_id,
+            ..Default::default()
+        };
+
+        let mock = MockClient::new();
+        mock.set_recent_sales(vec![sale(2), sale(1)]);
+
+        let cursors = MemoryState::new();
+
+        let first = sale_stream(
+            mock.clone(),
+            limits::RECENT_SALES_POLL_INTERVAL,
+            cursors.clone(),
+        );
+        futures_util::pin_mut!(first);
+
+        assert_eq!(first.next().await.unwrap().unwrap().sale_id, 1);
+        assert_eq!(first.next().await.unwrap().unwrap().sale_id, 2);
+
+        mock.set_recent_sales(vec![sale(3), sale(2)]);
+
+        // Sales already emitted by the first stream are skipped.
+        let second = sale_stream(mock, limits::RECENT_SALES_POLL_INTERVAL, cursors);
+        futures_util::pin_mut!(second);
+
+        assert_eq!(second.next().await.unwrap().unwrap().sale_id, 3);
+    }
+
+    /// Holds the first two reads of the store until both have been made, so that two
+    /// streams read the same cursor before either moves it.
+    #[derive(Clone)]
+    struct RacingState {
+        inner: MemoryState,
+        reads: Arc<std::sync::atomic::AtomicUsize>,
+        barrier: Arc<tokio::sync::Barrier>,
+    }
+
+    impl crate::state::StateStore for RacingState {
+        fn get<'a>(
+            &'a self,
+            key: &'a str,
+        ) -> futures_util::future::BoxFuture<'a, Result<Option<Vec<u8>>, RoliError>> {
+            Box::pin(async move {
+                let value = self.inner.get(key).await;
+
+                if self.reads.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < 2 {
+                    self.barrier.wait().await;
+                }
+
+                value
+            })
+        }
+
+        fn set<'a>(
+            &'a self,
+            key: &'a str,
+            value: &'a [u8],
+        ) -> futures_util::future::BoxFuture<'a, Result<(), RoliError>> {
+            self.inner.set(key, value)
+        }
+
+        fn compare_and_swap<'a>(
+            &'a self,
+            key: &'a str,
+            expected: Option<&'a [u8]>,
+            new: &'a [u8],
+        ) -> futures_util::future::BoxFuture<'a, Result<bool, RoliError>> {
+            self.inner.compare_and_swap(key, expected, new)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sale_stream_racing_cursor() {
+        let sale = |sale_id| Sale {
+            sale_id,
+            ..Default::default()
+        };
+
+        let mock = MockClient::new();
+        mock.set_recent_sales(vec![sale(2), sale(1)]);
+
+        let cursors = RacingState {
+            inner: MemoryState::new(),
+            reads: Arc::default(),
+            barrier: Arc::new(tokio::sync::Barrier::new(2)),
+        };
+
+        let first = sale_stream(
+            mock.clone(),
+            limits::RECENT_SALES_POLL_INTERVAL,
+            cursors.clone(),
+        );
+        let second = sale_stream(mock.clone(), limits::RECENT_SALES_POLL_INTERVAL, cursors);
+        let stream = stream::select(first, second);
+        futures_util::pin_mut!(stream);
+
+        let mut sale_ids = Vec::new();
+
+        // Reads until a later sale comes through, so that duplicates of the first
+        // sales would be emitted before it.
+        while sale_ids.last() != Some(&3) {
+            sale_ids.push(stream.next().await.unwrap().unwrap().sale_id);
+
+            if sale_ids.len() == 2 {
+                mock.set_recent_sales(vec![sale(3), sale(2), sale(1)]);
+            }
+        }
+
+        // Both streams saw the same cursor, but only one of them claimed the sales.
+        assert_eq!(sale_ids, vec![1, 2, 3]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rank_change_stream() {
+        let entry = |rank, user_id| LeaderboardEntry {
+            rank,
+            user_id,
+            ..Default::default()
+        };
+
+        let responses = Arc::new(Mutex::new(VecDeque::from([
+            Ok(vec![entry(1, 10), entry(2, 20)]),
+            Err(RoliError::TooManyRequests),
+            Ok(vec![entry(1, 20), entry(2, 10)]),
+        ])));
+
+        let stream = rank_change_stream(
+            move || {
+                let response = responses.lock().unwrap().pop_front().unwrap();
+                async move { response }
+            },
+            Duration::from_secs(600),
+        );
+        futures_util::pin_mut!(stream);
+
+        for expected in [entry(1, 10), entry(2, 20)] {
+            let change = stream.next().await.unwrap().unwrap();
+            assert_eq!(change, RankChange::Entered(expected));
+        }
+
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(RoliError::TooManyRequests)
+        ));
+
+        let climbed = stream.next().await.unwrap().unwrap();
+        assert_eq!(climbed.entry().user_id, 20);
+        assert_eq!(climbed.rank_delta(), 1);
+
+        let fell = stream.next().await.unwrap().unwrap();
+        assert_eq!(fell.entry().user_id, 10);
+        assert_eq!(fell.rank_delta(), -1);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::{Event, RoliError};
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The default amount of errors in a row after which a feed is restarted.
+pub const DEFAULT_MAX_CONSECUTIVE_ERRORS: u32 = 5;
+
+/// The default wait before the first restart of a feed. It doubles with every restart
+/// in a row, up to [`DEFAULT_MAX_BACKOFF`].
+pub const DEFAULT_MIN_BACKOFF: Duration = Duration::from_secs(5);
+
+/// The default longest wait before restarting a feed.
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+type Factory = Box<dyn FnMut() -> BoxStream<'static, Result<Event, RoliError>> + Send>;
+
+/// Runs several streams (feeds) as one, restarting any feed that keeps failing.
+///
+/// A feed is restarted, by building a new stream with its factory, once it yields
+/// `max_consecutive_errors` errors in a row or ends. The restart waits a backoff that
+/// doubles with every restart in a row and resets once the feed yields an event again.
+/// Feeds are polled concurrently, so a feed waiting out its backoff does not hold up
+/// the others, and dropping the supervised stream stops every feed.
+///
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use roli::state::MemoryState;
+/// use roli::streams;
+/// use roli::streams::supervisor::{Supervisor, SupervisorEvent};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let client = roli::ClientBuilder::new().build();
+///
+/// let sales_client = client.clone();
+/// let ads_client = client.clone();
+///
+/// // Sharing the cursor keeps a restarted sales feed from emitting old sales again.
+/// let cursors = MemoryState::new();
+///
+/// let stream = Supervisor::new()
+///     .add_feed("sales", move || {
+///         streams::sale_stream(sales_client.clone(), Duration::from_secs(3), cursors.clone())
+///     })
+///     .add_feed("trade_ads", move || ads_client.trade_ad_stream(Duration::from_secs(60)))
+///     .into_stream();
+/// futures_util::pin_mut!(stream);
+///
+/// while let Some(event) = stream.next().await {
+///     match event {
+///         SupervisorEvent::Event { event, .. } => println!("{}", event.kind()),
+///         SupervisorEvent::Error { feed, error } => println!("{} failed: {}", feed, error),
+///         SupervisorEvent::Restarted(restart) => println!("Restarted {}", restart.feed),
+///     }
+/// }
+/// # }
+/// ```
+pub struct Supervisor {
+    feeds: Vec<(String, Factory)>,
+    max_consecutive_errors: u32,
+    min_backoff: Duration,
+    max_backoff: Duration,
+}
+
+/// An item of a stream returned by [`Supervisor::into_stream`].
+#[derive(Debug)]
+pub enum SupervisorEvent {
+    /// An event yielded by a feed.
+    Event {
+        /// The name of the feed.
+        feed: String,
+        /// The event.
+        event: Event,
+    },
+    /// An error yielded by a feed. Every error is passed on, including the one that
+    /// causes a restart.
+    Error {
+        /// The name of the feed.
+        feed: String,
+        /// The error.
+        error: RoliError,
+    },
+    /// A feed was restarted.
+    Restarted(Restart),
+}
+
+/// A restart of a feed by a [`Supervisor`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Restart {
+    /// The name of the feed.
+    pub feed: String,
+    /// The amount of restarts of the feed in a row, without an event in between.
+    pub attempt: u32,
+    /// How long the supervisor waited before restarting the feed.
+    pub backoff: Duration,
+    /// The last error of the feed, or `None` if the feed was restarted because it ended.
+    pub last_error: Option<String>,
+}
+
+struct FeedState {
+    name: String,
+    factory: Factory,
+    stream: Option<BoxStream<'static, Result<Event, RoliError>>>,
+    consecutive_errors: u32,
+    attempt: u32,
+    restart_pending: Option<Option<String>>,
+    max_consecutive_errors: u32,
+    min_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Supervisor {
+    /// Creates a supervisor with no feeds, using [`DEFAULT_MAX_CONSECUTIVE_ERRORS`],
+    /// [`DEFAULT_MIN_BACKOFF`], and [`DEFAULT_MAX_BACKOFF`].
+    pub fn new() -> Self {
+        Self {
+            feeds: Vec::new(),
+            max_consecutive_errors: DEFAULT_MAX_CONSECUTIVE_ERRORS,
+            min_backoff: DEFAULT_MIN_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Sets the amount of errors in a row after which a feed is restarted. A value of
+    /// 0 is treated as 1.
+    pub fn set_max_consecutive_errors(mut self, max_consecutive_errors: u32) -> Self {
+        self.max_consecutive_errors = max_consecutive_errors.max(1);
+        self
+    }
+
+    /// Sets the wait before the first restart in a row and the longest wait.
+    ///
+    /// The bounds are swapped if `min` is greater than `max`.
+    pub fn set_backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min.min(max);
+        self.max_backoff = min.max(max);
+        self
+    }
+
+    /// Adds a feed. `factory` is called to build the feed's stream when the supervised
+    /// stream is first polled and on every restart.
+    pub fn add_feed<F, S, T>(mut self, name: impl Into<String>, mut factory: F) -> Self
+    where
+        F: FnMut() -> S + Send + 'static,
+        S: Stream<Item = Result<T, RoliError>> + Send + 'static,
+        T: Into<Event>,
+    {
+        let factory: Factory = Box::new(move || factory().map(|x| x.map(Into::into)).boxed());
+        self.feeds.push((name.into(), factory));
+        self
+    }
+
+    /// Returns a stream of every feed's events, errors, and restarts, in the order
+    /// they happen. The stream never ends unless there are no feeds.
+    pub fn into_stream(self) -> impl Stream<Item = SupervisorEvent> + Send {
+        let feeds = self.feeds.into_iter().map(|(name, factory)| {
+            feed_stream(FeedState {
+                name,
+                factory,
+                stream: None,
+                consecutive_errors: 0,
+                attempt: 0,
+                restart_pending: None,
+                max_consecutive_errors: self.max_consecutive_errors,
+                min_backoff: self.min_backoff,
+                max_backoff: self.max_backoff,
+            })
+            .boxed()
+        });
+
+        stream::select_all(feeds)
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Supervisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Supervisor")
+            .field(
+                "feeds",
+                &self.feeds.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            )
+            .field("max_consecutive_errors", &self.max_consecutive_errors)
+            .field("min_backoff", &self.min_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .finish()
+    }
+}
+
+impl FeedState {
+    /// The wait before the current restart, doubling with every restart in a row.
+    fn backoff(&self) -> Duration {
+        let doublings = self.attempt.saturating_sub(1).min(31);
+
+        self.min_backoff
+            .saturating_mul(1 << doublings)
+            .min(self.max_backoff)
+    }
+}
+
+fn feed_stream(state: FeedState) -> impl Stream<Item = SupervisorEvent> + Send {
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(last_error) = state.restart_pending.take() {
+                state.stream = None;
+                state.consecutive_errors = 0;
+                state.attempt += 1;
+
+                let backoff = state.backoff();
+                tokio::time::sleep(backoff).await;
+
+                crate::metrics::record_stream_restart(&state.name);
+
+                let restart = Restart {
+                    feed: state.name.clone(),
+                    attempt: state.attempt,
+                    backoff,
+                    last_error,
+                };
+
+                return Some((SupervisorEvent::Restarted(restart), state));
+            }
+
+            let stream = state.stream.get_or_insert_with(&mut state.factory);
+
+            let item = match stream.next().await {
+                Some(Ok(event)) => {
+                    state.consecutive_errors = 0;
+                    state.attempt = 0;
+
+                    SupervisorEvent::Event {
+                        feed: state.name.clone(),
+                        event,
+                    }
+                }
+                Some(Err(error)) => {
+                    state.consecutive_errors += 1;
+
+                    if state.consecutive_errors >= state.max_consecutive_errors {
+                        state.restart_pending = Some(Some(error.to_string()));
+                    }
+
+                    SupervisorEvent::Error {
+                        feed: state.name.clone(),
+                        error,
+                    }
+                }
+                None => {
+                    state.restart_pending = Some(None);
+                    continue;
+                }
+            };
+
+            return Some((item, state));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_activity::Sale;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_supervisor_restarts() {
+        let builds = Arc::new(AtomicU64::new(0));
+        let counter = builds.clone();
+
+        let stream = Supervisor::new()
+            .set_max_consecutive_errors(2)
+            .set_backoff(Duration::ZERO, Duration::ZERO)
+            .add_feed("sales", move || {
+                let sale_id = counter.fetch_add(1, Ordering::SeqCst);
+
+                stream::iter(vec![
+                    Ok(Sale {
+                        sale_id,
+                        ..Default::default()
+                    }),
+                    Err(RoliError::TooManyRequests),
+                    Err(RoliError::TooManyRequests),
+                    Ok(Sale::default()),
+                ])
+            })
+            .into_stream();
+        futures_util::pin_mut!(stream);
+
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            SupervisorEvent::Event {
+                event: Event::Sale(Sale { sale_id: 0, .. }),
+                ..
+            }
+        ));
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            SupervisorEvent::Error { .. }
+        ));
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            SupervisorEvent::Error { .. }
+        ));
+
+        let SupervisorEvent::Restarted(restart) = stream.next().await.unwrap() else {
+            panic!("expected a restart");
+        };
+        assert_eq!(restart.feed, "sales");
+        assert_eq!(restart.attempt, 1);
+        assert_eq!(restart.last_error.as_deref(), Some("Too Many Requests"));
+
+        // The restarted feed is built again, so it starts from its first event.
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            SupervisorEvent::Event {
+                event: Event::Sale(Sale { sale_id: 1, .. }),
+                ..
+            }
+        ));
+        assert_eq!(builds.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let state = |attempt| FeedState {
+            name: String::new(),
+            factory: Box::new(|| stream::empty().boxed()),
+            stream: None,
+            consecutive_errors: 0,
+            attempt,
+            restart_pending: None,
+            max_consecutive_errors: 1,
+            min_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(60),
+        };
+
+        assert_eq!(state(1).backoff(), Duration::from_secs(5));
+        assert_eq!(state(3).backoff(), Duration::from_secs(20));
+        assert_eq!(state(40).backoff(), Duration::from_secs(60));
+    }
+}
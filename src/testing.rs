@@ -0,0 +1,492 @@
+use crate::items::{Demand, ItemDetails, Trend};
+use crate::market_activity::{calculate_sale_price, Sale};
+use crate::players::{Badge, PlayerAsset, PlayerProfile, PresenceType};
+use crate::trade_ads::{Offer, Request, RequestTag, TradeAd};
+
+pub use mock::{MockClient, ScriptedResponse};
+
+mod mock;
+
+const START_TIMESTAMP: u64 = 1_700_000_000;
+
+const NAME_PARTS: &[&str] = &[
+    "Valkyrie",
+    "Sparkle",
+    "Time",
+    "Fedora",
+    "Dominus",
+    "Domino",
+    "Crown",
+    "Clockwork",
+    "Shades",
+    "Headphones",
+    "Wings",
+    "Hair",
+    "Korblox",
+    "Midnight",
+    "Frozen",
+    "Golden",
+    "Crimson",
+    "Violet",
+];
+
+const DEMANDS: [Demand; 6] = [
+    Demand::Unassigned,
+    Demand::Terrible,
+    Demand::Low,
+    Demand::Normal,
+    Demand::High,
+    Demand::Amazing,
+];
+
+const TRENDS: [Trend; 6] = [
+    Trend::Unassigned,
+    Trend::Lowering,
+    Trend::Unstable,
+    Trend::Stable,
+    Trend::Raising,
+    Trend::Fluctuating,
+];
+
+const REQUEST_TAGS: [RequestTag; 10] = [
+    RequestTag::Any,
+    RequestTag::Demand,
+    RequestTag::Rares,
+    RequestTag::Robux,
+    RequestTag::Upgrade,
+    RequestTag::Downgrade,
+    RequestTag::Rap,
+    RequestTag::Wishlist,
+    RequestTag::Projecteds,
+    RequestTag::Adds,
+];
+
+/// A deterministic generator of fake (but internally consistent) crate data.
+///
+/// The same seed always produces the same data, which makes it suitable for unit
+/// tests of matching and valuation logic without needing fixtures of real data.
+///
+/// # Example
+/// ```
+/// use roli::testing::Faker;
+///
+/// let mut faker = Faker::new(42);
+/// let items = faker.items(10);
+/// let sale = faker.sale_for(&items[0]);
+///
+/// assert_eq!(sale.item_id, items[0].item_id);
+/// assert_eq!(Faker::new(42).items(10), items);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Faker {
+    state: u64,
+    next_id: u64,
+    timestamp: u64,
+}
+
+impl Faker {
+    /// Creates a new generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            next_id: 1,
+            timestamp: START_TIMESTAMP,
+        }
+    }
+
+    /// Returns a pseudo random u64 (SplitMix64).
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo random number in `min..=max`.
+    pub fn range(&mut self, min: u64, max: u64) -> u64 {
+        if max <= min {
+            return min;
+        }
+
+        min + self.next_u64() % (max - min + 1)
+    }
+
+    /// Returns true with a probability of `percent`%.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.range(1, 100) <= percent
+    }
+
+    fn pick<T: Copy>(&mut self, values: &[T]) -> T {
+        values[self.range(0, values.len() as u64 - 1) as usize]
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn next_timestamp(&mut self) -> u64 {
+        self.timestamp += self.range(1, 60);
+        self.timestamp
+    }
+
+    /// Generates the details of a limited item with a unique item id.
+    pub fn item_details(&mut self) -> ItemDetails {
+        let item_id = self.next_id();
+
+        let first = self.pick(NAME_PARTS);
+        let second = self.pick(NAME_PARTS);
+        let item_name = format!("{} {}", first, second);
+
+        let acronym = if self.chance(30) {
+            Some(format!("{}{}", &first[..1], &second[..1]))
+        } else {
+            None
+        };
+
+        let rap = self.range(100, 500_000);
+        let valued = self.chance(40);
+        let value = if valued {
+            rap + rap / 10 * self.range(0, 10)
+        } else {
+            0
+        };
+
+        ItemDetails {
+            item_id,
+            item_name,
+            acronym,
+            rap,
+            valued,
+            value,
+            demand: if valued {
+                self.pick(&DEMANDS)
+            } else {
+                Demand::Unassigned
+            },
+            trend: if valued {
+                self.pick(&TRENDS)
+            } else {
+                Trend::Unassigned
+            },
+            projected: self.chance(5),
+            hyped: self.chance(5),
+            rare: self.chance(5),
+        }
+    }
+
+    /// Generates `amount` items with unique item ids.
+    pub fn items(&mut self, amount: usize) -> Vec<ItemDetails> {
+        (0..amount).map(|_| self.item_details()).collect()
+    }
+
+    /// Generates a sale of a random item.
+    pub fn sale(&mut self) -> Sale {
+        let item = self.item_details();
+        self.sale_for(&item)
+    }
+
+    /// Generates a sale of `item`, priced around its rap. Timestamps and sale ids increase
+    /// with every sale generated.
+    pub fn sale_for(&mut self, item: &ItemDetails) -> Sale {
+        let old_rap = item.rap;
+        let price = self.range(old_rap / 2, old_rap + old_rap / 2);
+        let new_rap = new_rap(old_rap, price);
+
+        Sale {
+            item_id: item.item_id,
+            old_rap,
+            new_rap,
+            sale_price: calculate_sale_price(old_rap, new_rap),
+            sale_id: self.next_id(),
+            timestamp: self.next_timestamp(),
+        }
+    }
+
+    /// Generates a trade ad offering and requesting items from `items`.
+    ///
+    /// `items` must not be empty.
+    pub fn trade_ad_with_items(&mut self, items: &[ItemDetails]) -> TradeAd {
+        let item_ids = items.iter().map(|x| x.item_id).collect::<Vec<_>>();
+
+        let offer_items = (0..self.range(1, 4))
+            .map(|_| self.pick(&item_ids))
+            .collect::<Vec<_>>();
+
+        let request_items = (0..self.range(0, 3))
+            .map(|_| self.pick(&item_ids))
+            .collect::<Vec<_>>();
+
+        let mut tags = Vec::new();
+
+        if request_items.is_empty() || self.chance(50) {
+            tags.push(self.pick(&REQUEST_TAGS));
+        }
+
+        let user_id = self.range(1, 5_000_000_000);
+
+        TradeAd {
+            trade_id: self.next_id(),
+            timestamp: self.next_timestamp(),
+            user_id,
+            username: format!("Player{}", user_id),
+            offer: Offer {
+                items: offer_items,
+                robux: if self.chance(20) {
+                    Some(self.range(100, 10_000))
+                } else {
+                    None
+                },
+            },
+            request: Request {
+                items: request_items,
+                tags,
+            },
+        }
+    }
+
+    /// Generates a trade ad using freshly generated items.
+    pub fn trade_ad(&mut self) -> TradeAd {
+        let items = self.items(5);
+        self.trade_ad_with_items(&items)
+    }
+
+    /// Generates a public, non-terminated player profile owning copies of items from `items`.
+    pub fn player_profile_with_items(&mut self, items: &[ItemDetails]) -> PlayerProfile {
+        let mut inventory = Vec::new();
+
+        for item in items {
+            if !self.chance(50) {
+                continue;
+            }
+
+            let uaids = (0..self.range(1, 3))
+                .map(|_| self.range(1, 500_000_000_000))
+                .collect();
+
+            inventory.push(PlayerAsset {
+                item_id: item.item_id,
+                uaids,
+            });
+        }
+
+        crate::players::normalize_inventory(&mut inventory);
+
+        let is_online = self.chance(50);
+
+        PlayerProfile {
+            user_id: self.range(1, 5_000_000_000),
+            terminated: false,
+            privated: false,
+            is_online,
+            last_online: self.next_timestamp(),
+            premium: self.chance(30),
+            verified: true,
+            presence_type: if is_online {
+                self.pick(&[PresenceType::Website, PresenceType::InGame])
+            } else {
+                PresenceType::Unavailable
+            },
+            join_context: None,
+            badges: vec![Badge {
+                name: "roli_verified".to_string(),
+                timestamp_earned: self.next_timestamp(),
+            }],
+            inventory,
+        }
+    }
+
+    /// Generates a player profile using freshly generated items.
+    pub fn player_profile(&mut self) -> PlayerProfile {
+        let items = self.items(10);
+        self.player_profile_with_items(&items)
+    }
+}
+
+/// The rap after an item with `old_rap` sells for `price` (rap moves 10% of the way to the price).
+fn new_rap(old_rap: u64, price: u64) -> u64 {
+    if old_rap == 0 {
+        return price;
+    }
+
+    (old_rap as i64 + (price as i64 - old_rap as i64) / 10) as u64
+}
+
+/// Proptest strategies for crate types.
+#[cfg(feature = "proptest")]
+pub mod strategies {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::option;
+    use proptest::prelude::*;
+
+    /// A strategy for any [`Demand`].
+    pub fn demand() -> impl Strategy<Value = Demand> {
+        proptest::sample::select(DEMANDS.to_vec())
+    }
+
+    /// A strategy for any [`Trend`].
+    pub fn trend() -> impl Strategy<Value = Trend> {
+        proptest::sample::select(TRENDS.to_vec())
+    }
+
+    /// A strategy for any [`RequestTag`].
+    pub fn request_tag() -> impl Strategy<Value = RequestTag> {
+        proptest::sample::select(REQUEST_TAGS.to_vec())
+    }
+
+    /// A strategy for [`ItemDetails`]. Unvalued items always have a value of 0.
+    pub fn item_details() -> impl Strategy<Value = ItemDetails> {
+        (
+            1..u32::MAX as u64,
+            "[A-Za-z ]{1,30}",
+            option::of("[A-Z]{1,5}"),
+            0..10_000_000_u64,
+            option::of(0..10_000_000_u64),
+            demand(),
+            trend(),
+            any::<(bool, bool, bool)>(),
+        )
+            .prop_map(
+                |(item_id, item_name, acronym, rap, value, demand, trend, flags)| ItemDetails {
+                    item_id,
+                    item_name,
+                    acronym,
+                    rap,
+                    valued: value.is_some(),
+                    value: value.unwrap_or_default(),
+                    demand,
+                    trend,
+                    projected: flags.0,
+                    hyped: flags.1,
+                    rare: flags.2,
+                },
+            )
+    }
+
+    /// A strategy for a [`Sale`] whose sale price is consistent with its rap change.
+    pub fn sale() -> impl Strategy<Value = Sale> {
+        (
+            1..u32::MAX as u64,
+            1..10_000_000_u64,
+            0..20_000_000_u64,
+            1..u32::MAX as u64,
+            START_TIMESTAMP..START_TIMESTAMP * 2,
+        )
+            .prop_map(|(item_id, old_rap, price, sale_id, timestamp)| {
+                let new_rap = new_rap(old_rap, price);
+
+                Sale {
+                    item_id,
+                    old_rap,
+                    new_rap,
+                    sale_price: calculate_sale_price(old_rap, new_rap),
+                    sale_id,
+                    timestamp,
+                }
+            })
+    }
+
+    /// A strategy for a [`TradeAd`].
+    pub fn trade_ad() -> impl Strategy<Value = TradeAd> {
+        (
+            1..u32::MAX as u64,
+            START_TIMESTAMP..START_TIMESTAMP * 2,
+            1..u32::MAX as u64,
+            "[A-Za-z0-9_]{3,20}",
+            vec(1..u32::MAX as u64, 1..=4),
+            option::of(1..100_000_u64),
+            vec(1..u32::MAX as u64, 0..=4),
+            vec(request_tag(), 0..=4),
+        )
+            .prop_map(
+                |(
+                    trade_id,
+                    timestamp,
+                    user_id,
+                    username,
+                    offer_items,
+                    robux,
+                    request_items,
+                    tags,
+                )| {
+                    TradeAd {
+                        trade_id,
+                        timestamp,
+                        user_id,
+                        username,
+                        offer: Offer {
+                            items: offer_items,
+                            robux,
+                        },
+                        request: Request {
+                            items: request_items,
+                            tags,
+                        },
+                    }
+                },
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faker_is_deterministic() {
+        let mut a = Faker::new(7);
+        let mut b = Faker::new(7);
+
+        assert_eq!(a.items(20), b.items(20));
+        assert_eq!(a.trade_ad(), b.trade_ad());
+        assert_eq!(a.player_profile(), b.player_profile());
+    }
+
+    #[test]
+    fn test_item_ids_are_unique() {
+        let mut faker = Faker::new(1);
+        let items = faker.items(100);
+
+        let mut ids = items.iter().map(|x| x.item_id).collect::<Vec<_>>();
+        ids.dedup();
+
+        assert_eq!(ids.len(), 100);
+    }
+
+    #[test]
+    fn test_sale_is_consistent() {
+        let mut faker = Faker::new(3);
+
+        for _ in 0..100 {
+            let sale = faker.sale();
+            assert_eq!(
+                sale.sale_price,
+                calculate_sale_price(sale.old_rap, sale.new_rap)
+            );
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn test_sale_strategy_is_consistent(sale in strategies::sale()) {
+            proptest::prop_assert!(sale.sale_price < 100_000_000);
+        }
+    }
+
+    #[test]
+    fn test_trade_ad_uses_given_items() {
+        let mut faker = Faker::new(9);
+        let items = faker.items(3);
+        let trade_ad = faker.trade_ad_with_items(&items);
+
+        for item_id in trade_ad.offer.items.iter().chain(&trade_ad.request.items) {
+            assert!(items.iter().any(|x| x.item_id == *item_id));
+        }
+
+        assert!(!trade_ad.request.items.is_empty() || !trade_ad.request.tags.is_empty());
+    }
+}
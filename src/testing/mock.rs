@@ -0,0 +1,401 @@
+use crate::api::RoliApi;
+use crate::deals::Activity;
+use crate::games::Game;
+use crate::groups::GroupSearchResult;
+use crate::items::ItemDetails;
+use crate::market_activity::Sale;
+use crate::players::{PlayerProfile, PlayerSearchResult};
+use crate::trade_ads::{CreateTradeAdParams, TradeAd};
+use crate::{Endpoint, RoliError};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// An in-memory implementation of [`RoliApi`] that returns canned responses.
+///
+/// Each endpoint returns whatever was last set for it (e.g. with
+/// [`MockClient::set_all_item_details`]), unless responses were scripted for that
+/// endpoint with [`MockClient::push_script`] or [`MockClient::push_error`], in which
+/// case the scripted responses are used first, in order. Endpoints that have nothing
+/// set return [`RoliError::UnidentifiedStatusCode`] with a status code of 404.
+///
+/// Clones share the same state, so a clone can be handed to the code under test
+/// while the original is used to script responses and inspect calls.
+///
+/// # Example
+/// ```
+/// use roli::api::RoliApi;
+/// use roli::testing::{Faker, MockClient};
+/// use roli::{Endpoint, RoliError};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mock = MockClient::new();
+/// mock.set_all_item_details(Faker::new(1).items(5));
+/// mock.push_error(Endpoint::AllItemDetails, RoliError::TooManyRequests);
+///
+/// assert!(matches!(
+///     mock.all_item_details().await,
+///     Err(RoliError::TooManyRequests)
+/// ));
+/// assert_eq!(mock.all_item_details().await.unwrap().len(), 5);
+/// assert_eq!(mock.call_count(Endpoint::AllItemDetails), 2);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+/// A response scripted with [`MockClient::push_script`], used to test how code
+/// handles failures.
+///
+/// The failures produce the same errors the real [`Client`](crate::Client) returns
+/// for them.
+#[derive(Debug)]
+pub enum ScriptedResponse {
+    /// Returns the response set for the endpoint, as if the request succeeded.
+    Ok,
+    /// Returns the error.
+    Error(RoliError),
+    /// Responds with the status code. 429 returns [`RoliError::TooManyRequests`], 500
+    /// returns [`RoliError::InternalServerError`], 200 is the same as
+    /// [`ScriptedResponse::Ok`], and anything else returns
+    /// [`RoliError::UnidentifiedStatusCode`].
+    Status(u16),
+    /// Responds with a body that is not valid json, which returns
+    /// [`RoliError::MalformedResponse`].
+    MalformedJson,
+    /// Responds with an html page, such as the maintenance page, which returns
+    /// [`RoliError::UnexpectedHtmlResponse`].
+    Html,
+    /// Returns [`RoliError::DeadlineExceeded`], as if the request took longer than the
+    /// client's deadline.
+    ///
+    /// Only available with the `async-rt` feature.
+    #[cfg(feature = "async-rt")]
+    Timeout,
+    /// Waits before returning the response set for the endpoint, to test the caller's
+    /// own timeouts.
+    ///
+    /// Only available with the `async-rt` feature.
+    #[cfg(feature = "async-rt")]
+    Delay(Duration),
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    all_item_details: Option<Vec<ItemDetails>>,
+    deals_activity: Option<Vec<Activity>>,
+    recent_trade_ads: Option<Vec<TradeAd>>,
+    player_search_results: Option<Vec<PlayerSearchResult>>,
+    player_profiles: HashMap<u64, PlayerProfile>,
+    games_list: Option<Vec<Game>>,
+    group_search_results: Option<Vec<GroupSearchResult>>,
+    recent_sales: Option<Vec<Sale>>,
+    created_trade_ads: Vec<CreateTradeAdParams>,
+    scripts: HashMap<Endpoint, VecDeque<ScriptedResponse>>,
+    call_counts: HashMap<Endpoint, usize>,
+}
+
+impl MockState {
+    /// Records a call to `endpoint` and plays the next scripted response for it, if any.
+    ///
+    /// Returns how long to wait before responding, or the error to respond with.
+    fn call(&mut self, endpoint: Endpoint) -> Result<Duration, RoliError> {
+        *self.call_counts.entry(endpoint).or_default() += 1;
+
+        let scripted = self
+            .scripts
+            .get_mut(&endpoint)
+            .and_then(|x| x.pop_front())
+            .unwrap_or(ScriptedResponse::Ok);
+
+        match scripted {
+            ScriptedResponse::Ok | ScriptedResponse::Status(200) => Ok(Duration::ZERO),
+            ScriptedResponse::Error(error) => Err(error),
+            ScriptedResponse::Status(status_code) => Err(crate::request::status_error(status_code)),
+            ScriptedResponse::MalformedJson => Err(RoliError::MalformedResponse),
+            ScriptedResponse::Html => Err(RoliError::UnexpectedHtmlResponse),
+            #[cfg(feature = "async-rt")]
+            ScriptedResponse::Timeout => Err(RoliError::DeadlineExceeded),
+            #[cfg(feature = "async-rt")]
+            ScriptedResponse::Delay(delay) => Ok(delay),
+        }
+    }
+}
+
+fn canned<T: Clone>(response: &Option<T>) -> Result<T, RoliError> {
+    response
+        .clone()
+        .ok_or(RoliError::UnidentifiedStatusCode(404))
+}
+
+impl MockClient {
+    /// Creates a mock with no responses set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // A panic while the lock is held can only come from a failed test, so
+        // the state is still usable.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queues an error to be returned by the next call to `endpoint`.
+    ///
+    /// This is the same as pushing a script of [`ScriptedResponse::Error`].
+    pub fn push_error(&self, endpoint: Endpoint, error: RoliError) {
+        self.push_script(endpoint, [ScriptedResponse::Error(error)]);
+    }
+
+    /// Queues responses for the next calls to `endpoint`, one per call.
+    ///
+    /// Scripted responses are played in the order they were queued, before falling
+    /// back to the response set for the endpoint.
+    ///
+    /// # Example
+    /// ```
+    /// use roli::api::RoliApi;
+    /// use roli::testing::{Faker, MockClient, ScriptedResponse};
+    /// use roli::{Endpoint, RoliError};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mock = MockClient::new();
+    /// mock.set_recent_sales(vec![Faker::new(1).sale()]);
+    /// mock.push_script(
+    ///     Endpoint::RecentSales,
+    ///     [
+    ///         ScriptedResponse::Status(429),
+    ///         ScriptedResponse::Status(429),
+    ///         ScriptedResponse::Html,
+    ///     ],
+    /// );
+    ///
+    /// let mut attempts = 0;
+    /// let sales = loop {
+    ///     attempts += 1;
+    ///
+    ///     match mock.recent_sales().await {
+    ///         Err(e) if e.is_retryable() => continue,
+    ///         result => break result,
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(sales.unwrap().len(), 1);
+    /// assert_eq!(attempts, 4);
+    /// # }
+    /// ```
+    pub fn push_script(
+        &self,
+        endpoint: Endpoint,
+        responses: impl IntoIterator<Item = ScriptedResponse>,
+    ) {
+        self.state()
+            .scripts
+            .entry(endpoint)
+            .or_default()
+            .extend(responses);
+    }
+
+    /// Records a call to `endpoint` and builds its response from the state with `f`,
+    /// unless a scripted failure is played instead.
+    fn respond<T: Send + 'static>(
+        &self,
+        endpoint: Endpoint,
+        f: impl FnOnce(&mut MockState) -> Result<T, RoliError>,
+    ) -> impl Future<Output = Result<T, RoliError>> + Send + 'static {
+        let (delay, result) = {
+            let mut state = self.state();
+
+            match state.call(endpoint) {
+                Ok(delay) => (delay, f(&mut state)),
+                Err(e) => (Duration::ZERO, Err(e)),
+            }
+        };
+
+        async move {
+            #[cfg(feature = "async-rt")]
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+
+            #[cfg(not(feature = "async-rt"))]
+            let _ = delay;
+
+            result
+        }
+    }
+
+    /// Returns how many times `endpoint` has been called, including calls that returned errors.
+    pub fn call_count(&self, endpoint: Endpoint) -> usize {
+        self.state()
+            .call_counts
+            .get(&endpoint)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns every trade ad successfully "posted" through [`RoliApi::create_trade_ad`].
+    pub fn created_trade_ads(&self) -> Vec<CreateTradeAdParams> {
+        self.state().created_trade_ads.clone()
+    }
+
+    /// Sets the response of [`RoliApi::all_item_details`].
+    pub fn set_all_item_details(&self, all_item_details: Vec<ItemDetails>) {
+        self.state().all_item_details = Some(all_item_details);
+    }
+
+    /// Sets the response of [`RoliApi::deals_activity`].
+    pub fn set_deals_activity(&self, activities: Vec<Activity>) {
+        self.state().deals_activity = Some(activities);
+    }
+
+    /// Sets the response of [`RoliApi::recent_trade_ads`].
+    pub fn set_recent_trade_ads(&self, trade_ads: Vec<TradeAd>) {
+        self.state().recent_trade_ads = Some(trade_ads);
+    }
+
+    /// Sets the response of [`RoliApi::player_search`], regardless of the username searched.
+    pub fn set_player_search_results(&self, results: Vec<PlayerSearchResult>) {
+        self.state().player_search_results = Some(results);
+    }
+
+    /// Sets the response of [`RoliApi::player_profile`] for the profile's user id.
+    ///
+    /// Profiles that were never inserted return [`RoliError::RequestReturnedUnsuccessful`].
+    pub fn insert_player_profile(&self, player_profile: PlayerProfile) {
+        self.state()
+            .player_profiles
+            .insert(player_profile.user_id, player_profile);
+    }
+
+    /// Sets the response of [`RoliApi::games_list`].
+    pub fn set_games_list(&self, games: Vec<Game>) {
+        self.state().games_list = Some(games);
+    }
+
+    /// Sets the response of [`RoliApi::group_search`], regardless of the group name searched.
+    pub fn set_group_search_results(&self, results: Vec<GroupSearchResult>) {
+        self.state().group_search_results = Some(results);
+    }
+
+    /// Sets the response of [`RoliApi::recent_sales`].
+    pub fn set_recent_sales(&self, sales: Vec<Sale>) {
+        self.state().recent_sales = Some(sales);
+    }
+}
+
+impl RoliApi for MockClient {
+    fn all_item_details(&self) -> impl Future<Output = Result<Vec<ItemDetails>, RoliError>> + Send {
+        self.respond(Endpoint::AllItemDetails, |state| {
+            canned(&state.all_item_details)
+        })
+    }
+
+    fn deals_activity(&self) -> impl Future<Output = Result<Vec<Activity>, RoliError>> + Send {
+        self.respond(Endpoint::DealsActivity, |state| {
+            canned(&state.deals_activity)
+        })
+    }
+
+    fn recent_trade_ads(&self) -> impl Future<Output = Result<Vec<TradeAd>, RoliError>> + Send {
+        self.respond(Endpoint::RecentTradeAds, |state| {
+            canned(&state.recent_trade_ads)
+        })
+    }
+
+    fn create_trade_ad(
+        &self,
+        create_trade_ad_params: CreateTradeAdParams,
+    ) -> impl Future<Output = Result<(), RoliError>> + Send {
+        // Like the real client, ads that break a posting rule never reach the endpoint.
+        let response = create_trade_ad_params.validate().map(|_| {
+            self.respond(Endpoint::CreateTradeAd, |state| {
+                state.created_trade_ads.push(create_trade_ad_params);
+                Ok(())
+            })
+        });
+
+        async move { response?.await }
+    }
+
+    fn player_search(
+        &self,
+        _username: &str,
+    ) -> impl Future<Output = Result<Vec<PlayerSearchResult>, RoliError>> + Send {
+        self.respond(Endpoint::PlayerSearch, |state| {
+            canned(&state.player_search_results)
+        })
+    }
+
+    fn player_profile(
+        &self,
+        user_id: u64,
+    ) -> impl Future<Output = Result<PlayerProfile, RoliError>> + Send {
+        self.respond(Endpoint::PlayerProfile, move |state| {
+            state
+                .player_profiles
+                .get(&user_id)
+                .cloned()
+                .ok_or(RoliError::RequestReturnedUnsuccessful)
+        })
+    }
+
+    fn games_list(&self) -> impl Future<Output = Result<Vec<Game>, RoliError>> + Send {
+        self.respond(Endpoint::GamesList, |state| canned(&state.games_list))
+    }
+
+    fn group_search(
+        &self,
+        _group_name: &str,
+    ) -> impl Future<Output = Result<Vec<GroupSearchResult>, RoliError>> + Send {
+        self.respond(Endpoint::GroupSearch, |state| {
+            canned(&state.group_search_results)
+        })
+    }
+
+    fn recent_sales(&self) -> impl Future<Output = Result<Vec<Sale>, RoliError>> + Send {
+        self.respond(Endpoint::RecentSales, |state| canned(&state.recent_sales))
+    }
+}
+
+#[cfg(all(test, feature = "async-rt"))]
+mod tests {
+    use super::*;
+    use crate::testing::Faker;
+
+    #[tokio::test]
+    async fn test_scripted_failures() {
+        let mock = MockClient::new();
+        mock.set_all_item_details(Faker::new(1).items(2));
+        mock.push_script(
+            Endpoint::AllItemDetails,
+            [
+                ScriptedResponse::Timeout,
+                ScriptedResponse::MalformedJson,
+                ScriptedResponse::Status(503),
+                ScriptedResponse::Delay(Duration::from_secs(60)),
+            ],
+        );
+
+        assert!(matches!(
+            mock.all_item_details().await,
+            Err(RoliError::DeadlineExceeded)
+        ));
+        assert!(matches!(
+            mock.all_item_details().await,
+            Err(RoliError::MalformedResponse)
+        ));
+        assert!(mock.all_item_details().await.unwrap_err().is_maintenance());
+
+        let delayed = tokio::time::timeout(Duration::from_millis(10), mock.all_item_details());
+        assert!(delayed.await.is_err());
+
+        assert_eq!(mock.all_item_details().await.unwrap().len(), 2);
+        assert_eq!(mock.call_count(Endpoint::AllItemDetails), 5);
+    }
+}
@@ -1,11 +1,31 @@
+#[cfg(feature = "async-rt")]
+use crate::api::RoliApi;
+#[cfg(feature = "async-rt")]
+use crate::items::ItemInfoProvider;
+use crate::items::{Demand, ItemDetails};
+use crate::limits;
+use crate::players::Badge;
+#[cfg(feature = "async-rt")]
+use crate::players::PlayerProfile;
+#[cfg(feature = "async-rt")]
+use crate::streams::Pacing;
+use crate::util::SeenWindow;
 use crate::Client;
+use crate::Endpoint;
 use crate::RoliError;
-use reqwest::header;
+use reqwest::{header, Method};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "async-rt")]
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Duration;
 
 const CREATE_TRADE_AD_API: &str = "https://www.rolimons.com/tradeapi/create";
 const RECENT_TRADE_ADS_API: &str = "https://www.rolimons.com/tradeadsapi/getrecentads";
 
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
 /// The optional request tags that can be used in place
 /// of items when making a trade ad.
 #[allow(missing_docs)]
@@ -47,12 +67,30 @@ impl TryFrom<u8> for RequestTag {
     }
 }
 
+impl RequestTag {
+    /// Returns whether `item` would fill this request tag if it were offered.
+    ///
+    /// `Any`, `Upgrade`, `Downgrade`, and `Adds` are satisfied by every item as they depend on
+    /// the rest of the trade. `Robux` and `Wishlist` are never satisfied by an item.
+    pub fn is_satisfied_by(&self, item: &ItemDetails) -> bool {
+        match self {
+            Self::Any | Self::Upgrade | Self::Downgrade | Self::Adds => true,
+            Self::Demand => item.demand >= Demand::High,
+            Self::Rares => item.rare,
+            Self::Rap => !item.valued,
+            Self::Projecteds => item.projected,
+            Self::Robux | Self::Wishlist => false,
+        }
+    }
+}
+
 /// A full (posted) trade ad.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Default)]
 pub struct TradeAd {
     /// The id of the trade ad.
     pub trade_id: u64,
     /// The timestamp of when the trade ad was created.
+    #[cfg_attr(feature = "rfc3339", serde(with = "crate::serde_helpers::rfc3339"))]
     pub timestamp: u64,
     /// The id of the user who created the trade ad.
     pub user_id: u64,
@@ -82,6 +120,307 @@ pub struct Request {
     pub tags: Vec<RequestTag>,
 }
 
+/// The owner of a trade ad, as looked up by [`enrich_ads_with_owners`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct AdOwner {
+    /// The total value of the owner's inventory, from
+    /// [`PlayerProfile::inventory_value`](crate::players::PlayerProfile::inventory_value).
+    pub value: u64,
+    /// The owner's Rolimons badges.
+    pub badges: Vec<Badge>,
+    /// Whether the owner has verified their account on Rolimons.
+    pub verified: bool,
+    /// Whether the owner is terminated.
+    pub terminated: bool,
+    /// Whether the owner has their inventory hidden.
+    pub privated: bool,
+    /// Whether the owner has premium.
+    pub premium: bool,
+}
+
+/// A [`TradeAd`] paired with details about the player who posted it.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct EnrichedTradeAd {
+    /// The trade ad itself.
+    pub trade_ad: TradeAd,
+    /// The owner of the trade ad, or `None` if they were not looked up.
+    pub owner: Option<AdOwner>,
+}
+
+/// Filters out trade ads that a user has already posted with the same content, so
+/// that matchers do not alert again every time an ad is bumped.
+///
+/// Ads are compared by their poster and [`TradeAd::content_fingerprint`], and are
+/// remembered in a [`SeenWindow`] bounded by `capacity` and `max_age`.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::trade_ads::RepostFilter;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let mut filter = RepostFilter::new(5000, Duration::from_secs(6 * 60 * 60));
+///
+/// loop {
+///     for trade_ad in filter.retain_new(client.recent_trade_ads().await?) {
+///         println!("New trade ad from {}", trade_ad.username);
+///     }
+///
+///     tokio::time::sleep(Duration::from_secs(60)).await;
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RepostFilter {
+    seen: SeenWindow<(u64, u64)>,
+}
+
+impl AdOwner {
+    #[cfg(feature = "async-rt")]
+    fn new(profile: &PlayerProfile, table: &impl ItemInfoProvider) -> Self {
+        Self {
+            value: profile.inventory_value(table),
+            badges: profile.badges.clone(),
+            verified: profile.verified,
+            terminated: profile.terminated,
+            privated: profile.privated,
+            premium: profile.premium,
+        }
+    }
+}
+
+impl TradeAd {
+    /// Returns whether the request side of this trade ad contains `item_id`.
+    pub fn requests_item_id(&self, item_id: u64) -> bool {
+        self.request.items.contains(&item_id)
+    }
+
+    /// Returns whether the request side of this trade ad contains `item`, either directly
+    /// or through a [`RequestTag`] the item satisfies.
+    pub fn requests_item(&self, item: &ItemDetails) -> bool {
+        self.requests_item_id(item.item_id)
+            || self
+                .request
+                .tags
+                .iter()
+                .any(|tag| tag.is_satisfied_by(item))
+    }
+
+    /// Returns a hash of the items, robux, and tags on both sides of this trade ad.
+    ///
+    /// The order of the items and tags does not matter, and the id, poster, and
+    /// timestamp of the ad are not included, so an ad that is deleted and posted again
+    /// has the same fingerprint. The hash is stable between runs and versions of
+    /// this crate, so it can be stored.
+    pub fn content_fingerprint(&self) -> u64 {
+        let mut offer_items = self.offer.items.clone();
+        offer_items.sort_unstable();
+
+        let mut request_items = self.request.items.clone();
+        request_items.sort_unstable();
+
+        let mut request_tags = self
+            .request
+            .tags
+            .iter()
+            .map(|tag| *tag as u64)
+            .collect::<Vec<_>>();
+        request_tags.sort_unstable();
+
+        let mut hash = FNV_OFFSET_BASIS;
+
+        // The lengths separate the lists, so an id cannot move from one list to
+        // another without changing the hash.
+        for list in [&offer_items, &request_items, &request_tags] {
+            hash = fnv1a(hash, list.len() as u64);
+
+            for x in list {
+                hash = fnv1a(hash, *x);
+            }
+        }
+
+        fnv1a(hash, self.offer.robux.map_or(0, |robux| robux + 1))
+    }
+}
+
+impl RepostFilter {
+    /// Creates a filter that remembers at most `capacity` ads, each for at most `max_age`.
+    pub fn new(capacity: usize, max_age: Duration) -> Self {
+        Self {
+            seen: SeenWindow::new(capacity, max_age),
+        }
+    }
+
+    /// Returns `true` if the user who posted `trade_ad` has not recently posted an ad
+    /// with the same content, and remembers it either way.
+    pub fn is_new(&mut self, trade_ad: &TradeAd) -> bool {
+        self.seen
+            .insert((trade_ad.user_id, trade_ad.content_fingerprint()))
+    }
+
+    /// Returns the trade ads in `trade_ads` for which [`RepostFilter::is_new`] is `true`,
+    /// in the same order.
+    pub fn retain_new(&mut self, trade_ads: Vec<TradeAd>) -> Vec<TradeAd> {
+        trade_ads
+            .into_iter()
+            .filter(|trade_ad| self.is_new(trade_ad))
+            .collect()
+    }
+}
+
+/// The trade ads posted after a given trade ad, as returned by
+/// [`Client::recent_trade_ads_since`] and [`since`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TradeAdsSince {
+    /// The trade ads with a higher trade id than the cutoff, oldest first.
+    pub trade_ads: Vec<TradeAd>,
+    /// Whether the recent trade ads reached back to the cutoff.
+    ///
+    /// The endpoint only returns the last [`RECENT_TRADE_ADS_WINDOW`](crate::limits::RECENT_TRADE_ADS_WINDOW)
+    /// of trade ads. If this is false, the cutoff is older than that, and trade ads
+    /// posted between it and the oldest ad returned were missed.
+    pub is_complete: bool,
+}
+
+/// Returns the trade ads in `trade_ads` with a higher trade id than `trade_id`, oldest
+/// first, along with whether `trade_ads` reaches back far enough that none were missed.
+///
+/// Trade ad ids increase as ads are posted, so this is how
+/// [`Client::recent_trade_ads_since`] fetches only newer ads, as the endpoint does
+/// not support this itself.
+pub fn since(trade_ads: Vec<TradeAd>, trade_id: u64) -> TradeAdsSince {
+    let is_complete = trade_ads.iter().any(|x| x.trade_id <= trade_id);
+
+    let mut trade_ads = trade_ads
+        .into_iter()
+        .filter(|x| x.trade_id > trade_id)
+        .collect::<Vec<_>>();
+
+    trade_ads.sort_by_key(|x| x.trade_id);
+
+    TradeAdsSince {
+        trade_ads,
+        is_complete,
+    }
+}
+
+/// Returns the trade ads whose request side directly contains `item_id`.
+///
+/// Use [`find_requesters_for_item`] to also match trade ads that request a tag
+/// the item satisfies.
+pub fn find_requesters(item_id: u64, trade_ads: &[TradeAd]) -> Vec<&TradeAd> {
+    trade_ads
+        .iter()
+        .filter(|trade_ad| trade_ad.requests_item_id(item_id))
+        .collect()
+}
+
+/// Returns the trade ads whose request side contains `item`, either directly or through a
+/// [`RequestTag`] the item satisfies (e.g. `Projecteds` if the item is projected).
+pub fn find_requesters_for_item<'a>(
+    item: &ItemDetails,
+    trade_ads: &'a [TradeAd],
+) -> Vec<&'a TradeAd> {
+    trade_ads
+        .iter()
+        .filter(|trade_ad| trade_ad.requests_item(item))
+        .collect()
+}
+
+/// Looks up the owner of each trade ad so that ads from new, unverified, or low value
+/// accounts can be filtered out.
+///
+/// The player profile endpoint is expensive, so at most `max_lookups` distinct owners
+/// are looked up, one at a time and in the order their ads appear, waiting `interval`
+/// between lookups. Owners with more than one ad are only looked up once, and profiles
+/// are fetched with [`RoliApi::player_profile_cached`], so a [`Client`] does not look up
+/// the same owner again across calls within its player profile cache ttl. Ads whose owner was not looked up, or whose
+/// lookup failed, have an `owner` of `None`. Once a lookup returns
+/// [`RoliError::TooManyRequests`], no more lookups are made.
+///
+/// Inventory values are computed with `table`.
+///
+/// Only available with the `async-rt` feature.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::ItemDetailsTable;
+/// use roli::trade_ads;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+/// let trade_ads = client.recent_trade_ads().await?;
+///
+/// let interval = Duration::from_secs(5);
+///
+/// for enriched in trade_ads::enrich_ads_with_owners(&client, trade_ads, &table, 5, interval).await
+/// {
+///     if let Some(owner) = enriched.owner {
+///         if owner.verified && owner.value > 100_000 {
+///             println!("Trade {} looks safe", enriched.trade_ad.trade_id);
+///         }
+///     }
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async-rt")]
+pub async fn enrich_ads_with_owners(
+    api: &impl RoliApi,
+    trade_ads: Vec<TradeAd>,
+    table: &impl ItemInfoProvider,
+    max_lookups: usize,
+    interval: impl Into<Pacing>,
+) -> Vec<EnrichedTradeAd> {
+    let interval = interval.into();
+    let mut owners = HashMap::<u64, Option<AdOwner>>::new();
+    let mut rate_limited = false;
+
+    for trade_ad in trade_ads.iter() {
+        if rate_limited || owners.len() >= max_lookups || owners.contains_key(&trade_ad.user_id) {
+            continue;
+        }
+
+        if !owners.is_empty() {
+            tokio::time::sleep(interval.next_gap()).await;
+        }
+
+        let owner = match api.player_profile_cached(trade_ad.user_id).await {
+            Ok(profile) => Some(AdOwner::new(&profile, table)),
+            Err(RoliError::TooManyRequests) => {
+                rate_limited = true;
+                None
+            }
+            Err(_) => None,
+        };
+
+        owners.insert(trade_ad.user_id, owner);
+    }
+
+    trade_ads
+        .into_iter()
+        .map(|trade_ad| EnrichedTradeAd {
+            owner: owners.get(&trade_ad.user_id).cloned().flatten(),
+            trade_ad,
+        })
+        .collect()
+}
+
+/// Mixes the bytes of `x` into an FNV-1a `hash`.
+fn fnv1a(hash: u64, x: u64) -> u64 {
+    x.to_le_bytes().iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
 impl TryFrom<RequestRaw> for Request {
     type Error = RoliError;
 
@@ -89,7 +428,15 @@ impl TryFrom<RequestRaw> for Request {
         let mut tags = Vec::new();
 
         for tag in value.tags {
-            tags.push(RequestTag::try_from(tag)?);
+            match RequestTag::try_from(tag) {
+                Ok(tag) => tags.push(tag),
+                // Unknown tags are skipped unless strict parsing is enabled.
+                Err(e) => {
+                    if cfg!(feature = "strict-parsing") {
+                        return Err(e);
+                    }
+                }
+            }
         }
 
         Ok(Self {
@@ -101,6 +448,7 @@ impl TryFrom<RequestRaw> for Request {
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct RecentTradeAdsResponse {
     pub success: bool,
     #[serde(rename = "trade_ad_count")]
@@ -112,6 +460,7 @@ struct RecentTradeAdsResponse {
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict-parsing", serde(deny_unknown_fields))]
 struct RequestRaw {
     #[serde(default)]
     pub tags: Vec<u8>,
@@ -133,11 +482,122 @@ pub struct CreateTradeAdParams {
     pub request_tags: Vec<RequestTag>,
 }
 
+/// A Rolimons trade ad posting rule broken by a [`CreateTradeAdParams`], as returned
+/// by [`CreateTradeAdParams::violations`].
+///
+/// Rolimons rejects these ads with status code 400, which is indistinguishable from
+/// [`RoliError::CooldownNotExpired`] and still uses up the poster's cooldown.
+#[derive(
+    thiserror::Error, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum TradeAdViolation {
+    /// No items are offered.
+    #[error("No Items Offered")]
+    NoOfferItems,
+    /// More than [`limits::TRADE_AD_MAX_OFFER_ITEMS`](crate::limits::TRADE_AD_MAX_OFFER_ITEMS)
+    /// items are offered. Contains the amount offered.
+    #[error("Too Many Items Offered {0}")]
+    TooManyOfferItems(usize),
+    /// No items or request tags are requested.
+    #[error("Nothing Requested")]
+    NoRequest,
+    /// More than [`limits::TRADE_AD_MAX_REQUEST_SLOTS`](crate::limits::TRADE_AD_MAX_REQUEST_SLOTS)
+    /// items and request tags are requested. Contains the amount requested.
+    #[error("Too Many Requests {0}")]
+    TooManyRequests(usize),
+    /// An item is both offered and requested. Contains the item id.
+    #[error("Requests Offered Item {0}")]
+    RequestsOfferedItem(u64),
+    /// An item is requested more than once. Contains the item id.
+    #[error("Duplicate Requested Item {0}")]
+    DuplicateRequestItem(u64),
+    /// A request tag is used more than once.
+    #[error("Duplicate Request Tag {0:?}")]
+    DuplicateRequestTag(RequestTag),
+}
+
+impl CreateTradeAdParams {
+    /// Returns every Rolimons posting rule the trade ad breaks, or an empty vector if
+    /// it can be posted.
+    ///
+    /// Offering several copies of the same item is allowed.
+    pub fn violations(&self) -> Vec<TradeAdViolation> {
+        let mut violations = Vec::new();
+
+        if self.offer_item_ids.is_empty() {
+            violations.push(TradeAdViolation::NoOfferItems);
+        }
+
+        if self.offer_item_ids.len() > limits::TRADE_AD_MAX_OFFER_ITEMS {
+            violations.push(TradeAdViolation::TooManyOfferItems(
+                self.offer_item_ids.len(),
+            ));
+        }
+
+        let request_slots = self.request_item_ids.len() + self.request_tags.len();
+
+        if request_slots == 0 {
+            violations.push(TradeAdViolation::NoRequest);
+        }
+
+        if request_slots > limits::TRADE_AD_MAX_REQUEST_SLOTS {
+            violations.push(TradeAdViolation::TooManyRequests(request_slots));
+        }
+
+        let mut requested = HashSet::new();
+
+        for item_id in &self.request_item_ids {
+            if !requested.insert(*item_id) {
+                violations.push(TradeAdViolation::DuplicateRequestItem(*item_id));
+            } else if self.offer_item_ids.contains(item_id) {
+                violations.push(TradeAdViolation::RequestsOfferedItem(*item_id));
+            }
+        }
+
+        let mut tags = HashSet::new();
+
+        for tag in &self.request_tags {
+            if !tags.insert(*tag) {
+                violations.push(TradeAdViolation::DuplicateRequestTag(*tag));
+            }
+        }
+
+        violations
+    }
+
+    /// Returns how many more items or request tags can be requested before the ad hits
+    /// [`limits::TRADE_AD_MAX_REQUEST_SLOTS`]. Items and tags share the same slots, so
+    /// an ad requesting 3 items can only add 1 tag.
+    pub fn remaining_request_slots(&self) -> usize {
+        limits::TRADE_AD_MAX_REQUEST_SLOTS
+            .saturating_sub(self.request_item_ids.len() + self.request_tags.len())
+    }
+
+    /// Returns whether `tag` can be added to the request without breaking a posting
+    /// rule, which is when the website would still let it be selected: there is a
+    /// free request slot and the tag is not already requested.
+    pub fn can_add_request_tag(&self, tag: RequestTag) -> bool {
+        self.remaining_request_slots() > 0 && !self.request_tags.contains(&tag)
+    }
+
+    /// Returns the first of [`CreateTradeAdParams::violations`] as
+    /// [`RoliError::TradeAdViolation`], if there are any.
+    pub fn validate(&self) -> Result<(), RoliError> {
+        match self.violations().into_iter().next() {
+            Some(violation) => Err(RoliError::TradeAdViolation(violation)),
+            None => Ok(()),
+        }
+    }
+}
+
 impl Client {
     /// Creates a trade ad with the given details.
     ///
     /// Note that the current ad limit is 55 per 24 hours, and the
-    /// cooldown is 15 minutes.
+    /// cooldown is 15 minutes (see [`crate::limits`]).
+    ///
+    /// Returns [`RoliError::TradeAdViolation`] without making a request if the ad
+    /// breaks a posting rule (see [`CreateTradeAdParams::violations`]).
     ///
     /// Requires authentication.
     ///
@@ -167,66 +627,35 @@ impl Client {
         &self,
         create_trade_ad_params: CreateTradeAdParams,
     ) -> Result<(), RoliError> {
-        let mut headers = header::HeaderMap::new();
+        create_trade_ad_params.validate()?;
 
-        headers.insert(
-            header::USER_AGENT,
-            header::HeaderValue::from_static(
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:101.0) Gecko/20100101 Firefox/101.0",
-            ),
-        );
-
-        headers.insert(
-            header::CONNECTION,
-            header::HeaderValue::from_static("keep-alive"),
-        );
-
-        headers.insert(
-            header::CONTENT_TYPE,
-            header::HeaderValue::from_static("application/json;charset=utf-8"),
-        );
+        let request = self
+            .authenticated_request(Method::POST, CREATE_TRADE_AD_API)?
+            .json(&create_trade_ad_params);
 
-        // if the roli_verification is set, add it to the headers
-        // otherwise, return RoliError::RoliVerificationNotSet
-        match &self.roli_verification {
-            Some(roli_verification) => {
-                let header_safe = match header::HeaderValue::from_str(&format!(
-                    "_RoliVerification={}",
-                    roli_verification
-                )) {
-                    Ok(x) => x,
-                    Err(_) => return Err(RoliError::RoliVerificationContainsInvalidCharacters),
-                };
-
-                headers.insert(header::COOKIE, header_safe);
-            }
-            None => {
-                return Err(RoliError::RoliVerificationNotSet);
+        if let Some(trade_ad_budget) = &self.trade_ad_budget {
+            if !trade_ad_budget.try_acquire() {
+                return Err(RoliError::CooldownNotExpired);
             }
         }
 
-        let result = self
-            .reqwest_client
-            .post(CREATE_TRADE_AD_API)
-            .headers(headers)
-            .json(&create_trade_ad_params)
-            .send()
-            .await;
-
-        match result {
-            Ok(resp) => {
-                let status_code = resp.status().as_u16();
-                match status_code {
-                    201 => Ok(()),
-                    400 => Err(RoliError::CooldownNotExpired),
-                    422 => Err(RoliError::RoliVerificationInvalidOrExpired),
-                    429 => Err(RoliError::TooManyRequests),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
+        self.instrument(Endpoint::CreateTradeAd, async {
+            let (status_code, _) = match self.send(Endpoint::CreateTradeAd, request).await {
+                Ok(x) => x,
+                Err(e) => return (Err(e), None),
+            };
 
-            Err(e) => Err(RoliError::ReqwestError(e)),
-        }
+            let result = match status_code {
+                201 => Ok(()),
+                400 => Err(RoliError::CooldownNotExpired),
+                422 => Err(RoliError::RoliVerificationInvalidOrExpired),
+                429 => Err(RoliError::TooManyRequests),
+                _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
+            };
+
+            (result, Some(status_code))
+        })
+        .await
     }
 
     /// Fetches all trade ads made in the last 3 minutes.
@@ -282,16 +711,10 @@ impl Client {
     /// Ok(())
     /// # }
     /// ```
-
     pub async fn recent_trade_ads(&self) -> Result<Vec<TradeAd>, RoliError> {
         let mut headers = header::HeaderMap::new();
 
-        headers.insert(
-            header::USER_AGENT,
-            header::HeaderValue::from_static(
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:101.0) Gecko/20100101 Firefox/101.0",
-            ),
-        );
+        headers.insert(header::USER_AGENT, self.user_agent());
 
         headers.insert(
             header::CONNECTION,
@@ -303,50 +726,330 @@ impl Client {
             header::HeaderValue::from_static("application/json;charset=utf-8"),
         );
 
-        let result = self
+        let request = self
             .reqwest_client
             .get(RECENT_TRADE_ADS_API)
-            .headers(headers)
-            .send()
-            .await;
-
-        match result {
-            Ok(response) => {
-                let status_code = response.status().as_u16();
-
-                match status_code {
-                    200 => {
-                        let raw = match response.json::<RecentTradeAdsResponse>().await {
-                            Ok(x) => x,
-                            Err(_) => return Err(RoliError::MalformedResponse),
-                        };
-
-                        let mut trade_ads = Vec::new();
-
-                        for (trade_id, timestamp, user_id, username, offer, request_raw) in
-                            raw.trade_ads
-                        {
-                            let request = Request::try_from(request_raw)?;
-
-                            trade_ads.push(TradeAd {
-                                trade_id,
-                                timestamp,
-                                user_id,
-                                username,
-                                offer,
-                                request,
-                            });
-                        }
-
-                        Ok(trade_ads)
-                    }
-                    429 => Err(RoliError::TooManyRequests),
-                    500 => Err(RoliError::InternalServerError),
-                    _ => Err(RoliError::UnidentifiedStatusCode(status_code)),
-                }
-            }
+            .headers(headers);
+
+        let raw = self
+            .request_json::<RecentTradeAdsResponse>(Endpoint::RecentTradeAds, request)
+            .await?;
+
+        let mut trade_ads = Vec::new();
+
+        for (trade_id, timestamp, user_id, username, offer, request_raw) in raw.trade_ads {
+            let request = Request::try_from(request_raw)?;
 
-            Err(e) => Err(RoliError::ReqwestError(e)),
+            trade_ads.push(TradeAd {
+                trade_id,
+                timestamp,
+                user_id,
+                username,
+                offer,
+                request,
+            });
         }
+
+        Ok(trade_ads)
+    }
+
+    /// Fetches the recent trade ads posted after the trade ad with id `trade_id`,
+    /// oldest first.
+    ///
+    /// The endpoint has no parameter for this, so every recent trade ad is fetched and
+    /// filtered with [`since`]. Check [`TradeAdsSince::is_complete`] to find out whether
+    /// `trade_id` fell out of the recent window before this call, in which case some
+    /// trade ads were missed.
+    ///
+    /// Does not require authentication.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::error::Error;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = roli::ClientBuilder::new().build();
+    /// let mut last_trade_id = 0;
+    ///
+    /// let newer = client.recent_trade_ads_since(last_trade_id).await?;
+    ///
+    /// if !newer.is_complete {
+    ///     println!("Some trade ads were missed");
+    /// }
+    ///
+    /// if let Some(trade_ad) = newer.trade_ads.last() {
+    ///     last_trade_id = trade_ad.trade_id;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn recent_trade_ads_since(&self, trade_id: u64) -> Result<TradeAdsSince, RoliError> {
+        Ok(since(self.recent_trade_ads().await?, trade_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "async-rt")]
+    use crate::items::ItemDetailsTable;
+    #[cfg(feature = "async-rt")]
+    use crate::testing::{Faker, MockClient};
+
+    fn trade_ad(trade_id: u64, items: Vec<u64>, tags: Vec<RequestTag>) -> TradeAd {
+        TradeAd {
+            trade_id,
+            request: Request { items, tags },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_since() {
+        let trade_ads = vec![
+            trade_ad(4, vec![], vec![]),
+            trade_ad(2, vec![], vec![]),
+            trade_ad(3, vec![], vec![]),
+        ];
+
+        let newer = since(trade_ads.clone(), 2);
+        assert!(newer.is_complete);
+        assert_eq!(
+            newer
+                .trade_ads
+                .iter()
+                .map(|x| x.trade_id)
+                .collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+
+        let newer = since(trade_ads, 1);
+        assert!(!newer.is_complete);
+        assert_eq!(newer.trade_ads.len(), 3);
+    }
+
+    #[test]
+    fn test_find_requesters() {
+        let trade_ads = vec![
+            trade_ad(1, vec![100], vec![]),
+            trade_ad(2, vec![200], vec![]),
+            trade_ad(3, vec![], vec![RequestTag::Any]),
+        ];
+
+        let requesters = find_requesters(100, &trade_ads);
+
+        assert_eq!(requesters.len(), 1);
+        assert_eq!(requesters[0].trade_id, 1);
+    }
+
+    #[test]
+    fn test_repost_filter() {
+        let original = TradeAd {
+            user_id: 1,
+            ..trade_ad(1, vec![100, 200], vec![RequestTag::Any, RequestTag::Adds])
+        };
+        let bumped = TradeAd {
+            user_id: 1,
+            ..trade_ad(2, vec![200, 100], vec![RequestTag::Adds, RequestTag::Any])
+        };
+        let other_user = TradeAd {
+            user_id: 2,
+            ..bumped.clone()
+        };
+
+        assert_eq!(original.content_fingerprint(), bumped.content_fingerprint());
+        assert_ne!(
+            original.content_fingerprint(),
+            trade_ad(3, vec![100], vec![RequestTag::Any]).content_fingerprint()
+        );
+
+        let mut filter = RepostFilter::new(10, Duration::from_secs(60));
+        let new = filter.retain_new(vec![original, bumped, other_user]);
+
+        assert_eq!(
+            new.iter().map(|x| x.trade_id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(new[1].user_id, 2);
+    }
+
+    #[test]
+    fn test_find_requesters_for_item() {
+        let trade_ads = vec![
+            trade_ad(1, vec![100], vec![]),
+            trade_ad(2, vec![], vec![RequestTag::Projecteds]),
+            trade_ad(3, vec![], vec![RequestTag::Rares]),
+            trade_ad(4, vec![], vec![RequestTag::Robux]),
+        ];
+
+        let item = ItemDetails {
+            item_id: 100,
+            projected: true,
+            ..Default::default()
+        };
+
+        let trade_ids = find_requesters_for_item(&item, &trade_ads)
+            .iter()
+            .map(|trade_ad| trade_ad.trade_id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(trade_ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_trade_ad_violations() {
+        let mut params = CreateTradeAdParams {
+            player_id: 1,
+            offer_item_ids: vec![1, 1],
+            request_item_ids: vec![2],
+            request_tags: vec![RequestTag::Any],
+        };
+
+        assert!(params.validate().is_ok());
+
+        params.request_item_ids = vec![1, 2, 2];
+        params.request_tags = vec![RequestTag::Any, RequestTag::Any];
+
+        assert_eq!(
+            params.violations(),
+            vec![
+                TradeAdViolation::TooManyRequests(5),
+                TradeAdViolation::RequestsOfferedItem(1),
+                TradeAdViolation::DuplicateRequestItem(2),
+                TradeAdViolation::DuplicateRequestTag(RequestTag::Any),
+            ]
+        );
+
+        let params = CreateTradeAdParams::default();
+
+        assert_eq!(
+            params.violations(),
+            vec![TradeAdViolation::NoOfferItems, TradeAdViolation::NoRequest]
+        );
+        assert!(matches!(
+            params.validate(),
+            Err(RoliError::TradeAdViolation(TradeAdViolation::NoOfferItems))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_trade_ad_keeps_budget() {
+        let client = crate::ClientBuilder::new()
+            .set_trade_ads_per_day(Some(1))
+            .build();
+
+        let params = CreateTradeAdParams {
+            player_id: 1,
+            offer_item_ids: vec![1],
+            request_item_ids: vec![2],
+            request_tags: vec![],
+        };
+
+        assert!(matches!(
+            client.create_trade_ad(params).await,
+            Err(RoliError::RoliVerificationNotSet)
+        ));
+        assert!(client.trade_ad_budget.as_ref().unwrap().try_acquire());
+    }
+
+    #[test]
+    fn test_request_slots() {
+        let params =
+            |request_item_ids: Vec<u64>, request_tags: Vec<RequestTag>| CreateTradeAdParams {
+                player_id: 1,
+                offer_item_ids: vec![1],
+                request_item_ids,
+                request_tags,
+            };
+
+        // Every combination of items and tags that fills the four slots is allowed.
+        for item_count in 0..=4u64 {
+            let tags = [
+                RequestTag::Any,
+                RequestTag::Demand,
+                RequestTag::Rares,
+                RequestTag::Robux,
+            ];
+            let full = params(
+                (2..2 + item_count).collect(),
+                tags[..4 - item_count as usize].to_vec(),
+            );
+
+            assert!(full.validate().is_ok());
+            assert_eq!(full.remaining_request_slots(), 0);
+            assert!(!full.can_add_request_tag(RequestTag::Adds));
+        }
+
+        let mut three_items = params(vec![2, 3, 4], Vec::new());
+        assert_eq!(three_items.remaining_request_slots(), 1);
+        assert!(three_items.can_add_request_tag(RequestTag::Upgrade));
+
+        three_items.request_tags.push(RequestTag::Upgrade);
+        three_items.request_tags.push(RequestTag::Downgrade);
+        assert_eq!(
+            three_items.violations(),
+            vec![TradeAdViolation::TooManyRequests(5)]
+        );
+
+        let one_tag = params(Vec::new(), vec![RequestTag::Any]);
+        assert!(one_tag.validate().is_ok());
+        assert!(!one_tag.can_add_request_tag(RequestTag::Any));
+        assert!(one_tag.can_add_request_tag(RequestTag::Wishlist));
+    }
+
+    #[test]
+    fn test_request_tag_is_satisfied_by() {
+        let item = ItemDetails {
+            valued: true,
+            demand: Demand::Amazing,
+            ..Default::default()
+        };
+
+        assert!(RequestTag::Any.is_satisfied_by(&item));
+        assert!(RequestTag::Demand.is_satisfied_by(&item));
+        assert!(!RequestTag::Rap.is_satisfied_by(&item));
+        assert!(!RequestTag::Rares.is_satisfied_by(&item));
+        assert!(!RequestTag::Wishlist.is_satisfied_by(&item));
+    }
+
+    #[cfg(feature = "async-rt")]
+    #[tokio::test]
+    async fn test_enrich_ads_with_owners() {
+        let mock = MockClient::new();
+
+        let mut faker = Faker::new(1);
+        let owner = PlayerProfile {
+            user_id: 1,
+            verified: true,
+            ..faker.player_profile()
+        };
+        let table = ItemDetailsTable::new(faker.items(5));
+        mock.insert_player_profile(owner.clone());
+
+        let trade_ads = [1, 1, 2, 3]
+            .into_iter()
+            .map(|user_id| TradeAd {
+                user_id,
+                ..Default::default()
+            })
+            .collect();
+
+        let start = std::time::Instant::now();
+        let interval = Duration::from_millis(20);
+        let enriched = enrich_ads_with_owners(&mock, trade_ads, &table, 2, interval).await;
+
+        // The second lookup waits for the interval after the first.
+        assert!(start.elapsed() >= interval);
+
+        let expected = AdOwner::new(&owner, &table);
+        assert_eq!(enriched[0].owner.as_ref(), Some(&expected));
+        assert_eq!(enriched[1].owner.as_ref(), Some(&expected));
+        assert!(expected.verified);
+        // User 2 has no profile in the mock and user 3 is past the lookup limit.
+        assert_eq!(enriched[2].owner, None);
+        assert_eq!(enriched[3].owner, None);
+        assert_eq!(mock.call_count(Endpoint::PlayerProfile), 2);
     }
 }
@@ -0,0 +1,158 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// A set of recently seen keys, bounded by both age and capacity.
+///
+/// This is the dedup structure used for responses without an increasing id to keep a
+/// cursor of (such as deals activity), for consumers that poll the endpoints themselves. A key is forgotten once it is older than `max_age`, or
+/// once `capacity` newer keys have been inserted after it, whichever comes first.
+///
+/// Trade ads are unique by id, but sales and deals activity are best keyed by a
+/// combination of fields, such as `(item_id, timestamp)`.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::util::SeenWindow;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let mut seen = SeenWindow::new(1000, Duration::from_secs(3600));
+///
+/// loop {
+///     for activity in client.deals_activity().await? {
+///         if let roli::deals::Activity::PriceUpdate(x) = activity {
+///             if seen.insert((x.item_id, x.timestamp, x.price)) {
+///                 println!("New price update: {:?}", x);
+///             }
+///         }
+///     }
+///
+///     tokio::time::sleep(Duration::from_secs(10)).await;
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SeenWindow<K> {
+    capacity: usize,
+    max_age: Duration,
+    seen_at: HashMap<K, Instant>,
+    order: VecDeque<K>,
+}
+
+impl<K: Eq + Hash + Clone> SeenWindow<K> {
+    /// Creates an empty window that remembers at most `capacity` keys, each for at
+    /// most `max_age`.
+    pub fn new(capacity: usize, max_age: Duration) -> Self {
+        Self {
+            capacity,
+            max_age,
+            seen_at: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Marks `key` as seen, returning `true` if it was not already in the window.
+    ///
+    /// Inserting a key that is already in the window does not refresh its age.
+    pub fn insert(&mut self, key: K) -> bool {
+        self.insert_at(key, Instant::now())
+    }
+
+    /// Returns whether `key` is in the window.
+    pub fn contains(&self, key: &K) -> bool {
+        self.seen_at
+            .get(key)
+            .is_some_and(|seen_at| seen_at.elapsed() <= self.max_age)
+    }
+
+    /// Returns the amount of keys in the window, including expired keys that have
+    /// not been evicted by an insert yet.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns whether the window is empty.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Forgets every key.
+    pub fn clear(&mut self) {
+        self.seen_at.clear();
+        self.order.clear();
+    }
+
+    fn insert_at(&mut self, key: K, now: Instant) -> bool {
+        self.evict(now);
+
+        if self.capacity == 0 || self.seen_at.contains_key(&key) {
+            return false;
+        }
+
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen_at.remove(&oldest);
+            }
+        }
+
+        self.seen_at.insert(key.clone(), now);
+        self.order.push_back(key);
+
+        true
+    }
+
+    fn evict(&mut self, now: Instant) {
+        while let Some(oldest) = self.order.front() {
+            let expired = self
+                .seen_at
+                .get(oldest)
+                .is_none_or(|seen_at| now.duration_since(*seen_at) > self.max_age);
+
+            if !expired {
+                break;
+            }
+
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen_at.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seen_window_capacity() {
+        let mut seen = SeenWindow::new(2, Duration::from_secs(60));
+
+        assert!(seen.insert(1));
+        assert!(seen.insert(2));
+        assert!(!seen.insert(1));
+
+        assert!(seen.insert(3));
+        assert_eq!(seen.len(), 2);
+        assert!(!seen.contains(&1));
+        assert!(seen.contains(&2));
+        assert!(seen.contains(&3));
+    }
+
+    #[test]
+    fn test_seen_window_max_age() {
+        let mut seen = SeenWindow::new(10, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(seen.insert_at((1, 100), start));
+        assert!(!seen.insert_at((1, 100), start + Duration::from_secs(60)));
+        assert!(seen.insert_at((2, 100), start + Duration::from_secs(61)));
+
+        // The first key expired, so it is evicted and counts as new again.
+        assert_eq!(seen.len(), 1);
+        assert!(seen.insert_at((1, 100), start + Duration::from_secs(61)));
+    }
+}
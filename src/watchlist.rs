@@ -0,0 +1,591 @@
+use crate::items::{ItemChange, ItemDetails, ItemDetailsTable};
+use crate::RoliError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A list of items to watch and the thresholds to alert at, usually loaded from a
+/// file so that the people operating a bot can change it without touching code.
+///
+/// Items are referred to by id, or by name or acronym (case insensitive), which
+/// are turned into ids with [`WatchList::resolve`].
+///
+/// In JSON, a watch list looks like
+/// `{"items": [{"item": 1365767, "max_price": 100000}, {"item": "TI", "min_deal_percent": 20}]}`.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items::ItemDetailsTable;
+/// use roli::watchlist::WatchList;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+/// let table = ItemDetailsTable::new(client.all_item_details().await?);
+///
+/// let watch_list = WatchList::from_file("watchlist.json")?.resolve(&table)?;
+///
+/// for sale in client.recent_sales().await? {
+///     if let Some(thresholds) = watch_list.get(sale.item_id) {
+///         let value = table.get(sale.item_id).map_or(0, |item| item.value_or_rap());
+///
+///         if thresholds.is_triggered(sale.sale_price, value) {
+///             println!("Watched item {} sold for {}", sale.item_id, sale.sale_price);
+///         }
+///     }
+/// }
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchList {
+    /// The watched items.
+    pub items: Vec<WatchTarget>,
+}
+
+/// A single watched item in a [`WatchList`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WatchTarget {
+    /// The item being watched.
+    pub item: ItemRef,
+    /// When to alert for the item.
+    #[serde(flatten)]
+    pub thresholds: Thresholds,
+}
+
+/// A reference to an item in a [`WatchList`], either by id or by name.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ItemRef {
+    /// The id of the item.
+    Id(u64),
+    /// The name or acronym of the item, compared case insensitively.
+    Name(String),
+}
+
+/// The thresholds a price has to cross for a watched item to alert.
+///
+/// If no threshold is set, every price alerts.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct Thresholds {
+    /// Alert when the price is at or below this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<u64>,
+    /// Alert when the price is at least this percentage below the item's value (or rap).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_deal_percent: Option<u8>,
+}
+
+/// Rules for alerting on changes to the value and rap of watched items, checked by a
+/// [`ValueWatcher`].
+///
+/// Only valued items are checked, as the rules compare against an item's value.
+/// If no rule is set, nothing alerts.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct ValueAlertRule {
+    /// Alert when an item's value moves by at least this percentage (up or down) from
+    /// the value it had at its last value alert, or when it was first seen.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_value_change_percent: Option<u8>,
+    /// Alert when an item's rap differs from its value by at least this percentage of
+    /// the value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_rap_divergence_percent: Option<u8>,
+    /// How many percentage points below `min_rap_divergence_percent` the divergence has
+    /// to fall before the item can alert for it again.
+    pub hysteresis_percent: u8,
+}
+
+/// An alert raised by a [`ValueWatcher`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ValueAlert {
+    /// The item's value moved by at least [`ValueAlertRule::min_value_change_percent`].
+    ValueChanged {
+        /// The id of the item.
+        item_id: u64,
+        /// The value the change is measured from.
+        previous_value: u64,
+        /// The new value.
+        value: u64,
+        /// The change as a percentage of `previous_value`, negative when the value dropped.
+        change_percent: f64,
+    },
+    /// The item's rap diverged from its value by at least
+    /// [`ValueAlertRule::min_rap_divergence_percent`].
+    RapDiverged {
+        /// The id of the item.
+        item_id: u64,
+        /// The value of the item.
+        value: u64,
+        /// The rap of the item.
+        rap: u64,
+        /// The difference between rap and value as a percentage of the value, negative
+        /// when the rap is below the value.
+        divergence_percent: f64,
+    },
+}
+
+/// Checks [`ItemChange`]s against a [`ValueAlertRule`], without alerting twice for the
+/// same move.
+///
+/// A value alert resets the value later changes are measured from, so the item only
+/// alerts again once its value moves by the threshold again. A divergence alert is
+/// raised when the divergence crosses its threshold, and is not raised again until the
+/// divergence falls below the threshold minus [`ValueAlertRule::hysteresis_percent`].
+///
+/// Each watcher keeps its own rule and state, so every subscriber of a bot can have
+/// their own thresholds.
+///
+/// # Example
+/// ```no_run
+/// # use std::error::Error;
+/// use roli::items;
+/// use roli::watchlist::{ValueAlertRule, ValueWatcher};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn Error>> {
+/// let client = roli::ClientBuilder::new().build();
+///
+/// let mut watcher = ValueWatcher::new(ValueAlertRule {
+///     min_value_change_percent: Some(5),
+///     min_rap_divergence_percent: Some(20),
+///     hysteresis_percent: 5,
+/// })
+/// .set_items([1365767, 1028606]);
+///
+/// let mut old = client.all_item_details_snapshot().await?;
+///
+/// loop {
+///     tokio::time::sleep(roli::limits::ALL_ITEM_DETAILS_POLL_INTERVAL).await;
+///     let new = client.all_item_details_snapshot().await?;
+///
+///     for change in items::diff(&old.items, &new.items) {
+///         for alert in watcher.observe(&change) {
+///             println!("{:?}", alert);
+///         }
+///     }
+///
+///     old = new;
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValueWatcher {
+    rule: ValueAlertRule,
+    /// `None` watches every item.
+    item_ids: Option<HashSet<u64>>,
+    states: HashMap<u64, ValueAlertState>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct ValueAlertState {
+    baseline_value: u64,
+    diverged: bool,
+}
+
+/// A [`WatchList`] with every item resolved to an id, as returned by
+/// [`WatchList::resolve`].
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ResolvedWatchList {
+    thresholds: HashMap<u64, Thresholds>,
+}
+
+impl WatchList {
+    /// Parses a [`WatchList`] from a JSON string.
+    ///
+    /// Returns [`RoliError::InvalidConfig`] if the string is not a valid watch list.
+    pub fn from_json_str(json_str: &str) -> Result<Self, RoliError> {
+        serde_json::from_str(json_str).map_err(|e| RoliError::InvalidConfig(e.to_string()))
+    }
+
+    /// Parses a [`WatchList`] from a TOML string, where each item is an `[[items]]` table.
+    ///
+    /// Returns [`RoliError::InvalidConfig`] if the string is not a valid watch list.
+    #[cfg(feature = "config")]
+    pub fn from_toml_str(toml_str: &str) -> Result<Self, RoliError> {
+        toml::from_str(toml_str).map_err(|e| RoliError::InvalidConfig(e.to_string()))
+    }
+
+    /// Reads and parses a [`WatchList`] from a file.
+    ///
+    /// Files ending in `.toml` are parsed as TOML, which requires the `config` feature.
+    /// Every other file is parsed as JSON.
+    ///
+    /// Returns [`RoliError::InvalidConfig`] if the file cannot be read or is not a valid
+    /// watch list.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RoliError> {
+        let path = path.as_ref();
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| RoliError::InvalidConfig(e.to_string()))?;
+
+        if path.extension().is_some_and(|x| x == "toml") {
+            #[cfg(feature = "config")]
+            return Self::from_toml_str(&contents);
+
+            #[cfg(not(feature = "config"))]
+            return Err(RoliError::InvalidConfig(
+                "toml watch lists require the config feature".to_string(),
+            ));
+        }
+
+        Self::from_json_str(&contents)
+    }
+
+    /// Resolves every item in the watch list to an id using `table`.
+    ///
+    /// If an item is listed more than once, the last entry wins.
+    ///
+    /// Returns [`RoliError::InvalidConfig`] if an id is not in `table`, or if a name
+    /// matches no items or more than one item.
+    pub fn resolve(&self, table: &ItemDetailsTable) -> Result<ResolvedWatchList, RoliError> {
+        let mut thresholds = HashMap::with_capacity(self.items.len());
+
+        for target in &self.items {
+            let item_id = target.item.resolve(table)?;
+            thresholds.insert(item_id, target.thresholds);
+        }
+
+        Ok(ResolvedWatchList { thresholds })
+    }
+}
+
+impl ItemRef {
+    /// Returns the id of the item this refers to in `table`.
+    ///
+    /// Returns [`RoliError::InvalidConfig`] if an id is not in `table`, or if a name
+    /// matches no items or more than one item.
+    pub fn resolve(&self, table: &ItemDetailsTable) -> Result<u64, RoliError> {
+        match self {
+            Self::Id(item_id) => match table.contains(*item_id) {
+                true => Ok(*item_id),
+                false => Err(RoliError::InvalidConfig(format!(
+                    "unknown item id {}",
+                    item_id
+                ))),
+            },
+            Self::Name(name) => {
+                let mut matches = table.iter().filter(|item| is_named(item, name));
+
+                match (matches.next(), matches.next()) {
+                    (Some(item), None) => Ok(item.item_id),
+                    (None, _) => Err(RoliError::InvalidConfig(format!(
+                        "unknown item name {}",
+                        name
+                    ))),
+                    (Some(_), Some(_)) => Err(RoliError::InvalidConfig(format!(
+                        "ambiguous item name {}",
+                        name
+                    ))),
+                }
+            }
+        }
+    }
+}
+
+impl Thresholds {
+    /// Returns whether `price` crosses any of the thresholds, given the item's
+    /// `value` (or rap).
+    ///
+    /// Always returns true if no threshold is set.
+    pub fn is_triggered(&self, price: u64, value: u64) -> bool {
+        if self.max_price.is_none() && self.min_deal_percent.is_none() {
+            return true;
+        }
+
+        let below_max_price = self.max_price.is_some_and(|max_price| price <= max_price);
+
+        let deal = self.min_deal_percent.is_some_and(|min_deal_percent| {
+            value > 0 && price < value && (value - price) * 100 / value >= min_deal_percent as u64
+        });
+
+        below_max_price || deal
+    }
+}
+
+impl ResolvedWatchList {
+    /// Returns the thresholds for an item, or `None` if it is not watched.
+    pub fn get(&self, item_id: u64) -> Option<&Thresholds> {
+        self.thresholds.get(&item_id)
+    }
+
+    /// Returns whether an item is watched.
+    pub fn contains(&self, item_id: u64) -> bool {
+        self.thresholds.contains_key(&item_id)
+    }
+
+    /// Returns whether an item is watched and `price` crosses its thresholds.
+    pub fn is_triggered(&self, item_id: u64, price: u64, value: u64) -> bool {
+        self.get(item_id)
+            .is_some_and(|thresholds| thresholds.is_triggered(price, value))
+    }
+
+    /// Returns the amount of watched items.
+    pub fn len(&self) -> usize {
+        self.thresholds.len()
+    }
+
+    /// Returns whether no items are watched.
+    pub fn is_empty(&self) -> bool {
+        self.thresholds.is_empty()
+    }
+
+    /// Returns an iterator over the watched item ids and their thresholds, in no
+    /// particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &Thresholds)> {
+        self.thresholds
+            .iter()
+            .map(|(item_id, thresholds)| (*item_id, thresholds))
+    }
+}
+
+impl ValueWatcher {
+    /// Creates a watcher that checks every item against `rule`.
+    pub fn new(rule: ValueAlertRule) -> Self {
+        Self {
+            rule,
+            item_ids: None,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Only checks the items in `item_ids`, such as the ids of a [`ResolvedWatchList`].
+    pub fn set_items(mut self, item_ids: impl IntoIterator<Item = u64>) -> Self {
+        self.item_ids = Some(item_ids.into_iter().collect());
+        self
+    }
+
+    /// Returns the rule the watcher checks items against.
+    pub fn rule(&self) -> &ValueAlertRule {
+        &self.rule
+    }
+
+    /// Checks a change to an item and returns the alerts it raises, if any.
+    ///
+    /// The first change seen for an item measures value changes from the value in
+    /// [`ItemChange::previous`], or from the current value if the item is new.
+    pub fn observe(&mut self, change: &ItemChange) -> Vec<ValueAlert> {
+        let item = &change.current;
+        let mut alerts = Vec::new();
+
+        if !item.valued || item.value == 0 {
+            return alerts;
+        }
+
+        if self
+            .item_ids
+            .as_ref()
+            .is_some_and(|item_ids| !item_ids.contains(&item.item_id))
+        {
+            return alerts;
+        }
+
+        let state = self
+            .states
+            .entry(item.item_id)
+            .or_insert_with(|| ValueAlertState {
+                baseline_value: change
+                    .previous
+                    .as_ref()
+                    .filter(|previous| previous.valued && previous.value != 0)
+                    .map_or(item.value, |previous| previous.value),
+                diverged: false,
+            });
+
+        if let Some(min_value_change_percent) = self.rule.min_value_change_percent {
+            let change_percent = percent_difference(item.value, state.baseline_value);
+
+            if change_percent.abs() >= min_value_change_percent as f64 {
+                alerts.push(ValueAlert::ValueChanged {
+                    item_id: item.item_id,
+                    previous_value: state.baseline_value,
+                    value: item.value,
+                    change_percent,
+                });
+
+                state.baseline_value = item.value;
+            }
+        }
+
+        if let Some(min_rap_divergence_percent) = self.rule.min_rap_divergence_percent {
+            let divergence_percent = percent_difference(item.rap, item.value);
+            let divergence = divergence_percent.abs();
+
+            if !state.diverged && divergence >= min_rap_divergence_percent as f64 {
+                alerts.push(ValueAlert::RapDiverged {
+                    item_id: item.item_id,
+                    value: item.value,
+                    rap: item.rap,
+                    divergence_percent,
+                });
+
+                state.diverged = true;
+            } else if state.diverged
+                && divergence
+                    < min_rap_divergence_percent as f64 - self.rule.hysteresis_percent as f64
+            {
+                state.diverged = false;
+            }
+        }
+
+        alerts
+    }
+}
+
+/// Returns how far `amount` is from `base`, as a percentage of `base`.
+fn percent_difference(amount: u64, base: u64) -> f64 {
+    (amount as f64 - base as f64) / base as f64 * 100.0
+}
+
+fn is_named(item: &ItemDetails, name: &str) -> bool {
+    item.item_name.eq_ignore_ascii_case(name)
+        || item
+            .acronym
+            .as_ref()
+            .is_some_and(|acronym| acronym.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> ItemDetailsTable {
+        ItemDetailsTable::new(vec![
+            ItemDetails {
+                item_id: 1,
+                item_name: "Test Item".to_string(),
+                acronym: Some("TI".to_string()),
+                ..Default::default()
+            },
+            ItemDetails {
+                item_id: 2,
+                item_name: "Other Item".to_string(),
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn test_resolve_json() {
+        let watch_list = WatchList::from_json_str(
+            r#"{"items": [
+                {"item": 2, "max_price": 500},
+                {"item": "ti", "min_deal_percent": 20}
+            ]}"#,
+        )
+        .unwrap()
+        .resolve(&table())
+        .unwrap();
+
+        assert_eq!(watch_list.len(), 2);
+        assert!(watch_list.is_triggered(2, 500, 0));
+        assert!(!watch_list.is_triggered(2, 501, 0));
+        assert!(watch_list.is_triggered(1, 800, 1000));
+        assert!(!watch_list.is_triggered(1, 900, 1000));
+        assert!(!watch_list.is_triggered(3, 0, 1000));
+    }
+
+    #[test]
+    fn test_resolve_unknown_item() {
+        let watch_list = WatchList {
+            items: vec![WatchTarget {
+                item: ItemRef::Name("Missing".to_string()),
+                thresholds: Thresholds::default(),
+            }],
+        };
+
+        assert!(matches!(
+            watch_list.resolve(&table()),
+            Err(RoliError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_value_watcher() {
+        let change = |previous_value, value, rap| ItemChange {
+            previous: Some(ItemDetails {
+                item_id: 1,
+                valued: true,
+                value: previous_value,
+                ..Default::default()
+            }),
+            current: ItemDetails {
+                item_id: 1,
+                valued: true,
+                value,
+                rap,
+                ..Default::default()
+            },
+        };
+
+        let mut watcher = ValueWatcher::new(ValueAlertRule {
+            min_value_change_percent: Some(5),
+            min_rap_divergence_percent: Some(20),
+            hysteresis_percent: 5,
+        });
+
+        // Up 10% from the previous value, with rap 25% below it.
+        let alerts = watcher.observe(&change(1000, 1100, 825));
+        assert_eq!(alerts.len(), 2);
+        assert!(matches!(
+            alerts[0],
+            ValueAlert::ValueChanged {
+                previous_value: 1000,
+                value: 1100,
+                ..
+            }
+        ));
+        assert!(matches!(
+            alerts[1],
+            ValueAlert::RapDiverged { divergence_percent, .. } if divergence_percent == -25.0
+        ));
+
+        // Only 2% from the last alert, and still diverged, so nothing new.
+        assert!(watcher.observe(&change(1100, 1122, 880)).is_empty());
+
+        // The divergence falls below 15%, which re-arms it.
+        assert!(watcher.observe(&change(1122, 1122, 1000)).is_empty());
+        assert!(matches!(
+            watcher.observe(&change(1122, 1122, 1400))[..],
+            [ValueAlert::RapDiverged { .. }]
+        ));
+
+        let mut watcher = ValueWatcher::new(ValueAlertRule {
+            min_value_change_percent: Some(5),
+            ..Default::default()
+        })
+        .set_items([2]);
+        assert!(watcher.observe(&change(1000, 2000, 0)).is_empty());
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_from_toml_str() {
+        let watch_list = WatchList::from_toml_str(
+            r#"
+            [[items]]
+            item = "Other Item"
+
+            [[items]]
+            item = 1
+            max_price = 100
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            watch_list.items[0].item,
+            ItemRef::Name("Other Item".to_string())
+        );
+        assert_eq!(watch_list.items[0].thresholds, Thresholds::default());
+        assert_eq!(watch_list.items[1].thresholds.max_price, Some(100));
+    }
+}